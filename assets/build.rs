@@ -0,0 +1,11 @@
+fn main() {
+    // The prebuilt kernels shipped with intel_tex_2 include C++ objects that
+    // reference the C++ runtime, which rustc doesn't link on its own.
+    let os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let env = std::env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+    match (os.as_str(), env.as_str()) {
+        ("macos" | "ios", _) => println!("cargo:rustc-link-lib=c++"),
+        (_, "gnu") => println!("cargo:rustc-link-lib=stdc++"),
+        _ => {}
+    }
+}
@@ -0,0 +1,77 @@
+use std::ffi::CString;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use flight_core::{Board, RuleSet};
+use winit::platform::android::activity::AndroidApp;
+
+use crate::gpu::AdapterChoice;
+use crate::play;
+
+/// The activity the game runs in, kept from [`android_main`].
+static APP: OnceLock<AndroidApp> = OnceLock::new();
+
+/// Starts the game when the activity is created: the window of `assets
+/// play` on the classic board for four, full screen, its settings kept in
+/// the app's data directory and its themes among the APK's assets.
+///
+/// Built into `libassets.so` with `cargo ndk -t arm64-v8a build -p assets
+/// --lib --release` and packaged in an APK whose manifest names the
+/// library for a `NativeActivity`, the `themes` directory of its assets
+/// holding the themes to choose from.
+#[unsafe(no_mangle)]
+fn android_main(app: AndroidApp) {
+    APP.get_or_init(|| app);
+    let result = play::run(
+        None,
+        None,
+        Board::new(4),
+        RuleSet::default(),
+        rand::random(),
+        Duration::from_millis(150),
+        AdapterChoice::default(),
+    );
+    if let Err(error) = result {
+        log::error!("{error:#}");
+    }
+}
+
+/// The activity the game runs in.
+pub fn app() -> &'static AndroidApp {
+    APP.get().expect("android_main keeps the app")
+}
+
+/// Directory private to the app its files are kept in, none if Android
+/// gives it none.
+pub fn data_directory() -> Option<PathBuf> {
+    app().internal_data_path()
+}
+
+/// Contents of the asset packaged in the APK at `path`, relative to its
+/// `assets` directory.
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let missing = || io::Error::from(io::ErrorKind::NotFound);
+    let name = path.to_str().ok_or_else(missing)?;
+    let name = CString::new(name).map_err(|_| missing())?;
+    let mut asset = app().asset_manager().open(&name).ok_or_else(missing)?;
+    let mut bytes = vec![];
+    asset.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Paths of the files packaged in the APK's `directory`, without its
+/// subdirectories, none if it has none.
+pub fn list(directory: &Path) -> Vec<PathBuf> {
+    let Some(name) = directory.to_str().and_then(|d| CString::new(d).ok())
+    else {
+        return vec![];
+    };
+    let Some(entries) = app().asset_manager().open_dir(&name) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|entry| Some(directory.join(entry.into_string().ok()?)))
+        .collect()
+}
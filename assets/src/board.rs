@@ -0,0 +1,656 @@
+use std::f64::consts::{PI, TAU};
+
+use flight_core::{Cell as Shape, Metrics, Player as Quadrant, Spot};
+use vello::kurbo::{
+    self, Affine, BezPath, Circle, CircleSegment, Line, Point, Rect,
+    RoundedRect, Shape as _, Stroke, Vec2,
+};
+use vello::peniko::{Color, Gradient};
+
+use crate::canvas::Canvas;
+use crate::number::Number;
+use crate::pattern::Pattern;
+use crate::plane::Plane;
+use crate::theme::{BackdropKind, SpotShape, Theme};
+
+pub use flight_core::CellKind;
+
+pub trait Drawable {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme);
+}
+
+/// Draws a soft shadow as translucent fills of `spread(t)` for `t` from
+/// the full spread of 1 down toward 0, darkest where they all overlap.
+pub fn shadow<S: kurbo::Shape>(
+    canvas: &mut impl Canvas,
+    affine: Affine,
+    spread: impl Fn(f64) -> S,
+) {
+    const LAYERS: u32 = 4;
+    let color = Color::BLACK.with_alpha(0.4 / LAYERS as f32);
+    for layer in (1..=LAYERS).rev() {
+        let t = f64::from(layer) / f64::from(LAYERS);
+        canvas.fill(affine, color, &spread(t));
+    }
+}
+
+/// Fills an accent spot of the theme's shape on `center`, `radius` scaled
+/// by the theme's spot size.
+pub fn spot(
+    canvas: &mut impl Canvas,
+    affine: Affine,
+    theme: &Theme,
+    center: Point,
+    radius: f64,
+) {
+    let radius = radius * theme.spot_scale;
+    match theme.spot_shape.path(center, radius) {
+        Some(path) => canvas.fill(affine, theme.accent, &path),
+        None => canvas.fill(affine, theme.accent, &Circle::new(center, radius)),
+    }
+}
+
+/// Draws the first artwork, then the second over it.
+impl<A: Drawable, B: Drawable> Drawable for (&A, &B) {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        self.0.draw(canvas, theme);
+        self.1.draw(canvas, theme);
+    }
+}
+
+/// The whole board for a number of players, drawn under a root transform.
+pub struct Board {
+    affine: Affine,
+    layout: flight_core::Board,
+    arrows: bool,
+    numbers: bool,
+}
+
+impl Board {
+    /// Player counts seated by some board shape.
+    pub const PLAYERS: [usize; 4] = flight_core::Board::PLAYERS;
+
+    /// The classic board for `players`.
+    pub fn new(affine: Affine, players: usize) -> Self {
+        Self::from_layout(affine, flight_core::Board::new(players))
+    }
+
+    /// Draws a board of any shape and track layout.
+    pub fn from_layout(affine: Affine, layout: flight_core::Board) -> Self {
+        Self {
+            affine,
+            layout,
+            arrows: false,
+            numbers: false,
+        }
+    }
+
+    /// Marks every ring cell with the way planes travel and every shortcut
+    /// entry with the way it flies.
+    pub fn with_arrows(mut self, arrows: bool) -> Self {
+        self.arrows = arrows;
+        self
+    }
+
+    /// Labels every track cell with its index in the core track, for
+    /// pointing at exact cells.
+    pub fn with_numbers(mut self, numbers: bool) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    /// The cell drawn at `spot` with the arrows it shows, if any.
+    fn cell(
+        &self,
+        shape: Shape,
+        color: Color,
+        affine: Affine,
+        spot: Spot,
+    ) -> Cell {
+        let cell = Cell::new(shape, color, affine);
+        let track = self.layout.track();
+        let Some(index) = track.index(spot).filter(|_| self.arrows) else {
+            return cell;
+        };
+        // Headings are measured in the quadrant, where the cell is drawn.
+        let center = shape.center();
+        let heading = |target: Option<usize>| {
+            let target = affine.inverse() * track.get(target?)?.center;
+            Some((target - center).atan2())
+        };
+        let next = track.get(index).and_then(|cell| cell.next);
+        let shortcut = track.get(index).and_then(|cell| cell.shortcut);
+        cell.with_arrows(heading(next), heading(shortcut))
+    }
+}
+
+impl Drawable for Board {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        canvas.group(self.affine, |canvas| {
+            let metrics = *self.layout.metrics();
+            if let Some((image, brush)) = theme
+                .background_texture
+                .as_ref()
+                .and_then(|texture| texture.brush(metrics.scale()))
+            {
+                let side = self.layout.dimension();
+                let area = Rect::new(0.0, 0.0, side, side);
+                canvas.fill_image(Affine::IDENTITY, image, brush, &area);
+            }
+            if theme.hub {
+                Hub::new(&self.layout).draw(canvas, theme);
+            }
+            let edges = self.layout.edges();
+            let runways = self.layout.runways();
+            for (seat, affine) in self.layout.seats().into_iter().enumerate() {
+                let player = Player::new(theme.player(seat), affine, metrics)
+                    .with_runway(runways[seat]);
+                let cells = self.layout.cells(seat);
+                for (index, cell) in cells.into_iter().enumerate() {
+                    // Colors shift with the seat, wrapping around the
+                    // seats' own, each with its pattern.
+                    let seats = self.layout.track().colors();
+                    let cell = Shape {
+                        color: cell.color % seats,
+                        ..cell
+                    };
+                    let color = theme.player(cell.color);
+                    let spot = Spot::Ring { seat, index };
+                    self.cell(cell, color, affine, spot).draw(canvas, theme);
+                }
+                if self.layout.is_seated(seat) {
+                    player.draw_base(canvas, theme);
+                }
+                player.draw_edges(canvas, theme, &edges);
+                player.draw(canvas, theme);
+            }
+            // Shortcuts fly straight over the board between cell centers.
+            let stroke = theme
+                .shortcut_line
+                .stroke(theme.stroke_width, metrics.scale());
+            let track = self.layout.track();
+            for index in 0..track.ring_len() {
+                if let Some(path) = track.shortcut_path(index) {
+                    canvas.stroke(
+                        &stroke,
+                        Affine::IDENTITY,
+                        theme.stroke,
+                        &path,
+                    );
+                }
+            }
+            if self.numbers {
+                let height = metrics.cell_radius() * 0.9;
+                for (index, cell) in track.cells().iter().enumerate() {
+                    Number::new(Affine::IDENTITY, cell.center, index, height)
+                        .draw(canvas, theme);
+                }
+            }
+        });
+    }
+}
+
+/// The theme's backdrop over an area of the output, drawn before the board
+/// so it shows around and under it.
+pub struct Backdrop {
+    area: Rect,
+    /// Size of the board's cells relative to the default, sizing a motif
+    /// along with them.
+    scale: f64,
+}
+
+impl Backdrop {
+    /// The backdrop filling `area`, next to cells `scale` times the default
+    /// size.
+    pub fn new(area: Rect, scale: f64) -> Self {
+        Self { area, scale }
+    }
+}
+
+impl Drawable for Backdrop {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let Some(style) = &theme.backdrop else {
+            return;
+        };
+        let color = style.color.with_alpha(style.opacity());
+        match style.kind {
+            BackdropKind::Vignette => {
+                // Clear over the middle, darkening out to the corners.
+                let radius = self.area.size().to_vec2().hypot() / 2.0;
+                let gradient =
+                    Gradient::new_radial(self.area.center(), radius as f32)
+                        .with_stops(
+                            [
+                                (0.0, color.with_alpha(0.0)),
+                                (0.45, color.with_alpha(0.0)),
+                                (1.0, color),
+                            ]
+                            .as_slice(),
+                        );
+                canvas.fill_gradient(Affine::IDENTITY, &gradient, &self.area);
+            }
+            BackdropKind::Motif => {
+                let spacing = style.spacing * self.scale;
+                if spacing <= 0.0 {
+                    return;
+                }
+                // Rows step by half the spacing, every other one shifted
+                // half a column, and planes all fly up and to the right.
+                let silhouette = Plane::silhouette();
+                let columns = (self.area.width() / spacing).ceil() as usize + 1;
+                let rows =
+                    (self.area.height() * 2.0 / spacing).ceil() as usize + 1;
+                let mut motif = BezPath::new();
+                for row in 0..rows {
+                    let shift = (row % 2) as f64 * spacing / 2.0;
+                    for column in 0..columns {
+                        let center = Vec2::new(
+                            self.area.x0 + column as f64 * spacing + shift,
+                            self.area.y0 + row as f64 * spacing / 2.0,
+                        );
+                        let affine = Affine::translate(center)
+                            * Affine::rotate(-PI / 4.0)
+                            * Affine::scale(spacing * 0.2);
+                        motif.extend(affine * &silhouette);
+                    }
+                }
+                canvas.clip(Affine::IDENTITY, &self.area, |canvas| {
+                    canvas.fill(Affine::IDENTITY, color, &motif);
+                });
+            }
+            BackdropKind::Snowflakes | BackdropKind::Fireworks => {
+                let spacing = style.spacing * self.scale;
+                if spacing <= 0.0 {
+                    return;
+                }
+                let mark = match style.kind {
+                    BackdropKind::Snowflakes => SpotShape::Snowflake
+                        .path(Point::ZERO, 1.0)
+                        .expect("snowflakes are paths"),
+                    _ => Self::burst(),
+                };
+                // One mark per grid square, placed, sized and turned by a
+                // hash of the square so every render scatters them alike.
+                let columns = (self.area.width() / spacing).ceil() as u64;
+                let rows = (self.area.height() / spacing).ceil() as u64;
+                let mut marks = BezPath::new();
+                for row in 0..rows {
+                    for column in 0..columns {
+                        let random = |salt| scatter(row, column, salt);
+                        let center = Vec2::new(
+                            self.area.x0
+                                + (column as f64 + 0.2 + random(0) * 0.6)
+                                    * spacing,
+                            self.area.y0
+                                + (row as f64 + 0.2 + random(1) * 0.6)
+                                    * spacing,
+                        );
+                        let affine = Affine::translate(center)
+                            * Affine::rotate(random(2) * TAU)
+                            * Affine::scale(spacing * (0.1 + random(3) * 0.15));
+                        marks.extend(affine * &mark);
+                    }
+                }
+                canvas.clip(Affine::IDENTITY, &self.area, |canvas| {
+                    canvas.fill(Affine::IDENTITY, color, &marks);
+                });
+            }
+        }
+    }
+}
+
+impl Backdrop {
+    /// A firework within the unit circle: rays around an empty middle, a
+    /// spark at the end of each.
+    fn burst() -> BezPath {
+        const RAYS: u32 = 12;
+        let ray = Rect::new(0.35, -0.03, 0.85, 0.03);
+        let mut path = BezPath::new();
+        for index in 0..RAYS {
+            let turn = Affine::rotate(f64::from(index) * TAU / f64::from(RAYS));
+            path.extend(turn * ray.to_path(0.01));
+            path.extend(turn * Circle::new((0.93, 0.0), 0.07).to_path(0.01));
+        }
+        path
+    }
+}
+
+/// A number in `0..1` picked by the grid square at `row` and `column` for
+/// the purpose `salt`, the same on every call.
+fn scatter(row: u64, column: u64, salt: u64) -> f64 {
+    // SplitMix64's finalizer over the packed arguments.
+    let mut bits =
+        (row << 40 ^ column << 8 ^ salt).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    bits = (bits ^ bits >> 30).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    bits = (bits ^ bits >> 27).wrapping_mul(0x94d0_49bb_1331_11eb);
+    bits ^= bits >> 31;
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Compass rose in the middle of the board, one wedge per seat shading
+/// from the accent color into the seat's color.
+pub struct Hub {
+    center: Point,
+    radius: f64,
+    metrics: Metrics,
+    /// Heading of each seat's home base from the center, in seating order.
+    headings: Vec<f64>,
+}
+
+impl Hub {
+    /// The hub of `layout`, under the runways meeting in the middle.
+    pub fn new(layout: &flight_core::Board) -> Self {
+        let metrics = layout.metrics();
+        let middle = layout.dimension() / 2.0;
+        let center = Point::new(middle, middle);
+        let headings = layout
+            .seats()
+            .into_iter()
+            .map(|affine| {
+                let base = affine * metrics.base().center();
+                (base - center).atan2()
+            })
+            .collect();
+        Self {
+            center,
+            radius: metrics.dim * 4.5,
+            metrics: *metrics,
+            headings,
+        }
+    }
+
+    /// Star pointing at every home base, with shorter points between.
+    fn compass(&self) -> BezPath {
+        let mut path = BezPath::new();
+        for (index, heading) in self.headings.iter().enumerate() {
+            let next = self.headings[(index + 1) % self.headings.len()];
+            // Halfway around from one base to the next, the long way round
+            // with a single seat.
+            let mut between = (next - heading).rem_euclid(TAU) / 2.0;
+            if between == 0.0 {
+                between = PI;
+            }
+            let points = [
+                (*heading, 0.9),
+                (heading + between / 2.0, 0.18),
+                (heading + between, 0.4),
+                (heading + between * 1.5, 0.18),
+            ];
+            for (angle, length) in points {
+                let point = self.center
+                    + Vec2::from_angle(angle) * self.radius * length;
+                if path.elements().is_empty() {
+                    path.move_to(point);
+                } else {
+                    path.line_to(point);
+                }
+            }
+        }
+        path.close_path();
+        path
+    }
+}
+
+impl Drawable for Hub {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let sweep = TAU / self.headings.len() as f64;
+        for (seat, heading) in self.headings.iter().enumerate() {
+            let gradient =
+                Gradient::new_radial(self.center, self.radius as f32)
+                    .with_stops([theme.accent, theme.player(seat)]);
+            let wedge = CircleSegment::new(
+                self.center,
+                self.radius,
+                0.0,
+                heading - sweep / 2.0,
+                sweep,
+            );
+            canvas.fill_gradient(Affine::IDENTITY, &gradient, &wedge);
+        }
+        let stroke = theme
+            .outline
+            .stroke(theme.stroke_width, self.metrics.scale());
+        let rim = Circle::new(self.center, self.radius);
+        canvas.stroke(&stroke, Affine::IDENTITY, theme.stroke, &rim);
+        let compass = self.compass();
+        canvas.fill(Affine::IDENTITY, theme.accent, &compass);
+        canvas.stroke(&stroke, Affine::IDENTITY, theme.stroke, &compass);
+    }
+}
+
+/// A player's quadrant painted in their color.
+pub struct Player {
+    color: Color,
+    affine: Affine,
+    /// Transform placing the runway, the quadrant's unless the board bends
+    /// it.
+    runway: Affine,
+    metrics: Metrics,
+}
+
+impl Player {
+    /// Names of the classic colors of the palette, the four colors cells
+    /// are painted in first.
+    pub const COLOR_NAMES: [&str; 6] =
+        ["red", "yellow", "blue", "green", "purple", "orange"];
+
+    /// Name of palette color `index`, numbered past the named ones.
+    pub fn color_name(index: usize) -> String {
+        match Self::COLOR_NAMES.get(index) {
+            Some(name) => name.to_string(),
+            None => format!("color{}", index + 1),
+        }
+    }
+
+    pub const fn new(color: Color, affine: Affine, metrics: Metrics) -> Self {
+        Player {
+            color,
+            affine,
+            runway: affine,
+            metrics,
+        }
+    }
+
+    /// The player with their runway placed by `runway`, as the hexagonal
+    /// board runs it straight to the middle rather than through the
+    /// quadrant.
+    pub const fn with_runway(mut self, runway: Affine) -> Self {
+        self.runway = runway;
+        self
+    }
+
+    /// Home base square in the player's local coordinates.
+    pub fn base(&self) -> Rect {
+        self.metrics.base()
+    }
+
+    /// Draws the home base square with its four hangar spots, over its
+    /// shadow if the theme casts them.
+    pub fn draw_base(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let base = self.base();
+        if theme.shadows {
+            // Light comes from the top left of the board, whichever way
+            // the quadrant is turned.
+            let lift = self.metrics.dim * 0.12;
+            let affine = Affine::translate((lift, lift)) * self.affine;
+            shadow(canvas, affine, |t| {
+                let spread = self.metrics.dim * 0.15 * t;
+                RoundedRect::from_rect(base.inflate(spread, spread), spread)
+            });
+        }
+        let texture = theme
+            .base_texture
+            .as_ref()
+            .and_then(|texture| texture.brush(self.metrics.scale()));
+        // The shading tints a texture, which shows through it.
+        let alpha = match texture {
+            Some((image, brush)) => {
+                canvas.fill_image(self.affine, image, brush, &base);
+                0.7
+            }
+            None => 1.0,
+        };
+        match theme.base.gradient(self.color, base) {
+            Some(gradient) => canvas.fill_gradient(
+                self.affine,
+                &gradient.multiply_alpha(alpha),
+                &base,
+            ),
+            None => canvas.fill(
+                self.affine,
+                self.color.multiply_alpha(alpha),
+                &base,
+            ),
+        }
+        let radius = self.metrics.hangar_radius();
+        for center in Quadrant::hangars(&self.metrics) {
+            spot(canvas, self.affine, theme, center, radius);
+        }
+    }
+
+    /// Strokes the edges of the track through the player's quadrant.
+    pub fn draw_edges(
+        &self,
+        canvas: &mut impl Canvas,
+        theme: &Theme,
+        edges: &[BezPath],
+    ) {
+        let stroke = self.stroke(theme);
+        for edge in edges {
+            canvas.stroke(&stroke, self.affine, theme.stroke, edge);
+        }
+    }
+
+    /// Theme strokes are authored for the default cell size.
+    fn stroke(&self, theme: &Theme) -> Stroke {
+        theme
+            .outline
+            .stroke(theme.stroke_width, self.metrics.scale())
+    }
+}
+
+impl Drawable for Player {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let metrics = &self.metrics;
+        let stroke = self.stroke(theme);
+        let runway = Quadrant::runway(metrics);
+        canvas.fill(self.runway, self.color, &runway);
+        canvas.stroke(&stroke, self.runway, theme.stroke, &runway);
+        if let Some(style) = &theme.runway_line {
+            // Down the middle from the track to the arrow's tip.
+            let bounds = runway.bounding_box();
+            let middle = bounds.center().y;
+            let line = Line::new((bounds.x0, middle), (bounds.x1, middle));
+            let stroke = style.stroke(theme.stroke_width, metrics.scale());
+            canvas.stroke(&stroke, self.runway, theme.stroke, &line);
+        }
+        for center in Quadrant::runway_spots(metrics) {
+            spot(canvas, self.runway, theme, center, metrics.cell_radius());
+        }
+    }
+}
+
+/// A track cell painted in a resolved color.
+pub struct Cell {
+    shape: Shape,
+    color: Color,
+    affine: Affine,
+    /// Heading planes leave the cell along, in radians clockwise from
+    /// pointing right.
+    travel: Option<f64>,
+    /// Heading of the shortcut starting on the cell.
+    fly: Option<f64>,
+}
+
+impl Cell {
+    pub fn new(shape: Shape, color: Color, affine: Affine) -> Cell {
+        Self {
+            shape,
+            color,
+            affine,
+            travel: None,
+            fly: None,
+        }
+    }
+
+    /// Draws an arrow along `travel` on the center spot and one along
+    /// `fly` at its rim.
+    pub fn with_arrows(
+        mut self,
+        travel: Option<f64>,
+        fly: Option<f64>,
+    ) -> Self {
+        self.travel = travel;
+        self.fly = fly;
+        self
+    }
+
+    /// Arrowhead of `size` pointing along `heading` from its back at
+    /// `back`.
+    fn arrow(back: Point, heading: f64, size: f64) -> BezPath {
+        let ahead = Vec2::from_angle(heading) * size;
+        let side = Vec2::new(-ahead.y, ahead.x) * 0.4;
+        let mut path = BezPath::new();
+        path.move_to(back + ahead);
+        path.line_to(back + side);
+        path.line_to(back + ahead * 0.3);
+        path.line_to(back - side);
+        path.close_path();
+        path
+    }
+
+    /// Marks `part` of the cell with the pattern of its color, if the
+    /// theme asks for patterns.
+    fn draw_pattern(
+        &self,
+        canvas: &mut impl Canvas,
+        theme: &Theme,
+        part: &impl kurbo::Shape,
+    ) {
+        if !theme.patterns {
+            return;
+        }
+        let pattern = Pattern::for_color(self.shape.color);
+        let color = theme.stroke.with_alpha(Pattern::ALPHA);
+        let area = self.shape.bounds();
+        canvas.clip(self.affine, part, |canvas| {
+            pattern.draw(
+                canvas,
+                self.affine,
+                area,
+                self.shape.dim * 0.2,
+                color,
+            );
+        });
+    }
+
+    /// Bounding box of the cell before its affine is applied.
+    pub fn bounds(&self) -> Rect {
+        self.shape.bounds()
+    }
+}
+
+impl Drawable for Cell {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        if let Some(shape) = self.shape.triangle().as_ref() {
+            canvas.fill(self.affine, self.color, shape);
+            self.draw_pattern(canvas, theme, shape);
+        }
+        if let Some(shape) = self.shape.block().as_ref() {
+            canvas.fill(self.affine, self.color, shape);
+            self.draw_pattern(canvas, theme, shape);
+        }
+        let (center, radius) = (self.shape.center(), self.shape.radius());
+        spot(canvas, self.affine, theme, center, radius);
+        if let Some(heading) = self.travel {
+            let back = center - Vec2::from_angle(heading) * radius * 0.45;
+            let arrow = Self::arrow(back, heading, radius * 0.9);
+            canvas.fill(self.affine, self.color, &arrow);
+        }
+        if let Some(heading) = self.fly {
+            let back = center + Vec2::from_angle(heading) * radius;
+            let arrow = Self::arrow(back, heading, radius * 0.7);
+            canvas.fill(self.affine, theme.stroke, &arrow);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use vello::Scene;
+use vello::kurbo::{Affine, Shape, Stroke};
+use vello::peniko::{Color, Fill, Gradient, ImageBrush};
+
+/// Flattening accuracy for shapes handed to the CPU rasterizer.
+const TOLERANCE: f64 = 0.1;
+
+/// Drawing surface the board is built on, so the same drawables can feed
+/// both the vello rasterizer and vector exporters.
+pub trait Canvas {
+    fn fill(&mut self, affine: Affine, color: Color, shape: &impl Shape);
+
+    /// Fills `shape` with `gradient`, laid out in the same coordinates as
+    /// the shape.
+    fn fill_gradient(
+        &mut self,
+        affine: Affine,
+        gradient: &Gradient,
+        shape: &impl Shape,
+    );
+
+    /// Fills `shape` with `image`, whose pixels `brush` lays out in the
+    /// coordinates of the shape.
+    fn fill_image(
+        &mut self,
+        affine: Affine,
+        image: &ImageBrush,
+        brush: Affine,
+        shape: &impl Shape,
+    );
+
+    fn stroke(
+        &mut self,
+        style: &Stroke,
+        affine: Affine,
+        color: Color,
+        shape: &impl Shape,
+    );
+
+    /// Draws everything emitted by `draw` transformed by `affine`.
+    fn group(&mut self, affine: Affine, draw: impl FnOnce(&mut Self));
+
+    /// Draws everything emitted by `draw` only where it falls inside
+    /// `shape` transformed by `affine`.
+    fn clip(
+        &mut self,
+        affine: Affine,
+        shape: &impl Shape,
+        draw: impl FnOnce(&mut Self),
+    );
+}
+
+impl Canvas for Scene {
+    fn fill(&mut self, affine: Affine, color: Color, shape: &impl Shape) {
+        Scene::fill(self, Fill::NonZero, affine, color, None, shape);
+    }
+
+    fn fill_gradient(
+        &mut self,
+        affine: Affine,
+        gradient: &Gradient,
+        shape: &impl Shape,
+    ) {
+        Scene::fill(self, Fill::NonZero, affine, gradient, None, shape);
+    }
+
+    fn fill_image(
+        &mut self,
+        affine: Affine,
+        image: &ImageBrush,
+        brush: Affine,
+        shape: &impl Shape,
+    ) {
+        Scene::fill(self, Fill::NonZero, affine, image, Some(brush), shape);
+    }
+
+    fn stroke(
+        &mut self,
+        style: &Stroke,
+        affine: Affine,
+        color: Color,
+        shape: &impl Shape,
+    ) {
+        Scene::stroke(self, style, affine, color, None, shape);
+    }
+
+    fn group(&mut self, affine: Affine, draw: impl FnOnce(&mut Self)) {
+        let mut group = Scene::new();
+        draw(&mut group);
+        self.append(&group, Some(affine));
+    }
+
+    fn clip(
+        &mut self,
+        affine: Affine,
+        shape: &impl Shape,
+        draw: impl FnOnce(&mut Self),
+    ) {
+        self.push_clip_layer(affine, shape);
+        draw(self);
+        self.pop_layer();
+    }
+}
+
+/// Transforms compose with the context's current one, so groups nest the
+/// same way they do on a vello scene.
+impl Canvas for vello_cpu::RenderContext {
+    fn fill(&mut self, affine: Affine, color: Color, shape: &impl Shape) {
+        let base = *self.transform();
+        self.set_transform(base * affine);
+        self.set_paint(color);
+        self.fill_path(&shape.to_path(TOLERANCE));
+        self.set_transform(base);
+    }
+
+    fn fill_gradient(
+        &mut self,
+        affine: Affine,
+        gradient: &Gradient,
+        shape: &impl Shape,
+    ) {
+        let base = *self.transform();
+        self.set_transform(base * affine);
+        self.set_paint(gradient.clone());
+        self.fill_path(&shape.to_path(TOLERANCE));
+        self.set_transform(base);
+    }
+
+    fn fill_image(
+        &mut self,
+        affine: Affine,
+        image: &ImageBrush,
+        brush: Affine,
+        shape: &impl Shape,
+    ) {
+        let base = *self.transform();
+        self.set_transform(base * affine);
+        self.set_paint_transform(brush);
+        self.set_paint(vello_cpu::Image {
+            image: vello_cpu::ImageSource::from_peniko_image_data(&image.image),
+            sampler: image.sampler,
+        });
+        self.fill_path(&shape.to_path(TOLERANCE));
+        self.reset_paint_transform();
+        self.set_transform(base);
+    }
+
+    fn stroke(
+        &mut self,
+        style: &Stroke,
+        affine: Affine,
+        color: Color,
+        shape: &impl Shape,
+    ) {
+        let base = *self.transform();
+        self.set_transform(base * affine);
+        self.set_paint(color);
+        self.set_stroke(style.clone());
+        self.stroke_path(&shape.to_path(TOLERANCE));
+        self.set_transform(base);
+    }
+
+    fn group(&mut self, affine: Affine, draw: impl FnOnce(&mut Self)) {
+        let base = *self.transform();
+        self.set_transform(base * affine);
+        draw(self);
+        self.set_transform(base);
+    }
+
+    fn clip(
+        &mut self,
+        affine: Affine,
+        shape: &impl Shape,
+        draw: impl FnOnce(&mut Self),
+    ) {
+        let base = *self.transform();
+        self.set_transform(base * affine);
+        self.push_clip_layer(&shape.to_path(TOLERANCE));
+        self.set_transform(base);
+        draw(self);
+        self.pop_layer();
+    }
+}
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use flight_core::DiceSpec;
+use rand::{Rng, RngExt};
+use vello::kurbo::{
+    Affine, BezPath, Circle, Point, Rect, RoundedRect, Shape, Stroke, Vec2,
+};
+
+use crate::board::Drawable;
+use crate::canvas::Canvas;
+use crate::theme::Theme;
+
+/// A die showing `face` as a rounded square with pips, `side` wide and
+/// centered on a point.
+pub struct Die {
+    affine: Affine,
+    face: u8,
+    side: f64,
+}
+
+impl Die {
+    /// A die on `center` under `affine`, turned by `tilt` radians.
+    pub fn new(
+        affine: Affine,
+        center: Point,
+        face: u8,
+        side: f64,
+        tilt: f64,
+    ) -> Self {
+        Self {
+            affine: affine
+                * Affine::translate(center.to_vec2())
+                * Affine::rotate(tilt),
+            face,
+            side,
+        }
+    }
+
+    /// The rounded square of the die, centered on the origin.
+    fn body(&self) -> RoundedRect {
+        let half = self.side / 2.0;
+        RoundedRect::new(-half, -half, half, half, half * 0.25)
+    }
+
+    /// The theme's dice skin fitted over the body, the body itself without
+    /// one.
+    fn outline(&self, theme: &Theme) -> BezPath {
+        let body = self.body();
+        let skin = theme.dice_skin.as_ref();
+        skin.and_then(|skin| skin.fit(body.rect()))
+            .unwrap_or_else(|| body.to_path(0.1))
+    }
+
+    fn stroke(&self) -> Stroke {
+        Stroke::new(self.side * 0.05)
+    }
+
+    /// Area the die covers with its outline.
+    pub fn bounds(&self) -> Rect {
+        let outline = self.stroke().width / 2.0;
+        let body = self.body().rect().inflate(outline, outline);
+        self.affine.transform_rect_bbox(body)
+    }
+}
+
+impl Drawable for Die {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let body = self.outline(theme);
+        canvas.fill(self.affine, theme.accent, &body);
+        canvas.stroke(&self.stroke(), self.affine, theme.stroke, &body);
+        let half = self.side / 2.0;
+        for pip in pips(self.face) {
+            let pip = Circle::new((pip * half * 0.55).to_point(), half / 6.0);
+            canvas.fill(self.affine, theme.stroke, &pip);
+        }
+    }
+}
+
+/// Dice showing `faces` side by side, as rounded squares with pips,
+/// centered on a point of the board.
+pub struct DiceFaces {
+    affine: Affine,
+    faces: Vec<u8>,
+    /// Side of each die.
+    side: f64,
+    /// Turn of each die, in radians.
+    tilt: f64,
+}
+
+impl DiceFaces {
+    pub fn new(
+        affine: Affine,
+        center: Point,
+        faces: Vec<u8>,
+        side: f64,
+        tilt: f64,
+    ) -> Self {
+        Self {
+            affine: affine * Affine::translate(center.to_vec2()),
+            faces,
+            side,
+            tilt,
+        }
+    }
+}
+
+impl Drawable for DiceFaces {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let gap = self.side * 1.3;
+        let first = -gap * (self.faces.len() as f64 - 1.0) / 2.0;
+        for (index, &face) in self.faces.iter().enumerate() {
+            let center = Point::new(first + gap * index as f64, 0.0);
+            Die::new(self.affine, center, face, self.side, self.tilt)
+                .draw(canvas, theme);
+        }
+    }
+}
+
+/// Where the pips of `face` go on a die two units wide, as on real dice
+/// up to nine and on a grid past that.
+fn pips(face: u8) -> Vec<Vec2> {
+    let grid = |x: f64, y: f64| Vec2::new(x, y);
+    let (left, right, top, bottom) = (-1.0, 1.0, -1.0, 1.0);
+    let corners = [grid(left, top), grid(right, bottom)];
+    let other = [grid(right, top), grid(left, bottom)];
+    let middle = [grid(left, 0.0), grid(right, 0.0)];
+    let center = [Vec2::ZERO];
+    let column = [grid(0.0, top), grid(0.0, bottom)];
+    let pips: Vec<&[Vec2]> = match face {
+        1 => vec![&center],
+        2 => vec![&corners],
+        3 => vec![&corners, &center],
+        4 => vec![&corners, &other],
+        5 => vec![&corners, &other, &center],
+        6 => vec![&corners, &other, &middle],
+        7 => vec![&corners, &other, &middle, &center],
+        8 => vec![&corners, &other, &middle, &column],
+        9 => vec![&corners, &other, &middle, &column, &center],
+        _ => {
+            let columns = f64::from(face).sqrt().ceil() as u8;
+            let step = 2.0 / f64::from(columns - 1);
+            return (0..face)
+                .map(|pip| {
+                    let (row, column) = (pip / columns, pip % columns);
+                    let x = -1.0 + step * f64::from(column);
+                    grid(x, -1.0 + step * f64::from(row))
+                })
+                .collect();
+        }
+    };
+    pips.concat()
+}
+
+/// A throw tumbling through random faces, changing more and more slowly
+/// until it settles on the faces thrown.
+pub struct Tumble {
+    /// Faces shown from each moment on, the faces thrown last.
+    frames: Vec<(Duration, Vec<u8>)>,
+    duration: Duration,
+}
+
+impl Tumble {
+    /// Faces changed while tumbling, each shown a little longer than the
+    /// one before.
+    const CHANGES: u32 = 10;
+
+    /// A tumble of `duration` for dice of `spec` settling on `faces`, its
+    /// intermediate faces drawn from `rng`.
+    pub fn new(
+        spec: DiceSpec,
+        faces: Vec<u8>,
+        duration: Duration,
+        rng: &mut impl Rng,
+    ) -> Self {
+        // The nth change comes after 1 + 2 + ... + n units of time.
+        let units = Self::CHANGES * (Self::CHANGES + 1) / 2;
+        let mut frames = vec![];
+        let mut elapsed = 0;
+        for change in 0..Self::CHANGES {
+            let shown = (0..faces.len())
+                .map(|_| rng.random_range(1..=spec.faces))
+                .collect();
+            frames.push((duration * elapsed / units, shown));
+            elapsed += change + 1;
+        }
+        frames.push((duration, faces));
+        Self { frames, duration }
+    }
+
+    /// Faces shown `elapsed` into the tumble and how far they are turned,
+    /// the dice straightening out as they settle.
+    pub fn at(&self, elapsed: Duration) -> (&[u8], f64) {
+        let index = self.frames.partition_point(|(from, _)| *from <= elapsed);
+        let (_, faces) = &self.frames[index.saturating_sub(1)];
+        let left = 1.0 - (elapsed.as_secs_f64() / self.duration.as_secs_f64());
+        let tilt = left.max(0.0) * index as f64 * 0.9;
+        (faces, tilt)
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
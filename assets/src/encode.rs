@@ -0,0 +1,289 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+
+use anyhow::{Context, Result, bail, ensure};
+
+/// Writes tightly packed RGBA8 pixels as a lossless PNG.
+pub fn write_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    bytes: &[u8],
+) -> Result<()> {
+    let mut file = File::create(path)?;
+    let mut encoder = png::Encoder::new(&mut file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(bytes)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Writes straight-alpha pixels with sRGB-encoded channels in `0..=1` as a
+/// 16 bit per channel PNG.
+pub fn write_png16(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[[f32; 4]],
+) -> Result<()> {
+    let mut file = File::create(path)?;
+    let mut encoder = png::Encoder::new(&mut file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    let mut writer = encoder.write_header()?;
+    let bytes = pixels
+        .iter()
+        .flatten()
+        .flat_map(|value| {
+            ((value.clamp(0.0, 1.0) * 65535.0).round() as u16).to_be_bytes()
+        })
+        .collect::<Vec<_>>();
+    writer.write_image_data(&bytes)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Writes straight-alpha pixels with sRGB-encoded channels in `0..=1` as a
+/// half-float OpenEXR, converted to the linear, premultiplied values the
+/// format expects.
+pub fn write_exr(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[[f32; 4]],
+) -> Result<()> {
+    use exr::prelude::f16;
+
+    let premultiplied = |value: f32, alpha: f32| {
+        let linear = if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        };
+        f16::from_f32(linear * alpha)
+    };
+    exr::prelude::write_rgba_file(
+        path,
+        width as usize,
+        height as usize,
+        |x, y| {
+            let [r, g, b, a] = pixels[y * width as usize + x];
+            (
+                premultiplied(r, a),
+                premultiplied(g, a),
+                premultiplied(b, a),
+                f16::from_f32(a),
+            )
+        },
+    )?;
+    Ok(())
+}
+
+/// Writes tightly packed RGBA8 pixels as a JPEG, dropping the alpha channel.
+pub fn write_jpeg(
+    path: &Path,
+    width: u32,
+    height: u32,
+    bytes: &[u8],
+    quality: u8,
+) -> Result<()> {
+    let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height))
+    else {
+        bail!("JPEG images are limited to 65535 pixels per side");
+    };
+    let encoder = jpeg_encoder::Encoder::new_file(path, quality)?;
+    encoder.encode(bytes, width, height, jpeg_encoder::ColorType::Rgba)?;
+    Ok(())
+}
+
+/// Writes tightly packed RGBA8 pixels as a lossy WebP, keeping alpha.
+pub fn write_webp(
+    path: &Path,
+    width: u32,
+    height: u32,
+    bytes: &[u8],
+    quality: u8,
+) -> Result<()> {
+    ensure!(
+        width <= 16383 && height <= 16383,
+        "WebP images are limited to 16383 pixels per side"
+    );
+    let encoded =
+        webp::Encoder::from_rgba(bytes, width, height).encode(quality.into());
+    fs::write(path, &*encoded)?;
+    Ok(())
+}
+
+/// Format of an [`Animation`].
+#[derive(Copy, Clone)]
+pub enum AnimationKind {
+    Gif,
+    Apng,
+    /// Numbered PNG files in a folder.
+    Frames,
+    /// Tightly packed RGBA8 frames back to back, without any header.
+    Rgba,
+}
+
+/// An animated image written a frame at a time, so long replays are never
+/// held in memory whole.
+pub enum Animation {
+    Gif {
+        encoder: gif::Encoder<BufWriter<File>>,
+        width: u16,
+        height: u16,
+        /// Frame time in hundredths of a second.
+        delay: u16,
+    },
+    Apng(png::Writer<BufWriter<File>>),
+    Frames {
+        folder: PathBuf,
+        width: u32,
+        height: u32,
+        next: u32,
+    },
+    Rgba {
+        out: Box<dyn Write>,
+        /// Encoder reading the frames, waited for when they are done.
+        child: Option<Child>,
+    },
+}
+
+impl Animation {
+    /// Starts an animation of `frames` tightly packed RGBA8 frames, each
+    /// shown for `delay` milliseconds and looping forever. Frame sequences
+    /// are written into the folder `path`, created if missing.
+    pub fn create(
+        path: &Path,
+        kind: AnimationKind,
+        width: u32,
+        height: u32,
+        frames: u32,
+        delay: u32,
+    ) -> Result<Self> {
+        match kind {
+            AnimationKind::Frames => {
+                fs::create_dir_all(path).with_context(|| {
+                    format!("failed to create {}", path.display())
+                })?;
+                Ok(Self::Frames {
+                    folder: path.to_path_buf(),
+                    width,
+                    height,
+                    next: 0,
+                })
+            }
+            AnimationKind::Rgba => {
+                let out = Box::new(BufWriter::new(File::create(path)?));
+                Ok(Self::Rgba { out, child: None })
+            }
+            AnimationKind::Gif => {
+                let file = BufWriter::new(File::create(path)?);
+                let (Ok(width), Ok(height)) =
+                    (u16::try_from(width), u16::try_from(height))
+                else {
+                    bail!("GIF images are limited to 65535 pixels per side");
+                };
+                let Ok(delay) = u16::try_from(delay.div_ceil(10)) else {
+                    bail!("GIF frames last at most 655 seconds");
+                };
+                let mut encoder = gif::Encoder::new(file, width, height, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                Ok(Self::Gif {
+                    encoder,
+                    width,
+                    height,
+                    delay,
+                })
+            }
+            AnimationKind::Apng => {
+                let Ok(delay) = u16::try_from(delay) else {
+                    bail!("APNG frames last at most 65 seconds");
+                };
+                let file = BufWriter::new(File::create(path)?);
+                let mut encoder = png::Encoder::new(file, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(frames, 0)?;
+                encoder.set_frame_delay(delay, 1000)?;
+                Ok(Self::Apng(encoder.write_header()?))
+            }
+        }
+    }
+
+    /// Pipes RGBA8 frames into the standard input of a shell `command`,
+    /// such as a video encoder.
+    pub fn piped(command: &str) -> Result<Self> {
+        let mut child = Command::new("sh")
+            .args(["-c", command])
+            .stdin(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run {command:?}"))?;
+        let stdin = child.stdin.take().context("no pipe to the encoder")?;
+        Ok(Self::Rgba {
+            out: Box::new(BufWriter::new(stdin)),
+            child: Some(child),
+        })
+    }
+
+    pub fn add(&mut self, bytes: &[u8]) -> Result<()> {
+        match self {
+            Self::Gif {
+                encoder,
+                width,
+                height,
+                delay,
+            } => {
+                let mut pixels = bytes.to_vec();
+                let mut frame = gif::Frame::from_rgba_speed(
+                    *width,
+                    *height,
+                    &mut pixels,
+                    10,
+                );
+                frame.delay = *delay;
+                encoder.write_frame(&frame)?;
+            }
+            Self::Apng(writer) => writer.write_image_data(bytes)?,
+            Self::Frames {
+                folder,
+                width,
+                height,
+                next,
+            } => {
+                let path = folder.join(format!("{next:05}.png"));
+                write_png(&path, *width, *height, bytes)?;
+                *next += 1;
+            }
+            Self::Rgba { out, .. } => out
+                .write_all(bytes)
+                .context("failed to write a frame, did the encoder exit?")?,
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self {
+            Self::Gif { encoder, .. } => {
+                encoder.into_inner()?;
+            }
+            Self::Apng(writer) => writer.finish()?,
+            Self::Frames { .. } => {}
+            Self::Rgba { mut out, child } => {
+                out.flush()?;
+                // Closing the pipe tells the encoder the frames are done.
+                drop(out);
+                if let Some(mut child) = child {
+                    let status = child.wait()?;
+                    ensure!(status.success(), "the encoder failed: {status}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,833 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use clap::{ArgMatches, Args, FromArgMatches, ValueEnum};
+use flight_core::{BoardGenerator, BoardShape, Game, Layout, Metrics, Replay};
+use serde::Deserialize;
+use vello::Scene;
+use vello::kurbo::{Affine, Rect};
+use vello::peniko::Color;
+
+use crate::board::{Backdrop, Board, Drawable};
+use crate::canvas::Canvas;
+use crate::encode::{Animation, AnimationKind};
+use crate::gpu::AdapterChoice;
+use crate::looks;
+use crate::manifest::{self, Asset, AssetKind, Manifest};
+use crate::pieces::Pieces;
+use crate::render::{self, Antialiasing, Rasterizer};
+use crate::sprites::{Sprite, SpriteSheet};
+use crate::svg::SvgCanvas;
+use crate::theme::{BackdropKind, ColorOverride, Theme};
+use crate::variant::Variant;
+use crate::{encode, ktx2};
+
+/// Everything describing one generated file, given on the command line or
+/// as a `[[job]]` table of a batch file.
+#[derive(Args, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Job {
+    /// Path of the generated image.
+    #[arg(short, long, default_value = "background.png")]
+    pub out: PathBuf,
+
+    /// Output width in pixels, defaults to the scaled board size.
+    #[arg(long)]
+    pub width: Option<u32>,
+
+    /// Output height in pixels, defaults to the scaled board size.
+    #[arg(long)]
+    pub height: Option<u32>,
+
+    /// Scale factor applied to the board, e.g. 2 for HiDPI assets.
+    #[arg(short, long, default_value_t = 1.0)]
+    pub scale: f64,
+
+    /// Format of the generated image.
+    #[arg(short, long, value_enum, default_value_t = Format::Png)]
+    pub format: Format,
+
+    /// Encoder quality for lossy formats, from 1 to 100.
+    #[arg(short, long, default_value_t = 90,
+          value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub quality: u8,
+
+    /// Render a packed sprite sheet of cells, home bases and planes
+    /// instead of the board, with sprite metadata written next to it as
+    /// JSON.
+    #[arg(long)]
+    pub sprites: bool,
+
+    /// Render a strip of the six die faces side by side instead of the
+    /// board, with their placements written next to it as JSON.
+    #[arg(long, conflicts_with = "sprites")]
+    pub dice: bool,
+
+    /// Mark the board with arrows showing the way planes travel around
+    /// the ring and where shortcuts fly.
+    #[arg(long)]
+    pub arrows: bool,
+
+    /// Label every track cell with its index, to point at exact cells in
+    /// rule discussions, bug reports and tutorials.
+    #[arg(long)]
+    pub numbers: bool,
+
+    /// Mark cells and planes with a pattern for each player color, for
+    /// players who cannot tell the colors apart.
+    #[arg(long)]
+    pub patterns: bool,
+
+    /// Decorate the whole output around and under the board, replacing the
+    /// kind of the theme's backdrop. With `--width` and `--height` this
+    /// makes wallpapers and menu backgrounds.
+    #[arg(long, value_enum)]
+    pub backdrop: Option<BackdropKind>,
+
+    /// Leave the background fully transparent so the artwork can be
+    /// composited over arbitrary backgrounds.
+    #[arg(long)]
+    pub transparent: bool,
+
+    /// Saved game to animate instead of drawing the empty board, in one of
+    /// the animated formats. Its board replaces the board options.
+    #[arg(long, conflicts_with_all = ["sprites", "dice"])]
+    pub replay: Option<PathBuf>,
+
+    /// Time each turn of a replay takes, in milliseconds.
+    #[arg(long, default_value_t = 500)]
+    pub turn_ms: u32,
+
+    /// Frames per second of a replay, moving planes smoothly between
+    /// turns. Without it every turn is a single frame.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub fps: Option<u32>,
+
+    /// Shell command to pipe raw RGBA frames into instead of writing them
+    /// to `--out`, such as a video encoder. `{width}`, `{height}` and
+    /// `{fps}` are replaced by the frame size and rate.
+    #[arg(long, requires = "replay")]
+    pub pipe: Option<String>,
+
+    /// Built-in look (classic, pastel, high-contrast or dark), or a TOML file
+    /// overriding the default board colors and strokes.
+    #[arg(short, long, global = true)]
+    pub theme: Option<PathBuf>,
+
+    /// Seasonal variant (winter, spring, summer, autumn or new-year), or a
+    /// TOML file of changes, derived from the theme for this file.
+    #[arg(long)]
+    pub variant: Option<PathBuf>,
+
+    /// Color of the theme replaced for this file, as `name=color` with
+    /// the name one of `player1`, `player2` and on, `background`, `accent`
+    /// or `stroke`. Can be given more than once.
+    #[arg(long = "color", value_name = "NAME=COLOR",
+          value_parser = ColorOverride::parse)]
+    pub colors: Vec<ColorOverride>,
+
+    /// TOML file describing the track cells of a quadrant, replacing the
+    /// classic layout.
+    #[arg(short, long, global = true)]
+    pub layout: Option<PathBuf>,
+
+    /// Seed of a randomly generated track replacing the layout. The same
+    /// seed and options always give the same board.
+    #[arg(long, global = true, conflicts_with = "layout")]
+    pub seed: Option<u64>,
+
+    /// Cells around the outer ring of a generated track, random when not
+    /// given.
+    #[arg(long, global = true, requires = "seed")]
+    pub ring: Option<usize>,
+
+    /// Shortcuts starting in each quadrant of a generated track.
+    #[arg(long, global = true, default_value_t = 0, requires = "seed")]
+    pub shortcuts: usize,
+
+    /// Number of players the board is laid out for.
+    #[arg(short, long, global = true, default_value_t = 4,
+          value_parser = players)]
+    pub players: usize,
+
+    /// Arrangement of the quadrants, `square` or `hex6`. Defaults to the
+    /// hexagonal board for 3 and 6 players and the square one otherwise.
+    #[arg(long, global = true)]
+    pub shape: Option<BoardShape>,
+
+    /// Board region `x,y,width,height` in unscaled board pixels to render
+    /// instead of the whole board, e.g. `0,0,1088,1088` for one quadrant.
+    /// It fills `--width` and `--height` when given.
+    #[arg(long, value_parser = crop)]
+    pub crop: Option<[f64; 4]>,
+
+    /// Bits per channel of PNG output, 8 or 16. EXR output always keeps
+    /// the extra precision.
+    #[arg(long, default_value_t = 8, value_parser = depth)]
+    pub depth: u8,
+}
+
+impl Default for Job {
+    /// The command line defaults, so batch files only list what differs.
+    fn default() -> Self {
+        let command = Job::augment_args(clap::Command::new("job"));
+        let matches: ArgMatches = command.get_matches_from(["job"]);
+        Job::from_arg_matches(&matches).expect("defaults are valid")
+    }
+}
+
+impl Job {
+    pub fn theme(&self) -> Result<Theme> {
+        let mut theme = match &self.theme {
+            Some(path) => Theme::find(path)?,
+            None => Theme::default(),
+        };
+        if let Some(path) = &self.variant {
+            Variant::find(path)?.apply(&mut theme)?;
+        }
+        if let Some(kind) = self.backdrop {
+            let backdrop = theme.backdrop.get_or_insert_with(Default::default);
+            backdrop.kind = kind;
+        }
+        // A textured or decorated background would cover the transparency
+        // asked for.
+        if self.transparent {
+            theme.background_texture = None;
+            theme.backdrop = None;
+        }
+        theme.override_colors(&self.colors)?;
+        theme.patterns |= self.patterns;
+        Ok(theme)
+    }
+
+    /// The track layout, checked so that broken custom layouts fail
+    /// before anything is drawn.
+    pub fn layout(&self) -> Result<Layout> {
+        if let Some(seed) = self.seed {
+            let generator = BoardGenerator {
+                ring: self.ring,
+                shortcuts: self.shortcuts,
+                ..BoardGenerator::new(seed, self.players)
+            };
+            return generator.layout();
+        }
+        let Some(path) = &self.layout else {
+            return Ok(Layout::classic(self.shape()));
+        };
+        let layout = Layout::load(path)?;
+        if let Err(errors) = layout.validate() {
+            let errors: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+            bail!("invalid layout {}:\n{}", path.display(), errors.join("\n"));
+        }
+        Ok(layout)
+    }
+
+    fn shape(&self) -> BoardShape {
+        self.shape
+            .unwrap_or_else(|| BoardShape::for_players(self.players))
+    }
+
+    /// The board to draw, checking that its shape seats the players.
+    pub fn board(&self) -> Result<flight_core::Board> {
+        let shape = self.shape();
+        ensure!(
+            shape.players().contains(&self.players),
+            "{} boards seat one of {:?} players",
+            shape.name(),
+            shape.players()
+        );
+        let board =
+            flight_core::Board::with_shape(shape, self.players, self.layout()?);
+        Ok(board.with_metrics(self.metrics()))
+    }
+
+    /// Without a crop the board is laid out at the output scale directly,
+    /// a crop stretches the default size board instead.
+    fn metrics(&self) -> Metrics {
+        match self.crop {
+            Some(_) => Metrics::default(),
+            None => Metrics::new(Metrics::DEFAULT_DIM * self.scale),
+        }
+    }
+
+    fn size(&self) -> Result<(u32, u32)> {
+        ensure!(self.scale > 0.0, "scale must be positive");
+        let region = self.region().size() * self.scale;
+        let width = self.width.unwrap_or(region.width.round() as u32);
+        let height = self.height.unwrap_or(region.height.round() as u32);
+        ensure!(width > 0 && height > 0, "output size must not be empty");
+        Ok((width, height))
+    }
+
+    /// Part of the unscaled board that ends up in the output.
+    fn region(&self) -> Rect {
+        match self.crop {
+            Some([x, y, width, height]) => {
+                Rect::from_origin_size((x, y), (width, height))
+            }
+            None => {
+                let dimension = self.shape().dimension(&Metrics::default());
+                Rect::new(0.0, 0.0, dimension, dimension)
+            }
+        }
+    }
+
+    /// Maps the board onto an output of the given size. Without a crop the
+    /// board is already laid out at scale and centered, an explicit size
+    /// pads it or cuts off its sides evenly.
+    fn affine(&self, width: u32, height: u32) -> Affine {
+        let region = self.region();
+        if self.crop.is_none() {
+            let board = region.size() * self.scale;
+            return Affine::translate((
+                (f64::from(width) - board.width) / 2.0,
+                (f64::from(height) - board.height) / 2.0,
+            ));
+        }
+        Affine::scale_non_uniform(
+            f64::from(width) / region.width(),
+            f64::from(height) / region.height(),
+        ) * Affine::translate(-region.origin().to_vec2())
+    }
+
+    /// How many times larger in each direction raster output is rendered
+    /// before it is averaged down. vello only writes RGBA8, so high
+    /// precision output recovers its extra bits from supersamples.
+    fn supersample(&self) -> u32 {
+        if self.depth > 8 || matches!(self.format, Format::Exr) {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// The theme's backdrop over the whole output, sized with the board.
+    fn backdrop(&self, width: u32, height: u32) -> Backdrop {
+        let area = Rect::new(0.0, 0.0, width.into(), height.into());
+        let scale = match self.crop {
+            Some(_) => f64::from(width) / self.region().width(),
+            None => self.scale,
+        };
+        Backdrop::new(area, scale)
+    }
+
+    fn background(&self, theme: &Theme) -> Color {
+        if self.transparent {
+            Color::TRANSPARENT
+        } else {
+            theme.background
+        }
+    }
+}
+
+fn players(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|players| Board::PLAYERS.contains(players))
+        .ok_or_else(|| format!("expected one of {:?}", Board::PLAYERS))
+}
+
+fn crop(value: &str) -> Result<[f64; 4], String> {
+    let numbers = value
+        .split(',')
+        .map(|number| number.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    let [x, y, width, height] = numbers[..] else {
+        return Err("expected x,y,width,height".into());
+    };
+    valid_crop([x, y, width, height])
+}
+
+fn valid_crop(crop: [f64; 4]) -> Result<[f64; 4], String> {
+    if crop[2] > 0.0 && crop[3] > 0.0 {
+        Ok(crop)
+    } else {
+        Err("crop width and height must be positive".into())
+    }
+}
+
+fn depth(value: &str) -> Result<u8, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|depth| DEPTHS.contains(depth))
+        .ok_or_else(|| format!("expected one of {DEPTHS:?}"))
+}
+
+const DEPTHS: [u8; 2] = [8, 16];
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+pub enum Backend {
+    /// The GPU, falling back to the CPU when no device can be opened.
+    Auto,
+    /// The GPU only, failing if no device can be opened.
+    Gpu,
+    /// The software rasterizer, slower but available everywhere.
+    Cpu,
+}
+
+#[derive(Copy, Clone, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Lossless raster image rendered through vello.
+    Png,
+    /// Lossy raster image without alpha, see `--quality`.
+    Jpeg,
+    /// Lossy raster image with alpha, see `--quality`.
+    Webp,
+    /// Resolution-independent vector document.
+    Svg,
+    /// GPU-ready texture with a BC7-compressed mip chain.
+    Ktx2,
+    /// Linear half-float image for external compositing pipelines.
+    Exr,
+    /// Animated replay with a palette of 256 colors per frame.
+    Gif,
+    /// Animated lossless replay.
+    Apng,
+    /// Replay as numbered PNG frames in the `--out` folder.
+    Frames,
+    /// Replay as raw RGBA8 frames back to back, for video encoders.
+    Rgba,
+}
+
+impl Format {
+    fn animation(self) -> Option<AnimationKind> {
+        match self {
+            Self::Gif => Some(AnimationKind::Gif),
+            Self::Apng => Some(AnimationKind::Apng),
+            Self::Frames => Some(AnimationKind::Frames),
+            Self::Rgba => Some(AnimationKind::Rgba),
+            _ => None,
+        }
+    }
+}
+
+/// Jobs listed in a batch file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Batch {
+    #[serde(rename = "job")]
+    pub jobs: Vec<Job>,
+}
+
+impl Batch {
+    /// Loads a batch file, resolving its paths against the file's folder.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut batch = toml::from_str::<Batch>(&text)
+            .with_context(|| format!("invalid batch {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        for job in &mut batch.jobs {
+            let shape = job.shape();
+            ensure!(
+                shape.players().contains(&job.players),
+                "{}: {} boards seat one of {:?} players",
+                job.out.display(),
+                shape.name(),
+                shape.players()
+            );
+            if let Some(crop) = job.crop {
+                valid_crop(crop).map_err(|error| {
+                    anyhow!("{}: {error}", job.out.display())
+                })?;
+            }
+            ensure!(
+                DEPTHS.contains(&job.depth),
+                "{}: depth must be one of {DEPTHS:?}",
+                job.out.display()
+            );
+            job.out = base.join(&job.out);
+            job.theme = job.theme.as_ref().map(|theme| {
+                let builtin = theme.to_str().and_then(looks::named);
+                if builtin.is_some() {
+                    theme.clone()
+                } else {
+                    base.join(theme)
+                }
+            });
+            job.variant = job.variant.as_ref().map(|variant| {
+                let builtin = variant.to_str().and_then(Variant::season);
+                if builtin.is_some() {
+                    variant.clone()
+                } else {
+                    base.join(variant)
+                }
+            });
+            job.layout = job.layout.as_ref().map(|layout| base.join(layout));
+            job.replay = job.replay.as_ref().map(|replay| base.join(replay));
+        }
+        Ok(batch)
+    }
+}
+
+/// Artwork drawn scaled up by a supersampling factor.
+struct Supersampled<'a, D>(f64, &'a D);
+
+impl<D: Drawable> Drawable for Supersampled<'_, D> {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        canvas
+            .group(Affine::scale(self.0), |canvas| self.1.draw(canvas, theme));
+    }
+}
+
+/// A raster job waiting for its pixels to come back from the GPU.
+struct Queued {
+    job: Job,
+    kind: AssetKind,
+    width: u32,
+    height: u32,
+    metadata: Option<PathBuf>,
+}
+
+enum Raster {
+    Unopened,
+    Gpu(Box<Rasterizer>),
+    Cpu,
+}
+
+/// Writes jobs one after the other, opening the GPU once and keeping
+/// frames in flight while earlier ones are encoded.
+pub struct Exporter {
+    backend: Backend,
+    adapter: AdapterChoice,
+    antialiasing: Antialiasing,
+    raster: Raster,
+    /// Jobs submitted to the rasterizer, oldest first.
+    queued: VecDeque<Queued>,
+    manifest: Option<Manifest>,
+}
+
+impl Exporter {
+    pub fn new(
+        backend: Backend,
+        adapter: AdapterChoice,
+        antialiasing: Antialiasing,
+        manifest: Option<Manifest>,
+    ) -> Self {
+        Self {
+            backend,
+            adapter,
+            antialiasing,
+            raster: Raster::Unopened,
+            queued: VecDeque::new(),
+            manifest,
+        }
+    }
+
+    pub fn export(&mut self, job: Job) -> Result<()> {
+        let theme = job.theme()?;
+        match (&job.replay, job.format.animation()) {
+            (Some(_), None) => bail!("replays are written in animated formats"),
+            (None, Some(_)) => bail!("animated formats need a --replay"),
+            _ => {}
+        }
+        if job.replay.is_some() {
+            self.animate(job, &theme)
+        } else if job.sprites || job.dice {
+            ensure!(job.scale > 0.0, "scale must be positive");
+            ensure!(job.crop.is_none(), "sprite sheets cannot be cropped");
+            let image = job
+                .out
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let (sheet, kind) = if job.dice {
+                let strip =
+                    SpriteSheet::strip(image, Sprite::dice(), job.scale);
+                (strip, AssetKind::Dice)
+            } else {
+                let sprites = Sprite::all(&theme);
+                let sheet = SpriteSheet::pack(image, sprites, job.scale);
+                (sheet, AssetKind::Sprites)
+            };
+            let metadata = job.out.with_extension("json");
+            serde_json::to_writer_pretty(File::create(&metadata)?, &sheet)?;
+            let queued = Queued {
+                job,
+                kind,
+                width: sheet.width,
+                height: sheet.height,
+                metadata: Some(metadata),
+            };
+            self.draw(queued, &theme, &sheet)
+        } else {
+            let (width, height) = job.size()?;
+            let board =
+                Board::from_layout(job.affine(width, height), job.board()?)
+                    .with_arrows(job.arrows)
+                    .with_numbers(job.numbers);
+            let backdrop = job.backdrop(width, height);
+            let queued = Queued {
+                job,
+                kind: AssetKind::Board,
+                width,
+                height,
+                metadata: None,
+            };
+            self.draw(queued, &theme, &(&backdrop, &board))
+        }
+    }
+
+    /// Writes the files of jobs still in flight and saves the manifest.
+    pub fn finish(mut self) -> Result<()> {
+        self.flush()?;
+        if let Some(manifest) = &self.manifest {
+            manifest.save()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the files of jobs still in flight.
+    fn flush(&mut self) -> Result<()> {
+        while let Raster::Gpu(rasterizer) = &mut self.raster
+            && let Some(bytes) = rasterizer.finish()?
+        {
+            let queued = self.queued.pop_front().context("lost a job")?;
+            self.encode(queued, &bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the board of a saved game after each turn into the frames
+    /// of an animation, with frames moving the planes in between at a set
+    /// rate. Frames stream through the rasterizer, which is emptied of
+    /// other jobs first so everything it returns is a frame.
+    fn animate(&mut self, mut job: Job, theme: &Theme) -> Result<()> {
+        let kind = job.format.animation().context("not an animation")?;
+        ensure!(
+            job.pipe.is_none() || matches!(job.format, Format::Rgba),
+            "only raw RGBA frames can be piped"
+        );
+        ensure!(job.turn_ms > 0, "turns must take some time");
+        let path = job.replay.clone().context("no replay given")?;
+        let game = Game::load(&path)?;
+        let record = game.record();
+        job.players = record.board.players();
+        job.shape = Some(record.board.shape());
+        let (width, height) = job.size()?;
+        let affine = job.affine(width, height);
+        let layout = record.board.clone().with_metrics(job.metrics());
+        let board = Board::from_layout(affine, layout.clone())
+            .with_arrows(job.arrows)
+            .with_numbers(job.numbers);
+        let backdrop = job.backdrop(width, height);
+        let board = (&backdrop, &board);
+
+        let mut replay = Replay::new(record)?;
+        let mut states = vec![];
+        for turn in 0..=replay.turns() {
+            replay.seek(turn)?;
+            states.push(replay.state().clone());
+        }
+        let pieces: Vec<Pieces> = states
+            .iter()
+            .map(|state| Pieces::new(affine, &layout, state))
+            .collect();
+        let steps = job.fps.map_or(1, |fps| {
+            (f64::from(fps) * f64::from(job.turn_ms) / 1000.0)
+                .round()
+                .max(1.0) as u32
+        });
+        let delay = job.turn_ms / steps;
+        let fps = job.fps.map_or(1000.0 / f64::from(job.turn_ms), f64::from);
+        let frames = u32::try_from(pieces.len() - 1)? * steps + 1;
+        let mut animation = match &job.pipe {
+            Some(command) => Animation::piped(
+                &command
+                    .replace("{width}", &width.to_string())
+                    .replace("{height}", &height.to_string())
+                    .replace("{fps}", &fps.to_string()),
+            )?,
+            None => {
+                Animation::create(&job.out, kind, width, height, frames, delay)?
+            }
+        };
+        let background = job.background(theme);
+        self.flush()?;
+        let moving = pieces.windows(2).flat_map(|turn| {
+            (0..steps).map(|step| {
+                turn[0].lerp(&turn[1], f64::from(step) / f64::from(steps))
+            })
+        });
+        let last = pieces.last().expect("replays start with a state");
+        for frame in moving.chain([last.clone()]) {
+            let artwork = (&board, &frame);
+            match self.rasterizer()? {
+                Some(rasterizer) => {
+                    let mut scene = Scene::new();
+                    artwork.draw(&mut scene, theme);
+                    let done =
+                        rasterizer.submit(&scene, width, height, background)?;
+                    if let Some(bytes) = done {
+                        animation.add(&bytes)?;
+                    }
+                }
+                None => animation.add(&render::render_cpu(
+                    &artwork, theme, width, height, background,
+                )?)?,
+            }
+        }
+        while let Raster::Gpu(rasterizer) = &mut self.raster
+            && let Some(bytes) = rasterizer.finish()?
+        {
+            animation.add(&bytes)?;
+        }
+        animation.finish()?;
+        // Frame folders and piped frames are not files to list.
+        if matches!(job.format, Format::Frames) || job.pipe.is_some() {
+            return Ok(());
+        }
+        self.record(Queued {
+            job,
+            kind: AssetKind::Replay,
+            width,
+            height,
+            metadata: None,
+        })
+    }
+
+    fn draw(
+        &mut self,
+        queued: Queued,
+        theme: &Theme,
+        artwork: &impl Drawable,
+    ) -> Result<()> {
+        let (width, height) = (queued.width, queued.height);
+        let background = queued.job.background(theme);
+        match queued.job.format {
+            Format::Svg => {
+                let mut canvas = SvgCanvas::new(width, height, background);
+                artwork.draw(&mut canvas, theme);
+                fs::write(&queued.job.out, canvas.finish())?;
+                return self.record(queued);
+            }
+            Format::Jpeg => ensure!(
+                !queued.job.transparent,
+                "JPEG output cannot be transparent"
+            ),
+            Format::Png | Format::Webp | Format::Ktx2 | Format::Exr => {}
+            Format::Gif | Format::Apng | Format::Frames | Format::Rgba => {
+                unreachable!("animations are drawn frame by frame")
+            }
+        }
+        ensure!(
+            queued.job.depth == 8
+                || matches!(queued.job.format, Format::Png | Format::Exr),
+            "only PNG and EXR output can have more than 8 bits per channel"
+        );
+        let factor = queued.job.supersample();
+        let (width, height) = (width * factor, height * factor);
+        let artwork = Supersampled(f64::from(factor), artwork);
+        match self.rasterizer()? {
+            Some(rasterizer) => {
+                let mut scene = Scene::new();
+                artwork.draw(&mut scene, theme);
+                let done =
+                    rasterizer.submit(&scene, width, height, background)?;
+                self.queued.push_back(queued);
+                if let Some(bytes) = done {
+                    let oldest =
+                        self.queued.pop_front().context("lost a job")?;
+                    self.encode(oldest, &bytes)?;
+                }
+                Ok(())
+            }
+            None => {
+                let bytes = render::render_cpu(
+                    &artwork, theme, width, height, background,
+                )?;
+                self.encode(queued, &bytes)
+            }
+        }
+    }
+
+    /// The GPU rasterizer, opened on first use, or `None` to use the CPU.
+    fn rasterizer(&mut self) -> Result<Option<&mut Rasterizer>> {
+        if let Raster::Unopened = self.raster {
+            self.raster = match self.backend {
+                Backend::Cpu => Raster::Cpu,
+                backend => {
+                    let opened = pollster::block_on(Rasterizer::new(
+                        &self.adapter,
+                        self.antialiasing,
+                    ));
+                    match opened {
+                        Ok(rasterizer) => Raster::Gpu(Box::new(rasterizer)),
+                        // An adapter picked by hand is not silently swapped
+                        // for the CPU.
+                        Err(error)
+                            if backend == Backend::Auto
+                                && self.adapter.adapter.is_none() =>
+                        {
+                            log::warn!(
+                                "falling back to the CPU renderer: {error:#}"
+                            );
+                            Raster::Cpu
+                        }
+                        Err(error) => return Err(error),
+                    }
+                }
+            };
+        }
+        Ok(match &mut self.raster {
+            Raster::Gpu(rasterizer) => Some(rasterizer),
+            _ => None,
+        })
+    }
+
+    fn encode(&mut self, queued: Queued, bytes: &[u8]) -> Result<()> {
+        let job = &queued.job;
+        let (path, width, height) = (&job.out, queued.width, queued.height);
+        let factor = job.supersample();
+        match job.format {
+            Format::Png if factor > 1 => {
+                let pixels = render::resolve(bytes, width, height, factor);
+                encode::write_png16(path, width, height, &pixels)
+            }
+            Format::Png => encode::write_png(path, width, height, bytes),
+            Format::Exr => {
+                let pixels = render::resolve(bytes, width, height, factor);
+                encode::write_exr(path, width, height, &pixels)
+            }
+            Format::Jpeg => {
+                encode::write_jpeg(path, width, height, bytes, job.quality)
+            }
+            Format::Webp => {
+                encode::write_webp(path, width, height, bytes, job.quality)
+            }
+            Format::Ktx2 => ktx2::write_ktx2(path, width, height, bytes),
+            Format::Svg => unreachable!("SVG is written without rasterizing"),
+            Format::Gif | Format::Apng | Format::Frames | Format::Rgba => {
+                unreachable!("animations are encoded frame by frame")
+            }
+        }?;
+        self.record(queued)
+    }
+
+    fn record(&mut self, queued: Queued) -> Result<()> {
+        let Some(manifest) = &mut self.manifest else {
+            return Ok(());
+        };
+        let job = &queued.job;
+        manifest.insert(Asset {
+            file: manifest.relative(&job.out),
+            kind: queued.kind,
+            format: job.format.to_possible_value().unwrap().get_name().into(),
+            width: queued.width,
+            height: queued.height,
+            theme: job.theme.as_ref().map_or_else(
+                || "default".into(),
+                |theme| theme.to_string_lossy().into_owned(),
+            ),
+            sha256: manifest::sha256(&job.out)?,
+            metadata: queued.metadata.map(|path| manifest.relative(&path)),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+use anyhow::{Context, Result, anyhow, bail};
+use clap::ValueEnum;
+use vello::util::RenderSurface;
+use vello::wgpu::util::TextureBlitter;
+use vello::wgpu::{
+    self, Adapter, BackendOptions, Backends, CompositeAlphaMode, Device,
+    DeviceDescriptor, DeviceType, Features, Instance, InstanceDescriptor,
+    InstanceFlags, Limits, MemoryBudgetThresholds, PowerPreference,
+    PresentMode, Queue, RequestAdapterOptions, Surface, SurfaceConfiguration,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+
+/// Kind of adapter to favor when none is named explicitly.
+#[derive(Copy, Clone, ValueEnum)]
+pub enum Preference {
+    /// A GPU with its own memory, usually the fastest one.
+    Discrete,
+    /// A GPU sharing memory with the CPU.
+    Integrated,
+    /// Whatever the platform reports as the most power efficient.
+    LowPower,
+}
+
+/// How to pick the adapter rendering is done on.
+#[derive(Clone, Default)]
+pub struct AdapterChoice {
+    /// Index from `--list-adapters` or a case-insensitive part of the name.
+    pub adapter: Option<String>,
+    pub preference: Option<Preference>,
+}
+
+/// Creates an instance honoring the usual `WGPU_*` environment overrides,
+/// like vello's own render context does.
+pub fn instance() -> Instance {
+    Instance::new(&InstanceDescriptor {
+        backends: Backends::from_env().unwrap_or_default(),
+        flags: InstanceFlags::from_build_config().with_env(),
+        memory_budget_thresholds: MemoryBudgetThresholds::default(),
+        backend_options: BackendOptions::from_env_or_default(),
+    })
+}
+
+/// Every adapter of `instance`, in the order `--adapter` indexes them.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn adapters(instance: &Instance) -> Vec<Adapter> {
+    instance.enumerate_adapters(Backends::all())
+}
+
+/// Browsers hand adapters out one at a time on request, leaving none to
+/// list, so adapters are always picked by preference.
+#[cfg(target_arch = "wasm32")]
+pub fn adapters(_instance: &Instance) -> Vec<Adapter> {
+    vec![]
+}
+
+/// One line per adapter, suitable for `--list-adapters`.
+pub fn describe(adapters: &[Adapter]) -> String {
+    adapters
+        .iter()
+        .enumerate()
+        .map(|(index, adapter)| {
+            let info = adapter.get_info();
+            format!(
+                "{index}: {} ({:?}, {:?})\n",
+                info.name, info.device_type, info.backend
+            )
+        })
+        .collect()
+}
+
+/// An adapter together with the device and queue opened on it.
+pub struct Gpu {
+    pub adapter: Adapter,
+    pub device: Device,
+    pub queue: Queue,
+}
+
+impl Gpu {
+    /// Picks an adapter according to `choice`, able to present to `surface`
+    /// if one is given, and opens a device on it.
+    pub async fn new(
+        instance: &Instance,
+        choice: &AdapterChoice,
+        surface: Option<&Surface<'_>>,
+    ) -> Result<Self> {
+        let adapter = select(instance, choice, surface).await?;
+        if let Some(surface) = surface
+            && !adapter.is_surface_supported(surface)
+        {
+            bail!(
+                "adapter {} cannot present to the window",
+                adapter.get_info().name
+            );
+        }
+        let (device, queue) = adapter
+            .request_device(&DeviceDescriptor {
+                label: None,
+                required_features: adapter.features()
+                    & (Features::CLEAR_TEXTURE | Features::PIPELINE_CACHE),
+                required_limits: Limits::default(),
+                ..Default::default()
+            })
+            .await
+            .with_context(|| {
+                format!("failed to open {}", adapter.get_info().name)
+            })?;
+        Ok(Self {
+            adapter,
+            device,
+            queue,
+        })
+    }
+
+    /// Configures `surface` for presenting frames of the given size.
+    pub fn surface<'s>(
+        &self,
+        surface: Surface<'s>,
+        width: u32,
+        height: u32,
+    ) -> Result<RenderSurface<'s>> {
+        let format = surface
+            .get_capabilities(&self.adapter)
+            .formats
+            .into_iter()
+            .find(|format| {
+                matches!(
+                    format,
+                    TextureFormat::Rgba8Unorm | TextureFormat::Bgra8Unorm
+                )
+            })
+            .ok_or_else(|| anyhow!("the window has no RGBA8 format"))?;
+        let (target_texture, target_view) = self.target(width, height);
+        let surface = RenderSurface {
+            surface,
+            config: SurfaceConfiguration {
+                usage: TextureUsages::RENDER_ATTACHMENT,
+                format,
+                width,
+                height,
+                present_mode: PresentMode::AutoVsync,
+                desired_maximum_frame_latency: 2,
+                alpha_mode: CompositeAlphaMode::Auto,
+                view_formats: vec![],
+            },
+            dev_id: 0,
+            format,
+            target_texture,
+            target_view,
+            blitter: TextureBlitter::new(&self.device, format),
+        };
+        surface.surface.configure(&self.device, &surface.config);
+        Ok(surface)
+    }
+
+    /// Resizes `surface` and its render target, both sides must be non-zero.
+    pub fn resize(&self, surface: &mut RenderSurface, width: u32, height: u32) {
+        (surface.target_texture, surface.target_view) =
+            self.target(width, height);
+        surface.config.width = width;
+        surface.config.height = height;
+        surface.surface.configure(&self.device, &surface.config);
+    }
+
+    /// vello renders with a compute shader, so frames go to an intermediate
+    /// storage texture that is then blitted onto the surface.
+    fn target(
+        &self,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = self.device.create_texture(&TextureDescriptor {
+            label: Some("Flight target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsages::STORAGE_BINDING
+                | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+        (texture, view)
+    }
+}
+
+async fn select(
+    instance: &Instance,
+    choice: &AdapterChoice,
+    surface: Option<&Surface<'_>>,
+) -> Result<Adapter> {
+    let mut adapters = adapters(instance);
+    if let Some(wanted) = &choice.adapter {
+        let index = match wanted.parse::<usize>() {
+            Ok(index) => Some(index).filter(|&i| i < adapters.len()),
+            Err(_) => {
+                let wanted = wanted.to_lowercase();
+                adapters.iter().position(|adapter| {
+                    adapter.get_info().name.to_lowercase().contains(&wanted)
+                })
+            }
+        };
+        return index.map(|index| adapters.swap_remove(index)).ok_or_else(
+            || {
+                anyhow!(
+                    "no adapter matches {wanted:?}, available:\n{}",
+                    describe(&adapters).trim_end()
+                )
+            },
+        );
+    }
+    let device_type = match choice.preference {
+        Some(Preference::Discrete) => Some(DeviceType::DiscreteGpu),
+        Some(Preference::Integrated) => Some(DeviceType::IntegratedGpu),
+        Some(Preference::LowPower) => {
+            return instance
+                .request_adapter(&RequestAdapterOptions {
+                    power_preference: PowerPreference::LowPower,
+                    force_fallback_adapter: false,
+                    compatible_surface: surface,
+                })
+                .await
+                .context("no low-power adapter available");
+        }
+        None => None,
+    };
+    // A preference the machine cannot satisfy falls back to the default pick.
+    if let Some(index) = adapters.iter().position(|adapter| {
+        Some(adapter.get_info().device_type) == device_type
+            && surface.is_none_or(|s| adapter.is_surface_supported(s))
+    }) {
+        return Ok(adapters.swap_remove(index));
+    }
+    wgpu::util::initialize_adapter_from_env_or_default(instance, surface)
+        .await
+        .context("no GPU adapter available")
+}
@@ -0,0 +1,192 @@
+use parley::{
+    FontContext, FontStack, FontWeight, Layout, LayoutContext,
+    PositionedLayoutItem, StyleProperty,
+};
+use vello::kurbo::{Affine, Point, Rect, RoundedRect, Size};
+use vello::peniko::{Blob, Brush, Color, Fill};
+use vello::{Glyph, Scene};
+
+use crate::theme::{Font, Theme};
+
+/// A stretch of text in one color, bold if `strong`.
+#[derive(Clone)]
+pub struct Span {
+    pub text: String,
+    pub color: Color,
+    pub strong: bool,
+}
+
+impl Span {
+    pub fn plain(text: impl Into<String>, color: Color) -> Self {
+        Self {
+            text: text.into(),
+            color,
+            strong: false,
+        }
+    }
+
+    pub fn strong(text: impl Into<String>, color: Color) -> Self {
+        Self {
+            text: text.into(),
+            color,
+            strong: true,
+        }
+    }
+}
+
+/// Lines of text drawn over the board in the window, laid out in the
+/// theme's font, on a panel of the background color.
+pub struct Hud {
+    fonts: FontContext,
+    layouts: LayoutContext<Brush>,
+    /// Families looked up, in CSS `font-family` form.
+    family: String,
+    /// Size of the text in logical pixels.
+    size: f32,
+}
+
+impl Default for Hud {
+    /// A HUD writing in the bundled font, with the system fonts to fall
+    /// back on.
+    fn default() -> Self {
+        let mut fonts = FontContext::new();
+        for data in Font::BUNDLED {
+            fonts
+                .collection
+                .register_fonts(Blob::from(data.to_vec()), None);
+        }
+        Self {
+            fonts,
+            layouts: LayoutContext::new(),
+            family: Font::default().families(),
+            size: Self::FONT_SIZE,
+        }
+    }
+}
+
+impl Hud {
+    /// Size of the text in logical pixels at the default scale.
+    const FONT_SIZE: f32 = 18.0;
+    /// Space between the panel's edge and the text, in logical pixels.
+    const PADDING: f64 = 8.0;
+
+    /// Writes in `font` from now on, registering the files it brings.
+    pub fn set_font(&mut self, font: &Font) {
+        for data in font.data() {
+            self.fonts.collection.register_fonts(data.clone(), None);
+        }
+        self.family = font.families();
+        self.size = Self::FONT_SIZE * font.scale;
+    }
+
+    /// Size of the panel `lines` of spans take, in physical pixels of a
+    /// window with `scale_factor`.
+    pub fn measure(&mut self, lines: &[Vec<Span>], scale_factor: f64) -> Size {
+        let layout = self.layout(lines, Color::BLACK, scale_factor);
+        Self::panel(&layout, Point::ZERO, scale_factor)
+            .rect()
+            .size()
+    }
+
+    /// Draws `lines` of spans into `scene` with the top left corner of
+    /// their panel at `corner`, in physical pixels of a window with
+    /// `scale_factor`, returning the panel.
+    pub fn draw(
+        &mut self,
+        scene: &mut Scene,
+        theme: &Theme,
+        corner: Point,
+        scale_factor: f64,
+        lines: &[Vec<Span>],
+    ) -> Rect {
+        let layout = self.layout(lines, theme.stroke, scale_factor);
+        let panel = Self::panel(&layout, corner, scale_factor);
+        let backdrop = theme.background.with_alpha(0.8);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, backdrop, None, &panel);
+        let padding = Self::PADDING * scale_factor;
+        let origin =
+            Affine::translate((corner.x + padding, corner.y + padding));
+        for line in layout.lines() {
+            for item in line.items() {
+                let PositionedLayoutItem::GlyphRun(glyphs) = item else {
+                    continue;
+                };
+                let run = glyphs.run();
+                scene
+                    .draw_glyphs(run.font())
+                    .brush(&glyphs.style().brush)
+                    .font_size(run.font_size())
+                    .normalized_coords(run.normalized_coords())
+                    .transform(origin)
+                    .draw(
+                        Fill::NonZero,
+                        glyphs.positioned_glyphs().map(|glyph| Glyph {
+                            id: glyph.id,
+                            x: glyph.x,
+                            y: glyph.y,
+                        }),
+                    );
+            }
+        }
+        panel.rect()
+    }
+
+    /// `lines` laid out, in `color` where spans do not say otherwise.
+    fn layout(
+        &mut self,
+        lines: &[Vec<Span>],
+        color: Color,
+        scale_factor: f64,
+    ) -> Layout<Brush> {
+        let mut text = String::new();
+        let mut ranges = vec![];
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                text.push('\n');
+            }
+            for span in line {
+                let start = text.len();
+                text.push_str(&span.text);
+                ranges.push((start..text.len(), span));
+            }
+        }
+        let scale = scale_factor as f32;
+        let mut builder =
+            self.layouts
+                .ranged_builder(&mut self.fonts, &text, scale, true);
+        builder.push_default(StyleProperty::FontStack(FontStack::Source(
+            self.family.as_str().into(),
+        )));
+        builder.push_default(StyleProperty::FontSize(self.size));
+        builder.push_default(StyleProperty::Brush(Brush::Solid(color)));
+        for (range, span) in ranges {
+            builder.push(
+                StyleProperty::Brush(Brush::Solid(span.color)),
+                range.clone(),
+            );
+            if span.strong {
+                builder
+                    .push(StyleProperty::FontWeight(FontWeight::BOLD), range);
+            }
+        }
+        let mut layout = builder.build(&text);
+        layout.break_all_lines(None);
+        layout
+    }
+
+    /// Panel around `layout` with its top left corner at `corner`.
+    fn panel(
+        layout: &Layout<Brush>,
+        corner: Point,
+        scale_factor: f64,
+    ) -> RoundedRect {
+        let padding = Self::PADDING * scale_factor;
+        RoundedRect::new(
+            corner.x,
+            corner.y,
+            corner.x + f64::from(layout.width()) + 2.0 * padding,
+            corner.y + f64::from(layout.height()) + 2.0 * padding,
+            padding,
+        )
+    }
+}
@@ -0,0 +1,227 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use intel_tex_2::{RgbaSurface, bc7};
+
+// VK_FORMAT_BC7_SRGB_BLOCK
+const VK_FORMAT: u32 = 146;
+
+// KHR_DF_MODEL_BC7
+const COLOR_MODEL: u8 = 134;
+
+const BLOCK: u32 = 4;
+
+const BLOCK_BYTES: usize = 16;
+
+/// Tightly packed RGBA8 pixels of one mip level.
+struct Level {
+    width: u32,
+    height: u32,
+    bytes: Vec<u8>,
+}
+
+impl Level {
+    /// Halves the level with a box filter applied to linear, premultiplied
+    /// colors so edges against transparency don't darken.
+    fn downsample(&self) -> Level {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut bytes = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0f32; 4];
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (x * 2 + dx).min(self.width - 1);
+                    let sy = (y * 2 + dy).min(self.height - 1);
+                    let i = ((sy * self.width + sx) * 4) as usize;
+                    let alpha = f32::from(self.bytes[i + 3]) / 255.0;
+                    for (sum, value) in
+                        sum.iter_mut().zip(&self.bytes[i..i + 3])
+                    {
+                        *sum += to_linear(*value) * alpha;
+                    }
+                    sum[3] += alpha;
+                }
+                let alpha = sum[3] / 4.0;
+                for value in &sum[..3] {
+                    bytes.push(if alpha > 0.0 {
+                        to_srgb(value / 4.0 / alpha)
+                    } else {
+                        0
+                    });
+                }
+                bytes.push((alpha * 255.0).round() as u8);
+            }
+        }
+        Level {
+            width,
+            height,
+            bytes,
+        }
+    }
+
+    fn compress(&self) -> Vec<u8> {
+        let level = self.padded();
+        let surface = RgbaSurface {
+            data: &level.bytes,
+            width: level.width,
+            height: level.height,
+            stride: level.width * 4,
+        };
+        bc7::compress_blocks(&bc7::alpha_basic_settings(), &surface)
+    }
+
+    /// Extends the level to whole compression blocks by repeating its last
+    /// row and column.
+    fn padded(&self) -> Level {
+        let width = self.width.next_multiple_of(BLOCK);
+        let height = self.height.next_multiple_of(BLOCK);
+        let mut bytes = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            let row = (y.min(self.height - 1) * self.width * 4) as usize;
+            for x in 0..width {
+                let i = row + (x.min(self.width - 1) * 4) as usize;
+                bytes.extend_from_slice(&self.bytes[i..i + 4]);
+            }
+        }
+        Level {
+            width,
+            height,
+            bytes,
+        }
+    }
+}
+
+fn to_linear(value: u8) -> f32 {
+    let value = f32::from(value) / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn to_srgb(value: f32) -> u8 {
+    let value = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Writes tightly packed RGBA8 pixels as a KTX2 texture with a full,
+/// BC7-compressed mip chain.
+pub fn write_ktx2(
+    path: &Path,
+    width: u32,
+    height: u32,
+    bytes: &[u8],
+) -> Result<()> {
+    let mut levels = vec![Level {
+        width,
+        height,
+        bytes: bytes.to_vec(),
+    }];
+    while let Some(last) = levels.last()
+        && (last.width > 1 || last.height > 1)
+    {
+        levels.push(last.downsample());
+    }
+    let blocks = levels.iter().map(Level::compress).collect::<Vec<_>>();
+
+    let level_count = levels.len() as u32;
+    let dfd = data_format_descriptor();
+    let kvd = key_values(&[("KTXwriter", env!("CARGO_PKG_NAME"))]);
+    let dfd_offset = 80 + 24 * level_count;
+    let kvd_offset = dfd_offset + dfd.len() as u32;
+
+    // Levels are stored smallest first, each aligned to a whole block.
+    let mut offsets = vec![0; blocks.len()];
+    let mut end =
+        (kvd_offset as usize + kvd.len()).next_multiple_of(BLOCK_BYTES);
+    for (offset, data) in offsets.iter_mut().zip(&blocks).rev() {
+        *offset = end;
+        end = (end + data.len()).next_multiple_of(BLOCK_BYTES);
+    }
+
+    let mut file = Vec::with_capacity(end);
+    file.extend_from_slice(&[
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ]);
+    for value in [
+        VK_FORMAT,
+        1, // typeSize
+        width,
+        height,
+        0, // pixelDepth
+        0, // layerCount
+        1, // faceCount
+        level_count,
+        0, // supercompressionScheme
+        dfd_offset,
+        dfd.len() as u32,
+        kvd_offset,
+        kvd.len() as u32,
+    ] {
+        file.extend_from_slice(&value.to_le_bytes());
+    }
+    // No supercompression global data.
+    file.extend_from_slice(&[0; 16]);
+    for (offset, data) in offsets.iter().zip(&blocks) {
+        let length = data.len() as u64;
+        file.extend_from_slice(&(*offset as u64).to_le_bytes());
+        file.extend_from_slice(&length.to_le_bytes());
+        file.extend_from_slice(&length.to_le_bytes());
+    }
+    file.extend_from_slice(&dfd);
+    file.extend_from_slice(&kvd);
+    for (offset, data) in offsets.iter().zip(&blocks).rev() {
+        file.resize(*offset, 0);
+        file.extend_from_slice(data);
+    }
+    fs::write(path, file)?;
+    Ok(())
+}
+
+/// Basic data format descriptor of sRGB BC7 blocks.
+fn data_format_descriptor() -> Vec<u8> {
+    let mut dfd = Vec::with_capacity(44);
+    dfd.extend_from_slice(&44u32.to_le_bytes());
+    // vendorId and descriptorType of KHR_DF_KHR_DESCRIPTORTYPE_BASICFORMAT
+    dfd.extend_from_slice(&0u32.to_le_bytes());
+    // versionNumber and descriptorBlockSize
+    dfd.extend_from_slice(&2u16.to_le_bytes());
+    dfd.extend_from_slice(&40u16.to_le_bytes());
+    dfd.extend_from_slice(&[
+        COLOR_MODEL,
+        1, // KHR_DF_PRIMARIES_BT709
+        2, // KHR_DF_TRANSFER_SRGB
+        0, // KHR_DF_FLAG_ALPHA_STRAIGHT
+    ]);
+    let block = (BLOCK - 1) as u8;
+    dfd.extend_from_slice(&[block, block, 0, 0]);
+    dfd.extend_from_slice(&[BLOCK_BYTES as u8, 0, 0, 0, 0, 0, 0, 0]);
+    // One sample covering the whole 128-bit block.
+    dfd.extend_from_slice(&0u16.to_le_bytes());
+    dfd.extend_from_slice(&[127, 0]);
+    dfd.extend_from_slice(&[0; 4]);
+    dfd.extend_from_slice(&0u32.to_le_bytes());
+    dfd.extend_from_slice(&u32::MAX.to_le_bytes());
+    dfd
+}
+
+fn key_values(entries: &[(&str, &str)]) -> Vec<u8> {
+    let mut kvd = vec![];
+    for (key, value) in entries {
+        let length = key.len() + value.len() + 2;
+        kvd.extend_from_slice(&(length as u32).to_le_bytes());
+        kvd.extend_from_slice(key.as_bytes());
+        kvd.push(0);
+        kvd.extend_from_slice(value.as_bytes());
+        kvd.push(0);
+        kvd.resize(kvd.len().next_multiple_of(4), 0);
+    }
+    kvd
+}
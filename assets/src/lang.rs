@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::board::Player;
+
+/// The strings of the game window in one language, looked up by key with
+/// `{name}` placeholders filled in. Keys a bundle lacks are shown in
+/// English.
+#[derive(Clone)]
+pub struct Strings {
+    code: String,
+    texts: HashMap<String, String>,
+    english: HashMap<String, String>,
+}
+
+impl Strings {
+    /// Bundles built into the game, by language code.
+    pub const BUILTIN: [(&str, &str); 2] = [
+        ("en", include_str!("../lang/en.toml")),
+        ("fr", include_str!("../lang/fr.toml")),
+    ];
+
+    /// The built-in bundle for language `code`, or else the TOML file of
+    /// keys and texts at that path.
+    pub fn find(code: &str) -> Result<Self> {
+        let builtin =
+            Self::BUILTIN.iter().find(|(builtin, _)| *builtin == code);
+        let text = match builtin {
+            Some((_, text)) => text.to_string(),
+            None => fs::read_to_string(code)
+                .with_context(|| format!("failed to read {code}"))?,
+        };
+        let texts = toml::from_str(&text)
+            .with_context(|| format!("invalid strings {code}"))?;
+        let code = match builtin {
+            Some(_) => code.to_string(),
+            None => Path::new(code)
+                .file_stem()
+                .map_or(code.into(), |stem| stem.to_string_lossy().into()),
+        };
+        Ok(Self {
+            code,
+            texts,
+            english: Self::english(),
+        })
+    }
+
+    fn english() -> HashMap<String, String> {
+        toml::from_str(Self::BUILTIN[0].1).expect("the English bundle parses")
+    }
+
+    /// Language code of the bundle, the file stem for bundles read from
+    /// files.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// The text of `key`, the key itself if no bundle has it.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.texts
+            .get(key)
+            .or_else(|| self.english.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// The text of `key` with each `{name}` of `values` replaced.
+    pub fn fill(&self, key: &str, values: &[(&str, &dyn ToString)]) -> String {
+        values
+            .iter()
+            .fold(self.get(key).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), &value.to_string())
+            })
+    }
+
+    /// `on` or `off` as `state` says.
+    pub fn toggle(&self, state: bool) -> &str {
+        self.get(if state { "on" } else { "off" })
+    }
+
+    /// Name of palette color `index`.
+    pub fn color(&self, index: usize) -> String {
+        let name = Player::color_name(index);
+        self.texts
+            .get(&name)
+            .or_else(|| self.english.get(&name))
+            .cloned()
+            .unwrap_or(name)
+    }
+}
+
+impl Default for Strings {
+    /// The English strings.
+    fn default() -> Self {
+        Self {
+            code: Self::BUILTIN[0].0.into(),
+            texts: HashMap::new(),
+            english: Self::english(),
+        }
+    }
+}
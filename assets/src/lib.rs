@@ -0,0 +1,48 @@
+//! Artwork of the flight board: the drawables, the renderers turning them
+//! into pixels or vectors, and the encoders writing asset files.
+//!
+//! On `wasm32` the crate is the game window in a browser canvas, started
+//! from [`web`], without the exporters and encoders writing files.
+//!
+//! On Android it is also a library, the game run full screen by the
+//! native activity entry point of [`android`].
+
+#[cfg(target_os = "android")]
+pub mod android;
+pub mod board;
+pub mod canvas;
+pub mod dice;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod encode;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod export;
+pub mod gpu;
+pub mod hud;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ktx2;
+pub mod lang;
+pub mod looks;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod manifest;
+pub mod menu;
+pub mod number;
+pub mod pattern;
+pub mod pieces;
+pub mod plane;
+pub mod play;
+mod readback;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod remote;
+pub mod render;
+pub mod settings;
+pub mod sprites;
+pub mod svg;
+pub mod table;
+pub mod theme;
+pub mod variant;
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+
+pub use render::Image;
+#[cfg(not(target_arch = "wasm32"))]
+pub use render::render_board;
@@ -0,0 +1,237 @@
+use vello::peniko::Color;
+use vello::peniko::color::palette::css;
+
+use crate::theme::{LineStyle, Shading, ShadingKind, Theme};
+
+/// A built-in look of the board, resolved into the [`Theme`] the drawables
+/// read. Each part defaults to the classic look, so a new look only
+/// overrides what it changes.
+pub trait Look {
+    /// Name the look is chosen by on the command line and in settings.
+    fn name(&self) -> &'static str;
+
+    /// Player colors, in seating order.
+    fn players(&self) -> Vec<Color> {
+        Theme::default().players
+    }
+
+    /// Color the board is composited over.
+    fn background(&self) -> Color {
+        Theme::default().background
+    }
+
+    /// Color of the spots drawn on cells and home bases.
+    fn accent(&self) -> Color {
+        Theme::default().accent
+    }
+
+    /// Color of the track outlines.
+    fn stroke(&self) -> Color {
+        Theme::default().stroke
+    }
+
+    /// Width of the track outlines at the default cell size.
+    fn stroke_width(&self) -> f64 {
+        Theme::default().stroke_width
+    }
+
+    /// Size of the spots relative to the default.
+    fn spot_scale(&self) -> f64 {
+        1.0
+    }
+
+    /// Line drawn down the middle of each runway, none if not drawn.
+    fn runway_line(&self) -> Option<LineStyle> {
+        Theme::default().runway_line
+    }
+
+    /// How each home base shades its player's color.
+    fn base(&self) -> Shading {
+        Shading::default()
+    }
+
+    /// Whether the middle of the board is decorated with a compass rose.
+    fn hub(&self) -> bool {
+        true
+    }
+
+    /// Whether home bases and planes cast shadows.
+    fn shadows(&self) -> bool {
+        true
+    }
+
+    /// Whether cells and planes are marked with a pattern per color.
+    fn patterns(&self) -> bool {
+        false
+    }
+
+    /// The theme drawing the board in this look.
+    fn theme(&self) -> Theme {
+        Theme {
+            players: self.players(),
+            background: self.background(),
+            accent: self.accent(),
+            stroke: self.stroke(),
+            stroke_width: self.stroke_width(),
+            spot_scale: self.spot_scale(),
+            runway_line: self.runway_line(),
+            base: self.base(),
+            hub: self.hub(),
+            shadows: self.shadows(),
+            patterns: self.patterns(),
+            ..Theme::default()
+        }
+    }
+}
+
+/// The red, yellow, blue and green board on grey.
+pub struct Classic;
+
+impl Look for Classic {
+    fn name(&self) -> &'static str {
+        "classic"
+    }
+}
+
+/// Soft colors on cream, with lighter outlines.
+pub struct Pastel;
+
+impl Look for Pastel {
+    fn name(&self) -> &'static str {
+        "pastel"
+    }
+
+    fn players(&self) -> Vec<Color> {
+        vec![
+            Color::from_rgb8(0xf4, 0x9a, 0x9a),
+            Color::from_rgb8(0xf6, 0xe0, 0x8c),
+            Color::from_rgb8(0x9c, 0xc3, 0xf0),
+            Color::from_rgb8(0xa8, 0xdc, 0xa8),
+            Color::from_rgb8(0xc9, 0xa8, 0xe0),
+            Color::from_rgb8(0xf6, 0xbc, 0x8c),
+        ]
+    }
+
+    fn background(&self) -> Color {
+        Color::from_rgb8(0xfa, 0xf3, 0xe6)
+    }
+
+    fn stroke(&self) -> Color {
+        Color::from_rgb8(0x6b, 0x5e, 0x70)
+    }
+
+    fn stroke_width(&self) -> f64 {
+        3.0
+    }
+}
+
+/// Bright colors on black with white outlines and black spots, every
+/// color pair contrasting as WCAG asks, heavier strokes, larger spots and
+/// patterns telling the colors apart.
+pub struct HighContrast;
+
+impl Look for HighContrast {
+    fn name(&self) -> &'static str {
+        "high-contrast"
+    }
+
+    fn players(&self) -> Vec<Color> {
+        vec![
+            Color::from_rgb8(0xff, 0x30, 0x30),
+            Color::from_rgb8(0xa8, 0x90, 0x00),
+            Color::from_rgb8(0x3c, 0x78, 0xff),
+            Color::from_rgb8(0x20, 0xa0, 0x40),
+            Color::from_rgb8(0xb4, 0x5c, 0xff),
+            Color::from_rgb8(0xe0, 0x60, 0x00),
+        ]
+    }
+
+    fn background(&self) -> Color {
+        css::BLACK
+    }
+
+    fn accent(&self) -> Color {
+        css::BLACK
+    }
+
+    fn stroke(&self) -> Color {
+        css::WHITE
+    }
+
+    fn stroke_width(&self) -> f64 {
+        8.0
+    }
+
+    fn spot_scale(&self) -> f64 {
+        1.25
+    }
+
+    fn runway_line(&self) -> Option<LineStyle> {
+        None
+    }
+
+    fn base(&self) -> Shading {
+        Shading {
+            kind: ShadingKind::Flat,
+            ..Shading::default()
+        }
+    }
+
+    fn shadows(&self) -> bool {
+        false
+    }
+
+    fn patterns(&self) -> bool {
+        true
+    }
+}
+
+/// Muted colors on charcoal, with light outlines and dark spots, for dim
+/// rooms.
+pub struct Dark;
+
+impl Look for Dark {
+    fn name(&self) -> &'static str {
+        "dark"
+    }
+
+    fn players(&self) -> Vec<Color> {
+        vec![
+            Color::from_rgb8(0xb0, 0x55, 0x52),
+            Color::from_rgb8(0xc2, 0xa4, 0x4e),
+            Color::from_rgb8(0x52, 0x74, 0xb0),
+            Color::from_rgb8(0x55, 0x96, 0x62),
+            Color::from_rgb8(0x85, 0x5c, 0xa0),
+            Color::from_rgb8(0xc0, 0x78, 0x48),
+        ]
+    }
+
+    fn background(&self) -> Color {
+        Color::from_rgb8(0x1e, 0x1f, 0x24)
+    }
+
+    fn accent(&self) -> Color {
+        Color::from_rgb8(0x2b, 0x2d, 0x33)
+    }
+
+    fn stroke(&self) -> Color {
+        Color::from_rgb8(0xd8, 0xda, 0xde)
+    }
+
+    fn stroke_width(&self) -> f64 {
+        4.0
+    }
+}
+
+/// Every built-in look, the classic one first.
+pub fn all() -> [&'static dyn Look; 4] {
+    [&Classic, &Pastel, &HighContrast, &Dark]
+}
+
+/// The theme of the built-in look called `name`, if there is one.
+pub fn named(name: &str) -> Option<Theme> {
+    all()
+        .into_iter()
+        .find(|look| look.name() == name)
+        .map(|look| look.theme())
+}
@@ -1,443 +1,575 @@
-use std::f64::consts::PI;
-use std::fs::File;
-use std::num::NonZeroUsize;
-use std::path::Path;
+use std::ffi::OsStr;
+use std::fs;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::{Result, anyhow, bail};
-use vello::kurbo::{Affine, BezPath, Circle, Point, Rect, Stroke, Triangle};
-use vello::peniko::color::HueDirection;
-use vello::peniko::color::palette::css;
-use vello::peniko::{Color, Fill};
-use vello::util::{RenderContext, block_on_wgpu};
-use vello::wgpu::wgt::{CommandEncoderDescriptor, TextureDescriptor};
-use vello::wgpu::{
-    BufferDescriptor, BufferUsages, Extent3d, MapMode, TexelCopyBufferInfo,
-    TexelCopyBufferLayout, TextureDimension, TextureFormat, TextureUsages,
-};
-use vello::{
-    AaConfig, AaSupport, RenderParams, Renderer, RendererOptions, Scene,
+use anyhow::{Context, Result, ensure};
+use clap::{Parser, Subcommand};
+use flight_core::protocol::PORT;
+use flight_core::server;
+use flight_core::{
+    Board, Game, GameRecord, RuleSet, Server, ServerEvent, Simulation, Stats,
+    Tuning, Weights, ai,
 };
 
-fn main() -> Result<()> {
-    env_logger::init();
-    pollster::block_on(render())?;
-    Ok(())
-}
+use assets::export::{Backend, Batch, Exporter, Job};
+use assets::gpu::{self, AdapterChoice, Preference};
+use assets::lang::Strings;
+use assets::manifest::Manifest;
+use assets::play;
+use assets::remote;
+use assets::render::Antialiasing;
+use assets::theme::Theme;
 
-async fn render() -> Result<()> {
-    let mut context = RenderContext::new();
-    let device_id = context
-        .device(None)
-        .await
-        .ok_or_else(|| anyhow!("no such render context"))?;
-    let handle = &mut context.devices[device_id];
-    let device = &handle.device;
-    let queue = &handle.queue;
+/// Renders the flight board background to an image file.
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    let mut renderer = Renderer::new(
-        device,
-        RendererOptions {
-            num_init_threads: NonZeroUsize::new(1),
-            antialiasing_support: AaSupport::area_only(),
-            ..Default::default()
-        },
-    )
-    .or_else(|_| bail!("failed to create renderer"))?;
-    let (width, height) = (Player::DIMENSION as u32, Player::DIMENSION as u32);
+    #[command(flatten)]
+    job: Job,
 
-    let scene = Player::create_scene()?;
-    let size = Extent3d {
-        width,
-        height,
-        depth_or_array_layers: 1,
-    };
-    let target = device.create_texture(&TextureDescriptor {
-        label: Some("Flight texture"),
-        size,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: TextureDimension::D2,
-        format: TextureFormat::Rgba8Unorm,
-        usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
-        view_formats: &[],
-    });
-    let view = target.create_view(&Default::default());
-    renderer
-        .render_to_texture(
-            device,
-            queue,
-            &scene,
-            &view,
-            &RenderParams {
-                base_color: css::GRAY.lerp(
-                    css::WHITE,
-                    0.5,
-                    HueDirection::Increasing,
-                ),
-                width,
-                height,
-                antialiasing_method: AaConfig::Area,
-            },
-        )
-        .or_else(|_| bail!("Got non-Send/Sync error from rendering"))?;
-    let stride = (width * 4).next_multiple_of(256);
-    let buffer = device.create_buffer(&BufferDescriptor {
-        label: Some("val"),
-        size: (stride * height).into(),
-        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
-        mapped_at_creation: false,
-    });
-    let mut encoder =
-        device.create_command_encoder(&CommandEncoderDescriptor {
-            label: Some("Copy out buffer"),
-        });
-    encoder.copy_texture_to_buffer(
-        target.as_image_copy(),
-        TexelCopyBufferInfo {
-            buffer: &buffer,
-            layout: TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(stride),
-                rows_per_image: None,
-            },
-        },
-        size,
-    );
-    queue.submit([encoder.finish()]);
+    /// JSON manifest to record the generated files in, created if missing.
+    #[arg(short, long, global = true)]
+    manifest: Option<PathBuf>,
 
-    let slice = buffer.slice(..);
+    /// GPU to render on, by index from `--list-adapters` or part of its
+    /// name.
+    #[arg(long, global = true)]
+    adapter: Option<String>,
 
-    let (tx, rx) = futures_intrusive::channel::shared::oneshot_channel();
-    slice.map_async(MapMode::Read, move |v| tx.send(v).unwrap());
-    block_on_wgpu(device, rx.receive())
-        .map(|r| r.map_err(|e| e.into()))
-        .unwrap_or_else(|| bail!("channel was closed"))?;
+    /// Kind of GPU to favor when `--adapter` is not given.
+    #[arg(long, global = true, value_enum)]
+    prefer: Option<Preference>,
 
-    let data = slice.get_mapped_range();
-    let mut bytes = Vec::<u8>::with_capacity((width * height * 4).try_into()?);
-    for row in 0..height {
-        let start = (row * stride).try_into()?;
-        bytes.extend(&data[start..start + (width * 4) as usize]);
-    }
-    let path = Path::new("background.png");
-    let mut file = File::create(path)?;
-    let mut encoder = png::Encoder::new(&mut file, width, height);
-    encoder.set_color(png::ColorType::Rgba);
-    encoder.set_depth(png::BitDepth::Eight);
-    let mut writer = encoder.write_header()?;
-    writer.write_image_data(&bytes)?;
-    writer.finish()?;
-    Ok(())
-}
+    /// Antialiasing method of the GPU renderer.
+    #[arg(long, global = true, value_enum,
+          default_value_t = Antialiasing::Area)]
+    aa: Antialiasing,
 
-trait Drawable {
-    fn draw(&self, scene: &mut Scene);
-}
+    /// Rasterizer used for raster formats.
+    #[arg(long, global = true, value_enum, default_value_t = Backend::Auto)]
+    backend: Backend,
 
-struct Player {
-    color: Color,
-    affine: Affine,
+    /// Print the available GPU adapters and exit.
+    #[arg(long)]
+    list_adapters: bool,
 }
 
-#[derive(Copy, Clone)]
-enum CellKind {
-    #[allow(unused)]
-    Triangle0,
-    Triangle90,
-    Triangle180,
-    Triangle270,
-    VBlock,
-    HBlock,
+#[derive(Subcommand)]
+enum Command {
+    /// Play games on the board in a window instead of writing a file. Who
+    /// plays each seat, the theme, language and antialiasing are chosen on
+    /// its settings screen and saved between runs, `--theme` and `--lang`
+    /// replacing the theme and language for the run.
+    Play {
+        /// TOML file of house rules, the classic rules otherwise.
+        #[arg(long)]
+        rules: Option<PathBuf>,
+        /// Seed of the dice for the first game, a random one otherwise.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Milliseconds a plane takes to move on by a cell, jump or fly.
+        #[arg(long, default_value_t = 150)]
+        step_ms: u64,
+        /// Language of the menus and HUD, `en` or `fr`, or a TOML file of
+        /// strings keyed like the built-in ones.
+        #[arg(long)]
+        lang: Option<String>,
+    },
+    /// Render every job of a TOML batch file, sharing one GPU device.
+    Batch {
+        /// File of `[[job]]` tables taking the same keys as the options,
+        /// with paths relative to it.
+        jobs: PathBuf,
+    },
+    /// Replay a saved game from its seed, checking every turn against the
+    /// recorded state checksums.
+    Verify {
+        /// Saved game to check.
+        game: PathBuf,
+    },
+    /// Print the contrast of every pair of theme colors drawn one over the
+    /// other, failing if any is below what WCAG asks for text or graphics.
+    Contrast,
+    /// Print statistics over saved games as JSON.
+    Stats {
+        /// Saved games to count.
+        games: Vec<PathBuf>,
+    },
+    /// Convert a game between a saved game, the compact binary record,
+    /// which files ending in `.flb` hold, and game notation, which files
+    /// ending in `.fgn` hold.
+    Convert {
+        /// Saved game, binary record or game notation to read.
+        from: PathBuf,
+        /// File to write, a binary record if it ends in `.flb`, game
+        /// notation if it ends in `.fgn` and a saved game otherwise.
+        to: PathBuf,
+    },
+    /// Play games between computer players without drawing them and print
+    /// how often each won.
+    Simulate {
+        /// Games to play.
+        #[arg(long, default_value_t = 100)]
+        games: u64,
+        /// Computer players by name, as `players` lists them. Two play
+        /// unless more are given.
+        #[arg(long, default_value = "heuristic")]
+        p1: String,
+        #[arg(long, default_value = "random")]
+        p2: String,
+        #[arg(long)]
+        p3: Option<String>,
+        #[arg(long, requires = "p3")]
+        p4: Option<String>,
+        #[arg(long, requires = "p4")]
+        p5: Option<String>,
+        #[arg(long, requires = "p5")]
+        p6: Option<String>,
+        /// Seed of the first game, the next ones counting up from it.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// TOML file of house rules, the classic rules otherwise.
+        #[arg(long)]
+        rules: Option<PathBuf>,
+        /// JSON file to write the summary and statistics to.
+        #[arg(long)]
+        json: Option<PathBuf>,
+        /// Milliseconds each player may think about a move at most, as
+        /// long as it likes otherwise.
+        #[arg(long)]
+        budget: Option<u64>,
+        /// Games played at once, one per core by default.
+        #[arg(short, long, default_value_t = 0)]
+        jobs: usize,
+    },
+    /// List the computer players known by name.
+    Players,
+    /// Host a lobby for players joining over the network with `join`,
+    /// each room playing once every seat is taken.
+    Serve {
+        #[arg(long, default_value_t = PORT)]
+        port: u16,
+        /// Port to take WebSocket connections from browsers on as well.
+        #[arg(long)]
+        websocket: Option<u16>,
+        /// Directory to save the games to once they are over.
+        #[arg(long)]
+        save: Option<PathBuf>,
+        /// Seconds a player dropping out of a game has to come back.
+        #[arg(long, default_value_t = server::GRACE.as_secs())]
+        grace: u64,
+        /// Computer player taking over from players who did not come back
+        /// in time, as `players` lists them. They forfeit otherwise.
+        #[arg(long)]
+        substitute: Option<String>,
+    },
+    /// Take a seat in a room of a lobby hosted with `serve`, choosing the
+    /// moves in the terminal. Joins the first room waiting for players
+    /// unless told otherwise.
+    Join {
+        /// Host of the server, with its port unless it is the default.
+        address: String,
+        #[arg(long, default_value = "player")]
+        name: String,
+        /// Room to join, by number.
+        #[arg(long, conflicts_with = "create")]
+        room: Option<u64>,
+        /// Take back a seat after dropping out, with the token given when
+        /// taking it.
+        #[arg(long, conflicts_with_all = ["room", "create"])]
+        resume: Option<String>,
+        /// Open a room for this many players instead.
+        #[arg(long)]
+        create: Option<usize>,
+        /// TOML file of house rules for the room opened, the classic rules
+        /// otherwise.
+        #[arg(long, requires = "create")]
+        rules: Option<PathBuf>,
+        /// Password of the room, or the one to give the room opened.
+        #[arg(long)]
+        password: Option<String>,
+        /// Computer player choosing the moves instead, as `players` lists
+        /// them.
+        #[arg(long)]
+        ai: Option<String>,
+    },
+    /// Watch the game of a room in a lobby hosted with `serve` without
+    /// playing it.
+    Watch {
+        /// Host of the server, with its port unless it is the default.
+        address: String,
+        /// Room to watch, by number.
+        room: u64,
+        #[arg(long, default_value = "spectator")]
+        name: String,
+        /// Password of the room.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Play a friend directly, without a server: host a game for two and
+    /// wait for them to connect, or connect to the one they host.
+    Peer {
+        /// Host of the friend's game, with its port unless it is the
+        /// default. Hosts a game instead if none.
+        address: Option<String>,
+        /// Port to host the game on.
+        #[arg(long, default_value_t = PORT, conflicts_with = "address")]
+        port: u16,
+        #[arg(long, default_value = "player")]
+        name: String,
+        /// TOML file of house rules for the game hosted, the classic rules
+        /// otherwise.
+        #[arg(long, conflicts_with = "address")]
+        rules: Option<PathBuf>,
+        /// Computer player choosing the moves instead, as `players` lists
+        /// them.
+        #[arg(long)]
+        ai: Option<String>,
+    },
+    /// Tune the weights of the heuristic player by self-play, mutating the
+    /// best weights so far every generation and keeping mutants beating
+    /// them.
+    Tune {
+        /// TOML file to write the best weights to.
+        out: PathBuf,
+        /// Weights to start from, the defaults otherwise.
+        #[arg(long)]
+        start: Option<PathBuf>,
+        #[arg(long, default_value_t = 10)]
+        generations: u32,
+        /// Mutants tried each generation.
+        #[arg(long, default_value_t = 8)]
+        candidates: usize,
+        /// Games each mutant plays against the best weights.
+        #[arg(long, default_value_t = 200)]
+        games: u64,
+        /// How far mutants stray, each weight scaled by up to e to this
+        /// power.
+        #[arg(long, default_value_t = 0.3)]
+        spread: f64,
+        /// Number of players seated for the games.
+        #[arg(long, default_value_t = 2)]
+        players: usize,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// TOML file of house rules, the classic rules otherwise.
+        #[arg(long)]
+        rules: Option<PathBuf>,
+        /// Games played at once, one per core by default.
+        #[arg(short, long, default_value_t = 0)]
+        jobs: usize,
+    },
 }
 
-pub struct Cell {
-    kind: CellKind,
-    color: Color,
-    affine: Affine,
-    origin: Point,
-}
-
-impl Cell {
-    const DIM: f64 = 128.0;
-
-    const DIM_X2: f64 = Self::DIM * 2.0;
-
-    const DIM_X4: f64 = Self::DIM * 4.0;
-
-    const RADIUS: f64 = Self::DIM * 0.35;
-
-    fn new(
-        kind: CellKind,
-        color: Color,
-        affine: Affine,
-        origin: Point,
-    ) -> Cell {
-        Self {
-            kind,
-            color,
-            affine,
-            origin,
+impl Args {
+    fn adapter(&self) -> AdapterChoice {
+        AdapterChoice {
+            adapter: self.adapter.clone(),
+            preference: self.prefer,
         }
     }
-}
-
-impl Player {
-    const DIMENSION: f64 = Cell::DIM * 17.0;
-
-    const RADIUS: f64 = Cell::DIM * 0.6;
 
-    const COLORS: [Color; 4] = [css::RED, css::YELLOW, css::BLUE, css::GREEN];
-
-    const fn new(color: Color, affine: Affine) -> Self {
-        Player { color, affine }
+    fn exporter(&self) -> Result<Exporter> {
+        let manifest = match &self.manifest {
+            Some(path) => Some(Manifest::load(path)?),
+            None => None,
+        };
+        Ok(Exporter::new(
+            self.backend,
+            self.adapter(),
+            self.aa,
+            manifest,
+        ))
     }
+}
 
-    fn color(index: usize) -> Color {
-        Self::COLORS[index % Self::COLORS.len()]
+fn main() -> Result<()> {
+    env_logger::init();
+    let args = Args::parse();
+    if args.list_adapters {
+        print!("{}", gpu::describe(&gpu::adapters(&gpu::instance())));
+        return Ok(());
     }
-
-    fn create_scene() -> Result<Scene> {
-        let mut scene = Scene::new();
-        let players = [
-            Player::new(Self::COLORS[0], Affine::IDENTITY),
-            Player::new(
-                Self::COLORS[1],
-                Affine::rotate(PI / 2.0)
-                    .then_translate((Self::DIMENSION, 0.0).into()),
-            ),
-            Player::new(
-                Self::COLORS[2],
-                Affine::rotate(PI)
-                    .then_translate((Self::DIMENSION, Self::DIMENSION).into()),
-            ),
-            Player::new(
-                Self::COLORS[3],
-                Affine::rotate(PI * 3.0 / 2.0)
-                    .then_translate((0.0, Self::DIMENSION).into()),
-            ),
-        ];
-        for (i, player) in players.iter().enumerate() {
-            let mut cells = vec![];
-            let mut origin = Point::new(Cell::DIM_X2, Cell::DIM_X4);
-            let mut color_index = i + Self::COLORS.len() - 1;
-            cells.push(Cell::new(
-                CellKind::Triangle180,
-                Self::color(color_index),
-                player.affine,
-                origin,
-            ));
-            color_index += 1;
-            cells.push(Cell::new(
-                CellKind::VBlock,
-                Self::color(color_index),
-                player.affine,
-                origin,
-            ));
-            origin += (Cell::DIM, 0.0);
-            color_index += 1;
-            cells.push(Cell::new(
-                CellKind::VBlock,
-                Self::color(color_index),
-                player.affine,
-                origin,
-            ));
-            origin += (Cell::DIM, 0.0);
-            color_index += 1;
-            cells.push(Cell::new(
-                CellKind::Triangle270,
-                Self::color(color_index),
-                player.affine,
-                origin,
-            ));
-            color_index += 1;
-            cells.push(Cell::new(
-                CellKind::Triangle90,
-                Self::color(color_index),
-                player.affine,
-                origin,
-            ));
-            origin += (0.0, -Cell::DIM);
-            color_index += 1;
-            cells.push(Cell::new(
-                CellKind::HBlock,
-                Self::color(color_index),
-                player.affine,
-                origin,
-            ));
-            origin += (0.0, -Cell::DIM);
-            color_index += 1;
-            cells.push(Cell::new(
-                CellKind::HBlock,
-                Self::color(color_index),
-                player.affine,
-                origin,
-            ));
-            origin += (Cell::DIM_X2, -Cell::DIM_X2);
-            color_index += 1;
-            cells.push(Cell::new(
-                CellKind::Triangle180,
-                Self::color(color_index),
-                player.affine,
-                origin,
-            ));
-            for _ in 0..5 {
-                color_index += 1;
-                cells.push(Cell::new(
-                    CellKind::VBlock,
-                    Self::color(color_index),
-                    player.affine,
-                    origin,
-                ));
-                origin += (Cell::DIM, 0.0);
+    let mut exporter = args.exporter()?;
+    match &args.command {
+        Some(Command::Play {
+            rules,
+            seed,
+            step_ms,
+            lang,
+        }) => {
+            let theme = args.job.theme.as_deref().map(Theme::find);
+            return play::run(
+                theme.transpose()?,
+                lang.as_deref().map(Strings::find).transpose()?,
+                args.job.board()?,
+                match rules {
+                    Some(path) => RuleSet::load(path)?,
+                    None => RuleSet::default(),
+                },
+                seed.unwrap_or_else(rand::random),
+                Duration::from_millis(*step_ms),
+                args.adapter(),
+            );
+        }
+        Some(Command::Verify { game }) => {
+            let game = Game::load(game)?;
+            game.record().verify()?;
+            println!("{} turns verified", game.record().plies.len());
+            return Ok(());
+        }
+        Some(Command::Contrast) => {
+            let pairs = args.job.theme()?.color_pairs();
+            let mut low = 0;
+            for pair in &pairs {
+                let verdict = if pair.ratio >= pair.minimum {
+                    "ok"
+                } else {
+                    low += 1;
+                    "LOW"
+                };
+                println!(
+                    "{:>10} on {:<10} {:>5.2}:1, needs {}:1 {verdict}",
+                    pair.front, pair.back, pair.ratio, pair.minimum
+                );
             }
-            for cell in cells {
-                cell.draw(&mut scene);
+            ensure!(
+                low == 0,
+                "{low} of {} pairs contrast too little",
+                pairs.len()
+            );
+            return Ok(());
+        }
+        Some(Command::Stats { games }) => {
+            let mut stats = Stats::default();
+            for game in games {
+                stats.add_record(Game::load(game)?.record())?;
             }
-            player.draw(&mut scene);
+            println!("{}", stats.to_json()?);
+            return Ok(());
         }
-        Ok(scene)
-    }
-}
-
-impl Drawable for Player {
-    fn draw(&self, scene: &mut Scene) {
-        scene.fill(
-            Fill::NonZero,
-            self.affine,
-            self.color,
-            None,
-            &Rect::from_origin_size(
-                Point::ORIGIN,
-                (Cell::DIM_X4, Cell::DIM_X4),
-            ),
-        );
-        let p = Point::new(Cell::DIM, Cell::DIM);
-        for center in [
-            p,
-            p + (0.0, Cell::DIM_X2),
-            p + (Cell::DIM_X2, 0.0),
-            p + (Cell::DIM_X2, Cell::DIM_X2),
-        ] {
-            scene.fill(
-                Fill::NonZero,
-                self.affine,
-                css::WHITE,
-                None,
-                &Circle::new(center, Self::RADIUS),
-            );
+        Some(Command::Convert { from, to }) => {
+            fn extension(path: &Path) -> Option<&str> {
+                path.extension().and_then(OsStr::to_str)
+            }
+            let game = match extension(from) {
+                Some("flb") => Game::from_record(GameRecord::read(from)?)?,
+                Some("fgn") => {
+                    let text = fs::read_to_string(from).with_context(|| {
+                        format!("failed to read {}", from.display())
+                    })?;
+                    let record =
+                        GameRecord::from_text(&text).with_context(|| {
+                            format!("invalid game notation {}", from.display())
+                        })?;
+                    Game::from_record(record)?
+                }
+                _ => Game::load(from)?,
+            };
+            match extension(to) {
+                Some("flb") => fs::write(to, game.record().to_bytes()?)?,
+                Some("fgn") => fs::write(to, game.record().to_text()?)?,
+                _ => game.save(to)?,
+            }
+            return Ok(());
         }
-        let mut p = Point::new(0.0, Cell::DIM_X4 + Cell::DIM_X2);
-        let mut path = BezPath::new();
-        path.move_to(p);
-        p += (Cell::DIM_X2, -Cell::DIM_X2);
-        path.line_to(p);
-        p += (Cell::DIM_X2, 0.0);
-        path.line_to(p);
-        p += (0.0, -Cell::DIM_X2);
-        path.line_to(p);
-        p += (Cell::DIM_X2, -Cell::DIM_X2);
-        path.line_to(p);
-        p += (Cell::DIM * 5.0, 0.0);
-        path.line_to(p);
-        scene.stroke(&Stroke::new(5.0), self.affine, css::BLACK, None, &path);
-
-        let mut path = BezPath::new();
-        let mut p = Point::new(Cell::DIM_X2, Cell::DIM_X4 + Cell::DIM_X2);
-        path.move_to(p);
-        p += (Cell::DIM_X4, 0.0);
-        path.line_to(p);
-        p += (0.0, -Cell::DIM_X4);
-        path.line_to(p);
-        p += (Cell::DIM * 5.0, 0.0);
-        path.line_to(p);
-        scene.stroke(&Stroke::new(5.0), self.affine, css::BLACK, None, &path);
-
-        let mut path = BezPath::new();
-        let mut p = Point::new(Cell::DIM_X2, Cell::DIM_X4 * 2.0);
-        path.move_to(p);
-        p += (Cell::DIM * 5.0, 0.0);
-        path.line_to(p);
-        p -= (0.0, Cell::DIM);
-        path.line_to(p);
-        path.line_to(p + (Cell::DIM * 1.5, Cell::DIM * 1.5));
-        p += (0.0, Cell::DIM * 3.0);
-        path.line_to(p);
-        p -= (0.0, Cell::DIM);
-        path.line_to(p);
-        p -= (Cell::DIM * 5.0, 0.0);
-        path.line_to(p);
-        path.close_path();
-        scene.fill(Fill::NonZero, self.affine, self.color, None, &path);
-        scene.stroke(&Stroke::new(5.0), self.affine, css::BLACK, None, &path);
-
-        let mut p = Point::new(Cell::DIM * 2.5, Cell::DIM * 8.5);
-        for _ in 0..6 {
-            scene.fill(
-                Fill::NonZero,
-                self.affine,
-                css::WHITE,
-                None,
-                &Circle::new(p, Cell::RADIUS),
+        Some(Command::Simulate {
+            games,
+            p1,
+            p2,
+            p3,
+            p4,
+            p5,
+            p6,
+            seed,
+            rules,
+            json,
+            budget,
+            jobs,
+        }) => {
+            let mut players = vec![p1.clone(), p2.clone()];
+            players.extend([p3, p4, p5, p6].into_iter().flatten().cloned());
+            ensure!(
+                Board::PLAYERS.contains(&players.len()),
+                "boards seat one of {:?} players",
+                Board::PLAYERS
             );
-            p += (Cell::DIM, 0.0);
+            let simulation = Simulation {
+                board: Board::new(players.len()),
+                rules: match rules {
+                    Some(path) => RuleSet::load(path)?,
+                    None => RuleSet::default(),
+                },
+                players,
+                games: *games,
+                budget: budget.map_or(Duration::MAX, Duration::from_millis),
+                seed: *seed,
+            };
+            let summary = simulation.run(*jobs)?;
+            print!("{}", summary.table());
+            if let Some(path) = json {
+                fs::write(path, summary.to_json()?).with_context(|| {
+                    format!("failed to write {}", path.display())
+                })?;
+            }
+            return Ok(());
         }
-    }
-}
-
-impl Drawable for Cell {
-    fn draw(&self, scene: &mut Scene) {
-        let origin = self.origin;
-        let mut center = Point::ZERO;
-        let shape = match self.kind {
-            CellKind::Triangle0 => Triangle::new(
-                origin,
-                origin + (Self::DIM_X2, 0.0),
-                origin + (0.0, Self::DIM_X2),
-            )
-            .into(),
-            CellKind::Triangle90 => Triangle::new(
-                origin,
-                origin + (Self::DIM_X2, 0.0),
-                origin + (Self::DIM_X2, Self::DIM_X2),
-            )
-            .into(),
-            CellKind::Triangle180 => Triangle::new(
-                origin,
-                origin + (0.0, Self::DIM_X2),
-                origin + (-Self::DIM_X2, Self::DIM_X2),
-            )
-            .into(),
-            CellKind::Triangle270 => Triangle::new(
-                origin,
-                origin + (Self::DIM_X2, Self::DIM_X2),
-                origin + (0.0, Self::DIM_X2),
-            )
-            .into(),
-            _ => None,
-        };
-        if let Some(shape) = shape.as_ref() {
-            scene.fill(Fill::NonZero, self.affine, self.color, None, shape);
-            center = shape.inscribed_circle().center;
+        Some(Command::Players) => {
+            for name in ai::names() {
+                println!("{name}");
+            }
+            return Ok(());
         }
-        let shape = match self.kind {
-            CellKind::VBlock => {
-                Rect::from_origin_size(origin, (Self::DIM, Self::DIM_X2)).into()
+        Some(Command::Serve {
+            port,
+            websocket,
+            save,
+            grace,
+            substitute,
+        }) => {
+            let server = Server {
+                websocket: websocket.map(|port| {
+                    SocketAddr::from((Ipv4Addr::UNSPECIFIED, port))
+                }),
+                grace: Duration::from_secs(*grace),
+                substitute: substitute.clone(),
+            };
+            println!("waiting for players on port {port}");
+            if let Some(websocket) = websocket {
+                println!("and for browsers on port {websocket}");
             }
-            CellKind::HBlock => {
-                Rect::from_origin_size(origin, (Self::DIM_X2, Self::DIM)).into()
+            let address = (Ipv4Addr::UNSPECIFIED, *port);
+            server.run(address, |room, game, message| {
+                if let Some(text) = remote::describe(message, &room.seated) {
+                    println!("{}: {text}", room.name);
+                }
+                if let (ServerEvent::Over { .. }, Some(save)) = (message, save)
+                {
+                    let path = save.join(format!("room-{}.toml", room.id));
+                    if let Err(error) = game.save(&path) {
+                        log::warn!("failed to save {}: {error:#}", room.name);
+                    }
+                }
+            })?;
+            return Ok(());
+        }
+        Some(Command::Join {
+            address,
+            name,
+            room,
+            resume,
+            create,
+            rules,
+            password,
+            ai,
+        }) => {
+            let room = match (resume, room, create) {
+                (Some(token), ..) => remote::Room::Resume(token.clone()),
+                (None, Some(room), _) => remote::Room::Id(*room),
+                (None, None, Some(players)) => remote::Room::Create {
+                    players: *players,
+                    rules: match rules {
+                        Some(path) => RuleSet::load(path)?,
+                        None => RuleSet::default(),
+                    },
+                },
+                (None, None, None) => remote::Room::First,
+            };
+            return remote::run(
+                address,
+                name,
+                room,
+                password.as_deref(),
+                ai.as_deref(),
+            );
+        }
+        Some(Command::Watch {
+            address,
+            room,
+            name,
+            password,
+        }) => {
+            return remote::watch(address, name, *room, password.as_deref());
+        }
+        Some(Command::Peer {
+            address,
+            port,
+            name,
+            rules,
+            ai,
+        }) => {
+            let peer = match address {
+                Some(address) => remote::Friend::Join(address.clone()),
+                None => remote::Friend::Host {
+                    port: *port,
+                    rules: match rules {
+                        Some(path) => RuleSet::load(path)?,
+                        None => RuleSet::default(),
+                    },
+                },
+            };
+            return remote::peer(peer, name, ai.as_deref());
+        }
+        Some(Command::Tune {
+            out,
+            start,
+            generations,
+            candidates,
+            games,
+            spread,
+            players,
+            seed,
+            rules,
+            jobs,
+        }) => {
+            ensure!(
+                Board::PLAYERS.contains(players),
+                "boards seat one of {:?} players",
+                Board::PLAYERS
+            );
+            let tuning = Tuning {
+                board: Board::new(*players),
+                rules: match rules {
+                    Some(path) => RuleSet::load(path)?,
+                    None => RuleSet::default(),
+                },
+                generations: *generations,
+                candidates: *candidates,
+                games: *games,
+                spread: *spread,
+                seed: *seed,
+            };
+            let start = match start {
+                Some(path) => Weights::load(path)?,
+                None => Weights::default(),
+            };
+            let best = tuning.run(start, *jobs, |generation| {
+                println!(
+                    "generation {}: best mutant won {:.1}% against {:.1}%{}",
+                    generation.number,
+                    100.0 * generation.rate,
+                    100.0 * generation.par,
+                    if generation.improved { ", kept" } else { "" }
+                );
+            })?;
+            let toml = best.to_toml()?;
+            print!("{toml}");
+            fs::write(out, toml).with_context(|| {
+                format!("failed to write {}", out.display())
+            })?;
+            return Ok(());
+        }
+        Some(Command::Batch { jobs }) => {
+            for job in Batch::load(jobs)?.jobs {
+                exporter.export(job)?;
             }
-            _ => None,
-        };
-        if let Some(shape) = shape.as_ref() {
-            scene.fill(Fill::NonZero, self.affine, self.color, None, shape);
-            center = shape.center();
         }
-        scene.fill(
-            Fill::NonZero,
-            self.affine,
-            css::WHITE,
-            None,
-            &Circle::new(center, Cell::RADIUS),
-        );
+        None => exporter.export(args.job)?,
     }
+    exporter.finish()
 }
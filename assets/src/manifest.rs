@@ -0,0 +1,90 @@
+use std::fmt::Write;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// What a generated file contains.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetKind {
+    Board,
+    Sprites,
+    Dice,
+    Replay,
+}
+
+/// Description of one generated file.
+#[derive(Serialize, Deserialize)]
+pub struct Asset {
+    /// Path relative to the manifest.
+    pub file: String,
+    pub kind: AssetKind,
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    /// Theme file the asset was drawn with, or "default".
+    pub theme: String,
+    /// Hex encoded SHA-256 of the file contents.
+    pub sha256: String,
+    /// Sidecar metadata such as sprite placements.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+}
+
+/// Index of generated assets, updated in place by every run so that
+/// regenerating one asset keeps the others listed.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub assets: Vec<Asset>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut manifest = if path.exists() {
+            let text = fs::read_to_string(path)?;
+            serde_json::from_str::<Manifest>(&text).with_context(|| {
+                format!("invalid manifest {}", path.display())
+            })?
+        } else {
+            Manifest::default()
+        };
+        manifest.path = path.to_path_buf();
+        Ok(manifest)
+    }
+
+    /// Path of `file` as recorded in the manifest.
+    pub fn relative(&self, file: &Path) -> String {
+        let base = self.path.parent().unwrap_or(Path::new(""));
+        file.strip_prefix(base)
+            .unwrap_or(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Adds `asset`, replacing any previous entry for the same file.
+    pub fn insert(&mut self, asset: Asset) {
+        self.assets.retain(|entry| entry.file != asset.file);
+        self.assets.push(asset);
+        self.assets.sort_by(|a, b| a.file.cmp(&b.file));
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(&self.path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+pub fn sha256(path: &Path) -> Result<String> {
+    let digest = Sha256::digest(fs::read(path)?);
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    Ok(hex)
+}
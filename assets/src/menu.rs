@@ -0,0 +1,101 @@
+use vello::Scene;
+use vello::kurbo::{Affine, Point, Rect, Size, Stroke};
+use vello::peniko::Color;
+
+use crate::hud::{Hud, Span};
+use crate::theme::Theme;
+
+/// A screen of buttons stacked in the middle of the window under a
+/// heading, each a line of text on a panel doing what its `T` says.
+pub struct Menu<T> {
+    pub heading: Vec<Vec<Span>>,
+    pub buttons: Vec<(String, T)>,
+}
+
+impl<T: Copy> Menu<T> {
+    /// Space between the heading and buttons, in logical pixels.
+    const GAP: f64 = 12.0;
+
+    /// Panels of the heading and every button, in physical pixels of a
+    /// window of `width` by `height` with `scale_factor`.
+    fn panels(
+        &self,
+        hud: &mut Hud,
+        width: u32,
+        height: u32,
+        scale_factor: f64,
+    ) -> (Rect, Vec<Rect>) {
+        let gap = Self::GAP * scale_factor;
+        let heading = hud.measure(&self.heading, scale_factor);
+        let buttons: Vec<_> = self
+            .buttons
+            .iter()
+            .map(|(label, _)| {
+                hud.measure(&label_lines(label, Color::BLACK), scale_factor)
+            })
+            .collect();
+        let total = heading.height
+            + buttons.iter().map(|size| gap + size.height).sum::<f64>();
+        let (width, height) = (f64::from(width), f64::from(height));
+        let mut top = (height - total) / 2.0;
+        let mut place = |size: Size| {
+            let corner = Point::new((width - size.width) / 2.0, top);
+            top += size.height + gap;
+            Rect::from_origin_size(corner, size)
+        };
+        let heading = place(heading);
+        (heading, buttons.into_iter().map(place).collect())
+    }
+
+    /// Draws the menu into `scene`, outlining the `highlighted` button.
+    pub fn draw(
+        &self,
+        hud: &mut Hud,
+        scene: &mut Scene,
+        theme: &Theme,
+        (width, height): (u32, u32),
+        scale_factor: f64,
+        highlighted: Option<usize>,
+    ) {
+        let (heading, buttons) = self.panels(hud, width, height, scale_factor);
+        hud.draw(scene, theme, heading.origin(), scale_factor, &self.heading);
+        let stroke = Stroke::new(2.0 * scale_factor);
+        let buttons = self.buttons.iter().zip(buttons).enumerate();
+        for (index, ((label, _), panel)) in buttons {
+            let line = label_lines(label, theme.stroke);
+            hud.draw(scene, theme, panel.origin(), scale_factor, &line);
+            if highlighted == Some(index) {
+                let outline = panel.to_rounded_rect(8.0 * scale_factor);
+                scene.stroke(
+                    &stroke,
+                    Affine::IDENTITY,
+                    theme.stroke,
+                    None,
+                    &outline,
+                );
+            }
+        }
+    }
+
+    /// Index of the button at `point`, in physical pixels.
+    pub fn at(
+        &self,
+        hud: &mut Hud,
+        (width, height): (u32, u32),
+        scale_factor: f64,
+        point: Point,
+    ) -> Option<usize> {
+        let (_, buttons) = self.panels(hud, width, height, scale_factor);
+        buttons.iter().position(|panel| panel.contains(point))
+    }
+
+    /// What the button at `index` does.
+    pub fn choice(&self, index: usize) -> Option<T> {
+        self.buttons.get(index).map(|(_, choice)| *choice)
+    }
+}
+
+/// The text of a button in `color`, which does not change its size.
+fn label_lines(label: &str, color: Color) -> Vec<Vec<Span>> {
+    vec![vec![Span::plain(label, color)]]
+}
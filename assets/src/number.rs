@@ -0,0 +1,229 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use parley::{
+    FontContext, FontStack, FontWeight, Layout, LayoutContext,
+    PositionedLayoutItem, StyleProperty,
+};
+use skrifa::instance::{LocationRef, NormalizedCoord, Size};
+use skrifa::outline::{DrawSettings, OutlinePen};
+use skrifa::{FontRef, GlyphId, MetadataProvider};
+use vello::kurbo::{Affine, BezPath, Join, Point, Shape, Stroke};
+use vello::peniko::Blob;
+
+use crate::board::Drawable;
+use crate::canvas::Canvas;
+use crate::theme::{Font, Theme};
+
+thread_local! {
+    static LETTERING: RefCell<Lettering> = RefCell::new(Lettering::default());
+}
+
+/// A number written in the theme's font centered on a point, its glyphs
+/// drawn as outlines so it looks the same on every canvas.
+pub struct Number {
+    affine: Affine,
+    value: usize,
+    /// Height of a digit at the font's default scale.
+    height: f64,
+}
+
+impl Number {
+    /// `value` centered on `center` under `affine`, its digits `height`
+    /// tall.
+    pub fn new(
+        affine: Affine,
+        center: Point,
+        value: usize,
+        height: f64,
+    ) -> Self {
+        Self {
+            affine: affine * Affine::translate(center.to_vec2()),
+            value,
+            height,
+        }
+    }
+
+    /// Outlines of every digit in `font`, centered on the origin.
+    fn path(&self, font: &Font) -> BezPath {
+        let text = self.value.to_string();
+        let path = LETTERING
+            .with_borrow_mut(|lettering| lettering.outline(font, &text));
+        let bounds = path.bounding_box();
+        if bounds.height() <= 0.0 {
+            return BezPath::new();
+        }
+        let scale = self.height * f64::from(font.scale) / bounds.height();
+        Affine::scale(scale)
+            * Affine::translate(-bounds.center().to_vec2())
+            * path
+    }
+}
+
+impl Drawable for Number {
+    /// Fills the digits over a halo of the accent color, so they stay
+    /// readable on any cell.
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let path = self.path(&theme.font);
+        let halo = Stroke::new(self.height * f64::from(theme.font.scale) * 0.3)
+            .with_join(Join::Round);
+        canvas.stroke(&halo, self.affine, theme.accent, &path);
+        canvas.fill(self.affine, theme.stroke, &path);
+    }
+}
+
+/// Fonts laying out numbers, kept for every number a thread draws so the
+/// system fonts are looked up once.
+struct Lettering {
+    fonts: FontContext,
+    layouts: LayoutContext<()>,
+    /// Ids of the theme font files registered so far.
+    registered: HashSet<u64>,
+}
+
+impl Default for Lettering {
+    fn default() -> Self {
+        let mut fonts = FontContext::new();
+        for data in Font::BUNDLED {
+            fonts
+                .collection
+                .register_fonts(Blob::from(data.to_vec()), None);
+        }
+        Self {
+            fonts,
+            layouts: LayoutContext::new(),
+            registered: HashSet::new(),
+        }
+    }
+}
+
+impl Lettering {
+    /// Size the glyphs are outlined at, before the number scales them.
+    const SIZE: f32 = 64.0;
+
+    /// Outlines of the bold glyphs of `text` in `font`, with the origin at
+    /// the start of its baseline and y pointing down.
+    fn outline(&mut self, font: &Font, text: &str) -> BezPath {
+        for data in font.data() {
+            if self.registered.insert(data.id()) {
+                self.fonts.collection.register_fonts(data.clone(), None);
+            }
+        }
+        let families = font.families();
+        let mut builder =
+            self.layouts
+                .ranged_builder(&mut self.fonts, text, 1.0, true);
+        builder.push_default(StyleProperty::FontStack(FontStack::Source(
+            families.as_str().into(),
+        )));
+        builder.push_default(StyleProperty::FontSize(Self::SIZE));
+        builder.push_default(StyleProperty::FontWeight(FontWeight::BOLD));
+        let mut layout: Layout<()> = builder.build(text);
+        layout.break_all_lines(None);
+        let mut path = BezPath::new();
+        for line in layout.lines() {
+            for item in line.items() {
+                let PositionedLayoutItem::GlyphRun(glyphs) = item else {
+                    continue;
+                };
+                let run = glyphs.run();
+                let data = run.font();
+                let Ok(face) =
+                    FontRef::from_index(data.data.as_ref(), data.index)
+                else {
+                    continue;
+                };
+                let outlines = face.outline_glyphs();
+                let coords: Vec<_> = run
+                    .normalized_coords()
+                    .iter()
+                    .map(|&coord| NormalizedCoord::from_bits(coord))
+                    .collect();
+                let size = Size::new(run.font_size());
+                for glyph in glyphs.positioned_glyphs() {
+                    let Some(outline) = outlines.get(GlyphId::new(glyph.id))
+                    else {
+                        continue;
+                    };
+                    // Outlines point y up from the baseline.
+                    let mut pen = Pen {
+                        path: &mut path,
+                        affine: Affine::translate((
+                            f64::from(glyph.x),
+                            f64::from(glyph.y),
+                        )) * Affine::FLIP_Y,
+                    };
+                    let settings =
+                        DrawSettings::unhinted(size, LocationRef::new(&coords));
+                    // A glyph that fails to draw is left blank.
+                    let _ = outline.draw(settings, &mut pen);
+                }
+            }
+        }
+        path
+    }
+}
+
+/// Glyph outlines added to a path under a transform.
+struct Pen<'a> {
+    path: &'a mut BezPath,
+    affine: Affine,
+}
+
+impl Pen<'_> {
+    fn point(&self, x: f32, y: f32) -> Point {
+        self.affine * Point::new(x.into(), y.into())
+    }
+}
+
+impl OutlinePen for Pen<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.path.move_to(self.point(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.path.line_to(self.point(x, y));
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.path.quad_to(self.point(cx0, cy0), self.point(x, y));
+    }
+
+    fn curve_to(
+        &mut self,
+        cx0: f32,
+        cy0: f32,
+        cx1: f32,
+        cy1: f32,
+        x: f32,
+        y: f32,
+    ) {
+        self.path.curve_to(
+            self.point(cx0, cy0),
+            self.point(cx1, cy1),
+            self.point(x, y),
+        );
+    }
+
+    fn close(&mut self) {
+        self.path.close_path();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_sized_by_the_theme_font() {
+        let number = Number::new(Affine::IDENTITY, Point::ZERO, 42, 10.0);
+        let mut font = Font::default();
+        font.family = Some("No Such Family".into());
+        let bounds = number.path(&font).bounding_box();
+        assert!((bounds.height() - 10.0).abs() < 1e-6, "{bounds:?}");
+        assert!(bounds.center().to_vec2().hypot() < 1e-6, "{bounds:?}");
+        font.scale = 2.0;
+        let bounds = number.path(&font).bounding_box();
+        assert!((bounds.height() - 20.0).abs() < 1e-6, "{bounds:?}");
+    }
+}
@@ -0,0 +1,138 @@
+use vello::kurbo::{Affine, BezPath, Circle, Rect, Stroke};
+use vello::peniko::Color;
+
+use crate::canvas::Canvas;
+
+/// Geometric marks laid over a player color, so players who cannot tell
+/// the colors apart still tell the players apart.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Pattern {
+    Stripes,
+    Dots,
+    Crosshatch,
+    Zigzag,
+    Bars,
+    Checks,
+}
+
+impl Pattern {
+    /// A pattern for each of the six seats' colors, in their order.
+    pub const ALL: [Pattern; 6] = [
+        Pattern::Stripes,
+        Pattern::Dots,
+        Pattern::Crosshatch,
+        Pattern::Zigzag,
+        Pattern::Bars,
+        Pattern::Checks,
+    ];
+
+    /// Opacity of the marks, over colors dark or light.
+    pub const ALPHA: f32 = 0.4;
+
+    /// The pattern marking player color `index`.
+    pub fn for_color(index: usize) -> Self {
+        Self::ALL[index % Self::ALL.len()]
+    }
+
+    /// Draws the pattern in `color` over all of `area` under `affine`, its
+    /// marks `spacing` apart. Callers clip it to the shape it marks.
+    pub fn draw(
+        self,
+        canvas: &mut impl Canvas,
+        affine: Affine,
+        area: Rect,
+        spacing: f64,
+        color: Color,
+    ) {
+        let stroke = Stroke::new(spacing * 0.25);
+        // Diagonals run across the area's bounding square.
+        let side = area.width().max(area.height());
+        let steps = (side * 2.0 / spacing).ceil() as usize + 1;
+        let diagonals = |slope: f64| {
+            let mut path = BezPath::new();
+            for step in 0..steps {
+                let offset = step as f64 * spacing - side;
+                if slope > 0.0 {
+                    path.move_to((area.x0 + offset, area.y0));
+                    path.line_to((area.x0 + offset + side, area.y0 + side));
+                } else {
+                    path.move_to((area.x0 + offset, area.y0 + side));
+                    path.line_to((area.x0 + offset + side, area.y0));
+                }
+            }
+            path
+        };
+        match self {
+            Pattern::Stripes => {
+                canvas.stroke(&stroke, affine, color, &diagonals(1.0));
+            }
+            Pattern::Crosshatch => {
+                canvas.stroke(&stroke, affine, color, &diagonals(1.0));
+                canvas.stroke(&stroke, affine, color, &diagonals(-1.0));
+            }
+            Pattern::Dots => {
+                let (columns, rows) = (
+                    (area.width() / spacing).ceil() as usize + 1,
+                    (area.height() / spacing).ceil() as usize + 1,
+                );
+                for row in 0..rows {
+                    for column in 0..columns {
+                        // Every other row shifts by half, packing the dots.
+                        let shift = (row % 2) as f64 * spacing / 2.0;
+                        let center = (
+                            area.x0 + column as f64 * spacing + shift,
+                            area.y0 + row as f64 * spacing,
+                        );
+                        let dot = Circle::new(center, spacing * 0.2);
+                        canvas.fill(affine, color, &dot);
+                    }
+                }
+            }
+            Pattern::Zigzag => {
+                let mut path = BezPath::new();
+                let rows = (area.height() / spacing).ceil() as usize + 1;
+                let teeth = (area.width() / spacing).ceil() as usize + 1;
+                for row in 0..rows {
+                    let y = area.y0 + row as f64 * spacing;
+                    path.move_to((area.x0, y));
+                    for tooth in 0..teeth {
+                        let x = area.x0 + tooth as f64 * spacing;
+                        path.line_to((x + spacing / 2.0, y + spacing * 0.4));
+                        path.line_to((x + spacing, y));
+                    }
+                }
+                canvas.stroke(&stroke, affine, color, &path);
+            }
+            Pattern::Bars => {
+                let mut path = BezPath::new();
+                let columns = (area.width() / spacing).ceil() as usize + 1;
+                for column in 0..columns {
+                    let x = area.x0 + column as f64 * spacing;
+                    path.move_to((x, area.y0));
+                    path.line_to((x, area.y1));
+                }
+                canvas.stroke(&stroke, affine, color, &path);
+            }
+            Pattern::Checks => {
+                let (columns, rows) = (
+                    (area.width() / spacing).ceil() as usize,
+                    (area.height() / spacing).ceil() as usize,
+                );
+                for row in 0..rows {
+                    // Every other square, alternating along the rows.
+                    for column in (row % 2..columns).step_by(2) {
+                        let corner = (
+                            area.x0 + column as f64 * spacing,
+                            area.y0 + row as f64 * spacing,
+                        );
+                        let square = Rect::from_origin_size(
+                            corner,
+                            (spacing / 2.0, spacing / 2.0),
+                        );
+                        canvas.fill(affine, color, &square);
+                    }
+                }
+            }
+        }
+    }
+}
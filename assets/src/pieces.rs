@@ -0,0 +1,271 @@
+use flight_core::{
+    GameState, Leg, PlaneId, Player as Quadrant, Position, Route,
+};
+use vello::kurbo::{Affine, ParamCurve, Point, QuadBez, Vec2};
+
+use crate::board::Drawable;
+use crate::canvas::Canvas;
+use crate::pattern::Pattern;
+use crate::plane::Plane;
+use crate::theme::Theme;
+
+/// A plane token at a point of the board, its nose along `heading`.
+#[derive(Copy, Clone, Debug)]
+pub struct Token {
+    pub plane: PlaneId,
+    pub center: Point,
+    /// Radians clockwise from pointing right.
+    pub heading: f64,
+}
+
+/// The planes of a game drawn as tokens in their players' colors, over a
+/// board drawn under the same root transform.
+#[derive(Clone)]
+pub struct Pieces {
+    affine: Affine,
+    tokens: Vec<Token>,
+    /// Token radius, which also scales their outlines.
+    radius: f64,
+}
+
+impl Pieces {
+    /// Tokens for every plane of `state` on `board`, whose metrics place
+    /// them. Planes sharing a spot are fanned out so each stays visible,
+    /// and head for the next cell of their way, or the middle of the board
+    /// from their hangars and home.
+    pub fn new(
+        affine: Affine,
+        board: &flight_core::Board,
+        state: &GameState,
+    ) -> Self {
+        let metrics = board.metrics();
+        let track = board.track();
+        let seats = board.seats();
+        let mut tokens: Vec<Token> = vec![];
+        let mut spots: Vec<Point> = vec![];
+        let middle = board.dimension() / 2.0;
+        let middle = Point::new(middle, middle);
+        for side in state.sides() {
+            let hangars = Quadrant::hangars(metrics);
+            for (plane, position) in side.planes.into_iter().enumerate() {
+                let (center, next) = match position {
+                    Position::Hangar => {
+                        (seats[side.seat] * hangars[plane], middle)
+                    }
+                    Position::Track(cell) => {
+                        let next = track.successor(cell, side.seat);
+                        let center = track.cells()[cell].center;
+                        let next = next.map(|next| track.cells()[next].center);
+                        (center, next.unwrap_or(middle))
+                    }
+                    Position::Home => {
+                        (track.cells()[track.goal(side.seat)].center, middle)
+                    }
+                };
+                let shared = spots
+                    .iter()
+                    .filter(|spot| spot.distance(center) < 1e-6)
+                    .count();
+                spots.push(center);
+                let fan = Vec2::new(1.0, -1.0) * metrics.dim * 0.12;
+                tokens.push(Token {
+                    plane: PlaneId {
+                        seat: side.seat,
+                        plane,
+                    },
+                    center: center + fan * shared as f64,
+                    heading: heading(center, next, 0.0),
+                });
+            }
+        }
+        Self::from_tokens(affine, tokens, metrics.dim * 0.3)
+    }
+
+    pub fn from_tokens(
+        affine: Affine,
+        tokens: Vec<Token>,
+        radius: f64,
+    ) -> Self {
+        Self {
+            affine,
+            tokens,
+            radius,
+        }
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Plane whose token covers `point`, in board pixels, the one drawn on
+    /// top where fanned out tokens overlap.
+    pub fn at(&self, point: Point) -> Option<PlaneId> {
+        let token = self
+            .tokens
+            .iter()
+            .rev()
+            .find(|token| token.center.distance(point) <= self.radius);
+        token.map(|token| token.plane)
+    }
+
+    /// Tokens a fraction `t` of the way to where they are in `to`, easing
+    /// in and out so moves start and land gently.
+    pub fn lerp(&self, to: &Pieces, t: f64) -> Pieces {
+        let eased = t * t * (3.0 - 2.0 * t);
+        let tokens = self
+            .tokens
+            .iter()
+            .map(|token| {
+                let end = to
+                    .tokens
+                    .iter()
+                    .find(|end| end.plane == token.plane)
+                    .map_or(token.center, |end| end.center);
+                Token {
+                    center: token.center.lerp(end, eased),
+                    ..*token
+                }
+            })
+            .collect();
+        Self::from_tokens(self.affine, tokens, self.radius)
+    }
+
+    /// Tokens a fraction `t` of the way to where they are in `to`, the
+    /// planes of `route` flying leg by leg along it over `board` and the
+    /// others, sent home by a capture, sliding straight there.
+    pub fn glide(
+        &self,
+        to: &Pieces,
+        board: &flight_core::Board,
+        seat: usize,
+        route: &Route,
+        t: f64,
+    ) -> Pieces {
+        let mut pieces = self.lerp(to, t);
+        for token in &mut pieces.tokens {
+            let plane = token.plane;
+            if plane.seat != seat || !route.planes.contains(&plane.plane) {
+                continue;
+            }
+            let center = |pieces: &Pieces| {
+                let token = pieces.tokens.iter().find(|t| t.plane == plane);
+                token.map(|token| token.center)
+            };
+            if let (Some(start), Some(end)) = (center(self), center(to)) {
+                let path = Flightpath::new(start, board, &route.legs, end);
+                token.center = path.at(t);
+                // Nose along the way, looking a little ahead.
+                let ahead = path.at(t + 0.01);
+                let behind = path.at(t - 0.01);
+                token.heading = heading(behind, ahead, token.heading);
+            }
+        }
+        pieces
+    }
+}
+
+/// The way a token takes during a move, through the center of every cell
+/// it passes, arcing over the board on jumps and flights.
+struct Flightpath {
+    start: Point,
+    /// Each leg and the point it ends on.
+    legs: Vec<(Leg, Point)>,
+}
+
+impl Flightpath {
+    /// How high jumps and flights arc, as a fraction of their length.
+    const JUMP_ARC: f64 = 0.4;
+    const FLY_ARC: f64 = 0.25;
+
+    /// The way from `start` along `legs` to `end`, where the token lands
+    /// fanned out beside the planes already there.
+    fn new(
+        start: Point,
+        board: &flight_core::Board,
+        legs: &[Leg],
+        end: Point,
+    ) -> Self {
+        let cells = board.track().cells();
+        let mut legs: Vec<(Leg, Point)> = legs
+            .iter()
+            .map(|&leg| {
+                let (Leg::Step(cell) | Leg::Jump(cell) | Leg::Fly(cell)) = leg;
+                (leg, cells[cell].center)
+            })
+            .collect();
+        if let Some((_, last)) = legs.last_mut() {
+            *last = end;
+        }
+        Self { start, legs }
+    }
+
+    /// Point a fraction `t` of the way, every leg taking as long and the
+    /// whole way easing in and out.
+    fn at(&self, t: f64) -> Point {
+        let eased = t.clamp(0.0, 1.0);
+        let eased = eased * eased * (3.0 - 2.0 * eased);
+        if self.legs.is_empty() {
+            return self.start;
+        }
+        let progress = eased * self.legs.len() as f64;
+        let index = (progress.floor() as usize).min(self.legs.len() - 1);
+        let from = match index {
+            0 => self.start,
+            _ => self.legs[index - 1].1,
+        };
+        let (leg, to) = self.legs[index];
+        let local = progress - index as f64;
+        let arc = match leg {
+            Leg::Step(_) => return from.lerp(to, local),
+            Leg::Jump(_) => Self::JUMP_ARC,
+            Leg::Fly(_) => Self::FLY_ARC,
+        };
+        // Arcs bulge to the left of the way as seen on screen.
+        let along = to - from;
+        let normal = Vec2::new(along.y, -along.x) * arc;
+        let control = from.midpoint(to) + normal;
+        QuadBez::new(from, control, to).eval(local)
+    }
+}
+
+impl Drawable for Pieces {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let planes: Vec<Plane> = self
+            .tokens
+            .iter()
+            .map(|token| {
+                let seat = token.plane.seat;
+                Plane::new(
+                    self.affine,
+                    token.center,
+                    theme.player(seat),
+                    self.radius,
+                    token.heading,
+                )
+                .with_pattern(Pattern::for_color(seat))
+            })
+            .collect();
+        if theme.shadows {
+            for plane in &planes {
+                plane.draw_shadow(canvas, theme);
+            }
+        }
+        for plane in &planes {
+            plane.draw(canvas, theme);
+        }
+    }
+}
+
+/// Heading from `from` to `to`, `fallback` when they are the same point.
+fn heading(from: Point, to: Point, fallback: f64) -> f64 {
+    let way = to - from;
+    if way.hypot2() < 1e-12 {
+        fallback
+    } else {
+        way.atan2()
+    }
+}
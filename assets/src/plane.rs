@@ -0,0 +1,126 @@
+use vello::kurbo::{Affine, BezPath, Point, Rect, Shape, Stroke, Vec2};
+use vello::peniko::Color;
+
+use crate::board::{self, Drawable};
+use crate::canvas::Canvas;
+use crate::pattern::Pattern;
+use crate::theme::Theme;
+
+/// A plane piece seen from above: a stylized aircraft in `color`, its
+/// wingspan twice `radius` and its nose pointing along `heading`.
+pub struct Plane {
+    /// Places the unit silhouette on the board.
+    affine: Affine,
+    /// Places the silhouette's shadow, offset toward the bottom right.
+    shadow: Affine,
+    color: Color,
+    /// Marks the plane as its player's when the theme asks for patterns.
+    pattern: Option<Pattern>,
+}
+
+impl Plane {
+    /// Width of the outline on the unit silhouette.
+    const OUTLINE: f64 = 0.08;
+
+    /// A plane centered on `center` under `affine`, `heading` radians
+    /// clockwise from pointing right.
+    pub fn new(
+        affine: Affine,
+        center: Point,
+        color: Color,
+        radius: f64,
+        heading: f64,
+    ) -> Self {
+        let place = |center: Point| {
+            affine
+                * Affine::translate(center.to_vec2())
+                * Affine::rotate(heading)
+                * Affine::scale(radius)
+        };
+        let lift = Vec2::new(radius, radius) * 0.25;
+        Self {
+            affine: place(center),
+            shadow: place(center + lift),
+            color,
+            pattern: None,
+        }
+    }
+
+    /// Marks the plane with `pattern`, drawn if the theme asks for them.
+    pub fn with_pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Draws the shadow the plane casts on the board, for drawing before
+    /// every plane so none falls over another.
+    pub fn draw_shadow(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let silhouette = Self::outline(theme);
+        board::shadow(canvas, self.shadow, |t| {
+            Affine::scale(1.0 + 0.15 * t) * &silhouette
+        });
+    }
+
+    /// Outline of a plane nose right within the unit circle: a rounded
+    /// fuselage, swept wings and a tailplane.
+    pub fn silhouette() -> BezPath {
+        // The upper half from behind the nose to the tail, mirrored below.
+        let half = [
+            (0.65, -0.15),
+            (0.3, -0.15),
+            (-0.12, -0.95),
+            (-0.42, -0.95),
+            (-0.3, -0.15),
+            (-0.68, -0.13),
+            (-0.86, -0.5),
+            (-1.0, -0.5),
+            (-0.95, -0.08),
+        ];
+        let mut path = BezPath::new();
+        path.move_to((1.0, 0.0));
+        path.quad_to((0.97, -0.15), half[0]);
+        for &point in &half[1..] {
+            path.line_to(point);
+        }
+        for &(x, y) in half[1..].iter().rev() {
+            path.line_to((x, -y));
+        }
+        let (x, y) = half[0];
+        path.line_to((x, -y));
+        path.quad_to((0.97, 0.15), (1.0, 0.0));
+        path.close_path();
+        path
+    }
+
+    /// The theme's plane skin fitted over the silhouette, the silhouette
+    /// itself without one.
+    fn outline(theme: &Theme) -> BezPath {
+        let silhouette = Self::silhouette();
+        let skin = theme.plane_skin.as_ref();
+        skin.and_then(|skin| skin.fit(silhouette.bounding_box()))
+            .unwrap_or(silhouette)
+    }
+
+    /// Area the plane covers with its outline.
+    pub fn bounds(&self) -> Rect {
+        let outline = Self::OUTLINE / 2.0;
+        let unit = Self::silhouette().bounding_box().inflate(outline, outline);
+        self.affine.transform_rect_bbox(unit)
+    }
+}
+
+impl Drawable for Plane {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let silhouette = Self::outline(theme);
+        canvas.fill(self.affine, self.color, &silhouette);
+        if let Some(pattern) = self.pattern.filter(|_| theme.patterns) {
+            let color = theme.stroke.with_alpha(Pattern::ALPHA);
+            let area = silhouette.bounding_box();
+            canvas.clip(self.affine, &silhouette, |canvas| {
+                pattern.draw(canvas, self.affine, area, 0.3, color);
+            });
+        }
+        let stroke = Stroke::new(Self::OUTLINE);
+        canvas.stroke(&stroke, self.affine, theme.stroke, &silhouette);
+    }
+}
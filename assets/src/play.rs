@@ -0,0 +1,1054 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use flight_core::{GameState, RuleSet, ai};
+use vello::kurbo::{Affine, Point, Rect};
+use vello::util::RenderSurface;
+use vello::wgpu::{
+    CommandEncoderDescriptor, Instance, Surface, TextureViewDescriptor,
+};
+use vello::{RenderParams, Renderer, RendererOptions, Scene};
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::{
+    ElementState, KeyEvent, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+    WindowEvent,
+};
+#[cfg(target_arch = "wasm32")]
+use winit::event_loop::EventLoopProxy;
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::window::{Window, WindowAttributes, WindowId};
+
+use crate::board::{Backdrop, Drawable};
+use crate::gpu::{self, AdapterChoice, Gpu};
+use crate::hud::{Hud, Span};
+use crate::lang::Strings;
+use crate::menu::Menu;
+use crate::render::Antialiasing;
+use crate::settings::Settings;
+use crate::table::{self, Table};
+use crate::theme::Theme;
+
+/// Opens a window on its menu until it is closed, for games on the board
+/// under `rules`, the first with the dice thrown from `seed`. Every seat is
+/// played from the window unless the settings leave it to the computer: a
+/// click throws the dice, another picks one of the ringed planes able to
+/// move and a last one its destination, which the plane then flies to
+/// taking `step` for every leg of the way. Where a plane under the mouse or
+/// picked can go shows in ghosts. The dice tumble before they settle,
+/// unless clicked again or skipped with space, and escape goes back to the
+/// menu.
+///
+/// The keyboard does it all too: space throws the dice, tab or the left
+/// and right arrows pick the next plane able to move, the up and down
+/// arrows its destination, and enter plays it, both circled in dashes.
+/// In menus the arrows or tab move between buttons and enter presses one.
+///
+/// On a touch screen a tap does what a click does, a finger held down
+/// points like the mouse, showing where the plane under it can go, and
+/// pinching zooms the board in and out around the fingers. The back button
+/// of a phone does what escape does, and the game leaves for the menu
+/// whenever the app is put away.
+///
+/// During a game the wheel zooms the board around the mouse, and dragging
+/// it with the mouse or a finger pans it, never so far it leaves the
+/// middle of the window.
+///
+/// P pauses the game and resumes it. From the menu a game can be restarted
+/// with the same dice, or anew, and the player to move can resign.
+///
+/// The theme, antialiasing and players come from the settings screen,
+/// saved between runs, `theme` and `strings` replacing the theme and
+/// language chosen there if given.
+pub fn run(
+    theme: Option<Theme>,
+    strings: Option<Strings>,
+    layout: flight_core::Board,
+    rules: RuleSet,
+    seed: u64,
+    step: Duration,
+    adapter: AdapterChoice,
+) -> Result<()> {
+    let mut builder = EventLoop::<Wake>::with_user_event();
+    #[cfg(target_os = "android")]
+    {
+        use winit::platform::android::EventLoopBuilderExtAndroid;
+        builder.with_android_app(crate::android::app().clone());
+    }
+    let event_loop = builder.build()?;
+    let settings = Settings::load()?;
+    let theme = match theme {
+        Some(mut theme) => {
+            theme.patterns |= settings.patterns;
+            theme
+        }
+        None => settings.theme()?,
+    };
+    let strings = match strings {
+        Some(strings) => strings,
+        None => settings.strings()?,
+    };
+    let mut hud = Hud::default();
+    hud.set_font(&theme.font);
+    let app = App {
+        #[cfg(target_arch = "wasm32")]
+        proxy: event_loop.create_proxy(),
+        instance: gpu::instance(),
+        adapter,
+        gpu: None,
+        renderer: None,
+        state: State::Suspended(None),
+        scene: Scene::new(),
+        settings,
+        theme,
+        strings,
+        layout,
+        rules,
+        seed: Some(seed),
+        step,
+        screen: Screen::Menu,
+        table: None,
+        cursor: None,
+        focus: None,
+        modifiers: ModifiersState::empty(),
+        touches: vec![],
+        tap: None,
+        pressed: None,
+        view: Affine::IDENTITY,
+        hud,
+        #[cfg(not(target_arch = "wasm32"))]
+        error: None,
+    };
+    // The page runs the loop once this returns, errors going to the
+    // console.
+    #[cfg(target_arch = "wasm32")]
+    {
+        use winit::platform::web::EventLoopExtWebSys;
+        event_loop.spawn_app(app);
+        Ok(())
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut app = app;
+        event_loop.run_app(&mut app)?;
+        app.error.map_or(Ok(()), Err)
+    }
+}
+
+/// Wakes the event loop with a device opened in the background, which
+/// only happens on the web.
+#[cfg(target_arch = "wasm32")]
+type Wake = Opened;
+#[cfg(not(target_arch = "wasm32"))]
+type Wake = ();
+
+/// A device opened for the window's surface, or why it could not be.
+#[cfg(target_arch = "wasm32")]
+struct Opened {
+    gpu: Result<Gpu>,
+    surface: Surface<'static>,
+}
+
+enum State<'s> {
+    Active {
+        surface: Box<RenderSurface<'s>>,
+        window: Arc<Window>,
+    },
+    /// Keeps the window across suspensions so it can be reused on resume.
+    Suspended(Option<Arc<Window>>),
+}
+
+/// What the window shows.
+#[derive(Copy, Clone, PartialEq)]
+enum Screen {
+    Menu,
+    Settings,
+    Game,
+    /// The ranking of the game just over, over its board.
+    Results,
+}
+
+/// What a button of a menu does.
+#[derive(Copy, Clone)]
+enum Choice {
+    /// Back to the game left for the menu.
+    Resume,
+    /// The game on or last played again, with the same dice.
+    Restart,
+    /// The player to move resigns.
+    Resign,
+    NewGame,
+    Settings,
+    Quit,
+    /// Back to the menu.
+    Menu,
+    /// The next theme, language, antialiasing method or volume.
+    Theme,
+    Language,
+    Antialiasing,
+    Volume,
+    /// Arrows shown or hidden.
+    Arrows,
+    /// Cell numbers shown or hidden.
+    Numbers,
+    /// Color patterns shown or hidden.
+    Patterns,
+    /// The next player for the seat this many in seating order.
+    Player(usize),
+}
+
+struct App<'s> {
+    /// Wakes the loop once the device is open.
+    #[cfg(target_arch = "wasm32")]
+    proxy: EventLoopProxy<Wake>,
+    instance: Instance,
+    adapter: AdapterChoice,
+    /// Opened on the first resume, once there is a window to present to.
+    gpu: Option<Gpu>,
+    /// Made again when the antialiasing method changes.
+    renderer: Option<Renderer>,
+    state: State<'s>,
+    scene: Scene,
+    settings: Settings,
+    theme: Theme,
+    /// Text of the menus and HUD.
+    strings: Strings,
+    layout: flight_core::Board,
+    rules: RuleSet,
+    /// Seed of the dice for the first game, the next ones drawing their own.
+    seed: Option<u64>,
+    /// Time a plane takes for every leg of a move.
+    step: Duration,
+    screen: Screen,
+    /// The game on or last played.
+    table: Option<Table>,
+    /// Where the mouse, or a finger down alone that has not moved, is in
+    /// the window, in physical pixels.
+    cursor: Option<Point>,
+    /// Button of the menu focused with the keyboard, none while the mouse
+    /// is used.
+    focus: Option<usize>,
+    modifiers: ModifiersState,
+    /// Fingers on the screen by touch, in physical pixels.
+    touches: Vec<(u64, Point)>,
+    /// The finger down alone that has not moved, a tap if lifted so.
+    tap: Option<(u64, Point)>,
+    /// Where the mouse button went down and whether it has dragged since,
+    /// a click if released without.
+    pressed: Option<(Point, bool)>,
+    /// Pan and zoom of the board, over its fit into the window.
+    view: Affine,
+    hud: Hud,
+    #[cfg(not(target_arch = "wasm32"))]
+    error: Option<anyhow::Error>,
+}
+
+impl App<'_> {
+    /// Initial side of the window, margin around the board and band above
+    /// it kept for the HUD, in logical pixels so they look the same on
+    /// every display density.
+    const SIZE: f64 = 800.0;
+    const MARGIN: f64 = 16.0;
+    const HUD: f64 = 64.0;
+    /// Distance a finger or the mouse can move and still tap or click, in
+    /// logical pixels.
+    const SLOP: f64 = 10.0;
+    /// Furthest the board can be zoomed in.
+    const ZOOM: f64 = 4.0;
+
+    /// Offset and side of the square the board is fit into, centered in
+    /// the physical surface size below the HUD, with a margin scaled by the
+    /// window's `scale_factor`.
+    fn root(width: u32, height: u32, scale_factor: f64) -> (Affine, f64) {
+        let (width, height) = (f64::from(width), f64::from(height));
+        let margin = Self::MARGIN * scale_factor;
+        let hud = Self::HUD * scale_factor;
+        let side = (width - 2.0 * margin)
+            .min(height - hud - 2.0 * margin)
+            .max(1.0);
+        let offset = Affine::translate((
+            (width - side) / 2.0,
+            hud + (height - hud - side) / 2.0,
+        ));
+        (offset, side)
+    }
+
+    /// Root transform and `layout` fit to a surface of `width` by `height`
+    /// physical pixels.
+    fn fit(
+        layout: &flight_core::Board,
+        width: u32,
+        height: u32,
+        scale_factor: f64,
+    ) -> (Affine, flight_core::Board) {
+        let (offset, side) = Self::root(width, height, scale_factor);
+        let metrics = layout.shape().fit(side);
+        (offset, layout.clone().with_metrics(metrics))
+    }
+
+    fn resume(&mut self, event_loop: &ActiveEventLoop) -> Result<()> {
+        let State::Suspended(window) = &mut self.state else {
+            return Ok(());
+        };
+        let window = match window.take() {
+            Some(window) => window,
+            None => Arc::new(event_loop.create_window(Self::attributes())?),
+        };
+        let surface = self.instance.create_surface(window.clone())?;
+        if self.gpu.is_none() {
+            #[cfg(target_arch = "wasm32")]
+            {
+                self.open(window, surface);
+                return Ok(());
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.gpu = Some(pollster::block_on(Gpu::new(
+                    &self.instance,
+                    &self.adapter,
+                    Some(&surface),
+                ))?);
+            }
+        }
+        self.activate(window, surface)
+    }
+
+    /// The window to open, drawing into the page's canvas on the web.
+    fn attributes() -> WindowAttributes {
+        let attributes = Window::default_attributes()
+            .with_title("Flight")
+            .with_inner_size(LogicalSize::new(
+                Self::SIZE,
+                Self::SIZE + Self::HUD,
+            ));
+        #[cfg(target_arch = "wasm32")]
+        let attributes = {
+            use winit::platform::web::WindowAttributesExtWebSys;
+            let canvas = crate::web::canvas();
+            let append = canvas.is_none();
+            attributes.with_canvas(canvas).with_append(append)
+        };
+        attributes
+    }
+
+    /// Opens a device for `window` in the background, as browsers cannot
+    /// wait on one, keeping the window until it is open.
+    #[cfg(target_arch = "wasm32")]
+    fn open(&mut self, window: Arc<Window>, surface: Surface<'static>) {
+        let instance = self.instance.clone();
+        let adapter = self.adapter.clone();
+        let proxy = self.proxy.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let gpu = Gpu::new(&instance, &adapter, Some(&surface)).await;
+            // The loop is gone if the page was closed meanwhile.
+            let _ = proxy.send_event(Opened { gpu, surface });
+        });
+        self.state = State::Suspended(Some(window));
+    }
+
+    /// Shows `window`, presenting to `surface` on the open device.
+    fn activate(
+        &mut self,
+        window: Arc<Window>,
+        surface: Surface<'static>,
+    ) -> Result<()> {
+        let gpu = self.gpu.as_ref().context("no device is open")?;
+        let size = window.inner_size();
+        let surface = gpu.surface(surface, size.width, size.height)?;
+        window.request_redraw();
+        self.state = State::Active {
+            surface: Box::new(surface),
+            window,
+        };
+        Ok(())
+    }
+
+    /// The menu of the screen shown, none during a game.
+    fn menu(&self) -> Option<Menu<Choice>> {
+        let text = self.theme.stroke;
+        let strings = &self.strings;
+        let button = |label: &str, choice| (label.to_string(), choice);
+        let key = |key: &str, choice| (strings.get(key).to_string(), choice);
+        let menu = match self.screen {
+            Screen::Game => return None,
+            Screen::Menu => {
+                let mut buttons = vec![];
+                if let Some(table) = &self.table
+                    && !table.over()
+                {
+                    buttons.push(key("resume", Choice::Resume));
+                    buttons.push(key("restart", Choice::Restart));
+                    if let Some(seat) = table.resignable() {
+                        let name = strings.color(seat);
+                        buttons.push(button(
+                            &strings.fill("resign", &[("name", &name)]),
+                            Choice::Resign,
+                        ));
+                    }
+                }
+                buttons.extend([
+                    key("new-game", Choice::NewGame),
+                    key("settings", Choice::Settings),
+                    key("quit", Choice::Quit),
+                ]);
+                Menu {
+                    heading: vec![vec![Span::strong(
+                        strings.get("title"),
+                        text,
+                    )]],
+                    buttons,
+                }
+            }
+            Screen::Settings => {
+                let settings = &self.settings;
+                let theme = settings.theme.as_deref().unwrap_or("classic");
+                let antialiasing = settings
+                    .antialiasing
+                    .to_possible_value()
+                    .expect("no method is skipped");
+                let toggle = |key: &str, state: bool, choice| {
+                    let state = strings.toggle(state);
+                    button(&strings.fill(key, &[("state", &state)]), choice)
+                };
+                let mut buttons = vec![
+                    button(
+                        &strings.fill("theme", &[("name", &theme)]),
+                        Choice::Theme,
+                    ),
+                    button(
+                        &strings.fill(
+                            "language-choice",
+                            &[("name", &strings.get("language"))],
+                        ),
+                        Choice::Language,
+                    ),
+                    button(
+                        &strings.fill(
+                            "antialiasing",
+                            &[("method", &antialiasing.get_name())],
+                        ),
+                        Choice::Antialiasing,
+                    ),
+                    button(
+                        &strings
+                            .fill("volume", &[("percent", &settings.volume)]),
+                        Choice::Volume,
+                    ),
+                    toggle("arrows", settings.arrows, Choice::Arrows),
+                    toggle("numbers", settings.numbers, Choice::Numbers),
+                    toggle("patterns", settings.patterns, Choice::Patterns),
+                ];
+                let state = GameState::new(&self.layout);
+                for (index, side) in state.sides().iter().enumerate() {
+                    let values: [(&str, &dyn ToString); 3] = [
+                        ("number", &(index + 1)),
+                        ("color", &strings.color(side.seat)),
+                        ("player", &settings.player(index)),
+                    ];
+                    buttons.push(button(
+                        &strings.fill("seat", &values),
+                        Choice::Player(index),
+                    ));
+                }
+                buttons.push(key("back", Choice::Menu));
+                Menu {
+                    heading: vec![vec![Span::strong(
+                        strings.get("settings"),
+                        text,
+                    )]],
+                    buttons,
+                }
+            }
+            Screen::Results => {
+                let state = self.table.as_ref()?.game().state();
+                let over = Span::strong(strings.get("game-over"), text);
+                let mut heading = vec![vec![over]];
+                heading.extend(table::ranking(state, &self.theme, strings));
+                Menu {
+                    heading,
+                    buttons: vec![
+                        key("play-again", Choice::NewGame),
+                        key("replay", Choice::Restart),
+                        key("menu", Choice::Menu),
+                    ],
+                }
+            }
+        };
+        Some(menu)
+    }
+
+    /// Does what the button pressed says.
+    fn choose(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        choice: Choice,
+    ) -> Result<()> {
+        let screen = self.screen;
+        match choice {
+            Choice::Resume => self.screen = Screen::Game,
+            Choice::Restart => {
+                if let Some(table) = &mut self.table {
+                    table.restart(table.seed(), &self.settings)?;
+                }
+                self.screen = Screen::Game;
+            }
+            Choice::Resign => {
+                if let Some(table) = &mut self.table {
+                    table.resign()?;
+                }
+                self.screen = Screen::Game;
+            }
+            Choice::NewGame => {
+                let seed = self.seed.take().unwrap_or_else(rand::random);
+                match &mut self.table {
+                    Some(table) => table.restart(seed, &self.settings)?,
+                    None => {
+                        self.table = Some(Table::new(
+                            self.layout.clone(),
+                            self.rules,
+                            seed,
+                            self.step,
+                            &self.settings,
+                        )?);
+                    }
+                }
+                self.screen = Screen::Game;
+            }
+            Choice::Settings => self.screen = Screen::Settings,
+            Choice::Quit => event_loop.exit(),
+            Choice::Menu => self.screen = Screen::Menu,
+            Choice::Theme => {
+                let themes: Vec<_> =
+                    Settings::themes().into_iter().map(Some).collect();
+                self.settings.theme = next(&themes, &self.settings.theme);
+                self.theme = self.settings.theme()?;
+                self.hud.set_font(&self.theme.font);
+            }
+            Choice::Language => {
+                let codes: Vec<_> = Strings::BUILTIN
+                    .iter()
+                    .map(|(code, _)| code.to_string())
+                    .collect();
+                let current = self.strings.code().to_string();
+                let code = next(&codes, &current);
+                self.strings = Strings::find(&code)?;
+                self.settings.lang = Some(code);
+            }
+            Choice::Antialiasing => {
+                let methods = Antialiasing::value_variants();
+                let current = &self.settings.antialiasing;
+                self.settings.antialiasing = next(methods, current);
+                self.renderer = None;
+            }
+            Choice::Volume => {
+                self.settings.volume =
+                    (self.settings.volume / 10 + 1) % 11 * 10;
+            }
+            Choice::Arrows => self.settings.arrows = !self.settings.arrows,
+            Choice::Numbers => self.settings.numbers = !self.settings.numbers,
+            Choice::Patterns => {
+                self.settings.patterns = !self.settings.patterns;
+                self.theme.patterns = self.settings.patterns;
+            }
+            Choice::Player(index) => {
+                let mut players = vec![Settings::HUMAN.to_string()];
+                players.extend(ai::names());
+                let current = self.settings.player(index).to_string();
+                let player = next(&players, &current);
+                let seats = &mut self.settings.players;
+                if seats.len() <= index {
+                    seats.resize(index + 1, Settings::HUMAN.into());
+                }
+                seats[index] = player;
+            }
+        }
+        if matches!(
+            choice,
+            Choice::Theme
+                | Choice::Antialiasing
+                | Choice::Volume
+                | Choice::Arrows
+                | Choice::Numbers
+                | Choice::Patterns
+                | Choice::Language
+                | Choice::Player(_)
+        ) {
+            self.settings.save()?;
+        }
+        if self.screen != screen {
+            self.focus = self.focus.map(|_| 0);
+        }
+        Ok(())
+    }
+
+    fn redraw(&mut self) -> Result<()> {
+        let menu = self.menu();
+        let State::Active { surface, window } = &mut self.state else {
+            return Ok(());
+        };
+        let (width, height) = (surface.config.width, surface.config.height);
+        let scale_factor = window.scale_factor();
+        let (offset, board) =
+            Self::fit(&self.layout, width, height, scale_factor);
+        let offset = self.view * offset;
+        self.scene.reset();
+        let area = Rect::new(0.0, 0.0, width.into(), height.into());
+        Backdrop::new(area, board.metrics().scale())
+            .draw(&mut self.scene, &self.theme);
+        if let (Screen::Game | Screen::Results, Some(table)) =
+            (self.screen, &mut self.table)
+        {
+            if self.screen == Screen::Game {
+                table.tick()?;
+            }
+            let cursor = self.cursor.map(|cursor| offset.inverse() * cursor);
+            table.draw(
+                &mut self.scene,
+                &self.theme,
+                offset,
+                &board,
+                &self.settings,
+                cursor,
+            );
+            if self.screen == Screen::Game {
+                let lines = table.status(&self.theme, &self.strings);
+                let corner = Point::new(Self::MARGIN, Self::MARGIN);
+                let corner = (corner.to_vec2() * scale_factor).to_point();
+                let hud = &mut self.hud;
+                hud.draw(
+                    &mut self.scene,
+                    &self.theme,
+                    corner,
+                    scale_factor,
+                    &lines,
+                );
+                if table.over() {
+                    self.screen = Screen::Results;
+                }
+            }
+        }
+        if let Some(menu) = menu {
+            let size = (width, height);
+            let highlighted = self.focus.or_else(|| {
+                let cursor = self.cursor?;
+                menu.at(&mut self.hud, size, scale_factor, cursor)
+            });
+            menu.draw(
+                &mut self.hud,
+                &mut self.scene,
+                &self.theme,
+                size,
+                scale_factor,
+                highlighted,
+            );
+        }
+
+        let Some(handle) = &self.gpu else {
+            return Ok(());
+        };
+        let antialiasing = self.settings.antialiasing;
+        let renderer = match &mut self.renderer {
+            Some(renderer) => renderer,
+            None => self.renderer.insert(renderer(handle, antialiasing)?),
+        };
+        renderer
+            .render_to_texture(
+                &handle.device,
+                &handle.queue,
+                &self.scene,
+                &surface.target_view,
+                &RenderParams {
+                    base_color: self.theme.background,
+                    width,
+                    height,
+                    antialiasing_method: antialiasing.config(),
+                },
+            )
+            .map_err(|e| anyhow!("failed to render: {e}"))?;
+        let texture = surface.surface.get_current_texture()?;
+        let mut encoder =
+            handle
+                .device
+                .create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("Surface blit"),
+                });
+        surface.blitter.copy(
+            &handle.device,
+            &mut encoder,
+            &surface.target_view,
+            &texture
+                .texture
+                .create_view(&TextureViewDescriptor::default()),
+        );
+        handle.queue.submit([encoder.finish()]);
+        window.pre_present_notify();
+        texture.present();
+        window.request_redraw();
+        Ok(())
+    }
+
+    /// Plays a click or tap at `cursor` on the board, or does what the
+    /// button clicked in a menu says.
+    fn click(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        cursor: Point,
+    ) -> Result<()> {
+        let State::Active { surface, window } = &self.state else {
+            return Ok(());
+        };
+        let (width, height) = (surface.config.width, surface.config.height);
+        let scale_factor = window.scale_factor();
+        if let Some(menu) = self.menu() {
+            let size = (width, height);
+            let index = menu.at(&mut self.hud, size, scale_factor, cursor);
+            if let Some(choice) = index.and_then(|index| menu.choice(index)) {
+                self.choose(event_loop, choice)?;
+            }
+            return Ok(());
+        }
+        let Some(table) = &mut self.table else {
+            return Ok(());
+        };
+        let (offset, board) =
+            Self::fit(&self.layout, width, height, scale_factor);
+        let offset = self.view * offset;
+        table.click(&board, offset.inverse() * cursor)
+    }
+
+    /// Follows the fingers on the screen: a tap clicks where it lands, a
+    /// finger pointing until then, and two fingers pinch the board, the
+    /// point between them staying under them as they move.
+    fn touch(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        touch: Touch,
+        scale_factor: f64,
+    ) -> Result<()> {
+        let point = Point::new(touch.location.x, touch.location.y);
+        let id = touch.id;
+        let index = self.touches.iter().position(|&(touch, _)| touch == id);
+        match (touch.phase, index) {
+            (TouchPhase::Started, _) => {
+                self.touches.push((id, point));
+                self.tap = (self.touches.len() == 1).then_some((id, point));
+                self.focus = None;
+            }
+            (TouchPhase::Moved, Some(index)) => {
+                let slop = Self::SLOP * scale_factor;
+                if self.tap.is_some_and(|(tap, from)| {
+                    tap == id && (point - from).hypot() > slop
+                }) {
+                    self.tap = None;
+                }
+                let before = self.pinch();
+                let from = std::mem::replace(&mut self.touches[index].1, point);
+                if let (Some(before), Some(after)) = (before, self.pinch()) {
+                    let span = (before.1 - before.0).hypot();
+                    if span > 0.0 {
+                        let ratio = (after.1 - after.0).hypot() / span;
+                        let middle = before.0.midpoint(before.1);
+                        self.reframe(middle, after.0.midpoint(after.1), ratio);
+                    }
+                } else if self.touches.len() == 1 && self.tap.is_none() {
+                    self.reframe(from, point, 1.0);
+                }
+            }
+            (TouchPhase::Ended, Some(index)) => {
+                self.touches.remove(index);
+                if let Some((tap, point)) = self.tap.take()
+                    && tap == id
+                {
+                    self.click(event_loop, point)?;
+                }
+            }
+            (TouchPhase::Cancelled, Some(index)) => {
+                self.touches.remove(index);
+                self.tap = None;
+            }
+            _ => {}
+        }
+        self.cursor = self.tap.map(|(_, point)| point);
+        Ok(())
+    }
+
+    /// The two fingers pinching, if exactly two are down.
+    fn pinch(&self) -> Option<(Point, Point)> {
+        match self.touches[..] {
+            [(_, first), (_, second)] => Some((first, second)),
+            _ => None,
+        }
+    }
+
+    /// Moves the view so the point of the window at `from` comes to `to`,
+    /// zoomed by `ratio` around it, between the board fit to the window
+    /// and `ZOOM` times that, with the middle of the window kept on the
+    /// board. Only a game's board moves.
+    fn reframe(&mut self, from: Point, to: Point, ratio: f64) {
+        if self.screen != Screen::Game {
+            return;
+        }
+        let current = self.view.as_coeffs()[0];
+        let zoom = (current * ratio).clamp(1.0, Self::ZOOM);
+        self.view = Affine::translate(to.to_vec2())
+            * Affine::scale(zoom / current)
+            * Affine::translate(-from.to_vec2())
+            * self.view;
+        self.clamp();
+    }
+
+    /// Pans the view back until the board covers the middle of the fit
+    /// square again.
+    fn clamp(&mut self) {
+        let State::Active { surface, window } = &self.state else {
+            return;
+        };
+        let (width, height) = (surface.config.width, surface.config.height);
+        let (offset, side) = Self::root(width, height, window.scale_factor());
+        let corner = offset.translation().to_point();
+        let square = Rect::from_origin_size(corner, (side, side));
+        let middle = square.center();
+        let [zoom, _, _, _, x, y] = self.view.as_coeffs();
+        let x =
+            x.clamp(middle.x - zoom * square.x1, middle.x - zoom * square.x0);
+        let y =
+            y.clamp(middle.y - zoom * square.y1, middle.y - zoom * square.y0);
+        self.view = Affine::new([zoom, 0.0, 0.0, zoom, x, y]);
+    }
+
+    /// Plays the key pressed on the board, or moves between and presses
+    /// the buttons of a menu with it.
+    fn key(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        key: NamedKey,
+    ) -> Result<()> {
+        let back = self.modifiers.shift_key();
+        if let Some(menu) = self.menu() {
+            let count = menu.buttons.len();
+            let focus = self.focus.filter(|&index| index < count);
+            let cycle = |forward| table::step(count, focus, forward);
+            match key {
+                NamedKey::ArrowDown => self.focus = cycle(true),
+                NamedKey::ArrowUp => self.focus = cycle(false),
+                NamedKey::Tab => self.focus = cycle(!back),
+                NamedKey::Enter | NamedKey::Space => match focus {
+                    Some(index) => {
+                        let choice = menu.choice(index);
+                        if let Some(choice) = choice {
+                            self.choose(event_loop, choice)?;
+                        }
+                    }
+                    None => self.focus = Some(0),
+                },
+                NamedKey::Escape | NamedKey::BrowserBack
+                    if self.screen != Screen::Menu =>
+                {
+                    self.choose(event_loop, Choice::Menu)?;
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        let Some(table) = &mut self.table else {
+            return Ok(());
+        };
+        match key {
+            NamedKey::Space => table.throw()?,
+            NamedKey::Enter => table.confirm()?,
+            NamedKey::Tab => table.cycle(!back),
+            NamedKey::ArrowRight => table.cycle(true),
+            NamedKey::ArrowLeft => table.cycle(false),
+            NamedKey::ArrowDown => table.cycle_target(true),
+            NamedKey::ArrowUp => table.cycle_target(false),
+            NamedKey::Escape | NamedKey::BrowserBack => {
+                self.screen = Screen::Menu;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Pauses the game on or resumes it.
+    fn pause(&mut self) {
+        if let (Screen::Game, Some(table)) = (self.screen, &mut self.table) {
+            table.pause();
+        }
+    }
+
+    fn fail(&mut self, event_loop: &ActiveEventLoop, error: anyhow::Error) {
+        #[cfg(target_arch = "wasm32")]
+        crate::web::report(&format!("{error:#}"));
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.error = Some(error);
+        }
+        event_loop.exit();
+    }
+}
+
+impl ApplicationHandler<Wake> for App<'_> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if let Err(error) = self.resume(event_loop) {
+            self.fail(event_loop, error);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, opened: Opened) {
+        let State::Suspended(window) = &mut self.state else {
+            return;
+        };
+        let Some(window) = window.take() else {
+            return;
+        };
+        let result = opened.gpu.and_then(|gpu| {
+            self.gpu = Some(gpu);
+            self.activate(window, opened.surface)
+        });
+        if let Err(error) = result {
+            self.fail(event_loop, error);
+        }
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        if self.screen == Screen::Game {
+            self.screen = Screen::Menu;
+        }
+        if let State::Active { window, .. } = &self.state {
+            self.state = State::Suspended(Some(window.clone()));
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let State::Active { surface, window } = &mut self.state else {
+            return;
+        };
+        if window.id() != window_id {
+            return;
+        }
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Resized(size) => {
+                if let Some(gpu) = &self.gpu
+                    && size.width > 0
+                    && size.height > 0
+                {
+                    gpu.resize(surface, size.width, size.height);
+                }
+                window.request_redraw();
+                self.clamp();
+            }
+            // The new physical size arrives as a `Resized` event, only the
+            // margin needs redrawing here.
+            WindowEvent::ScaleFactorChanged { .. } => window.request_redraw(),
+            WindowEvent::CursorMoved { position, .. } => {
+                let point = Point::new(position.x, position.y);
+                let from = self.cursor.replace(point);
+                self.focus = None;
+                let slop = Self::SLOP * window.scale_factor();
+                if let Some((down, dragged)) = &mut self.pressed {
+                    *dragged |= (point - *down).hypot() > slop;
+                    if let (true, Some(from)) = (*dragged, from) {
+                        self.reframe(from, point, 1.0);
+                    }
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, lines) => f64::from(lines),
+                    MouseScrollDelta::PixelDelta(pixels) => pixels.y / 40.0,
+                };
+                if let Some(cursor) = self.cursor {
+                    self.reframe(cursor, cursor, 1.2_f64.powf(lines));
+                }
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let Some(cursor) = self.cursor else {
+                    return;
+                };
+                let pressed = self.pressed.take();
+                if state == ElementState::Pressed {
+                    self.pressed = Some((cursor, false));
+                } else if let Some((down, false)) = pressed
+                    && let Err(error) = self.click(event_loop, down)
+                {
+                    self.fail(event_loop, error);
+                }
+            }
+            WindowEvent::Touch(touch) => {
+                let scale_factor = window.scale_factor();
+                if let Err(error) = self.touch(event_loop, touch, scale_factor)
+                {
+                    self.fail(event_loop, error);
+                }
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        logical_key: Key::Named(key),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                if let Err(error) = self.key(event_loop, key) {
+                    self.fail(event_loop, error);
+                }
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        logical_key: Key::Character(text),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } if text.eq_ignore_ascii_case("p") => self.pause(),
+            WindowEvent::RedrawRequested => {
+                if let Err(error) = self.redraw() {
+                    self.fail(event_loop, error);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A renderer on `gpu` supporting `antialiasing`.
+fn renderer(gpu: &Gpu, antialiasing: Antialiasing) -> Result<Renderer> {
+    Renderer::new(
+        &gpu.device,
+        RendererOptions {
+            num_init_threads: NonZeroUsize::new(1),
+            antialiasing_support: antialiasing.support(),
+            ..Default::default()
+        },
+    )
+    .map_err(|e| anyhow!("failed to create renderer: {e}"))
+}
+
+/// The option after `current` in `options`, the first past the last or
+/// when `current` is not one of them.
+fn next<T: Clone + PartialEq>(options: &[T], current: &T) -> T {
+    let index = options.iter().position(|option| option == current);
+    let next = index.map_or(0, |index| (index + 1) % options.len());
+    options[next].clone()
+}
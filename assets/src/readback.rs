@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver};
+
+use anyhow::{Context, Result};
+use vello::wgpu::{
+    Buffer, BufferAsyncError, BufferDescriptor, BufferUsages,
+    CommandEncoderDescriptor, Device, Extent3d, MapMode, PollType, Queue,
+    SubmissionIndex, TexelCopyBufferInfo, TexelCopyBufferLayout, Texture,
+};
+
+/// Staging buffers that frames are copied into and mapped asynchronously,
+/// so the GPU can render the next frame while the CPU encodes the last.
+pub struct ReadbackRing {
+    depth: usize,
+    /// Unmapped buffers ready for reuse.
+    free: Vec<Buffer>,
+    /// Copies in flight, oldest first.
+    pending: VecDeque<Pending>,
+}
+
+struct Pending {
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    stride: u32,
+    submission: SubmissionIndex,
+    mapped: Receiver<Result<(), BufferAsyncError>>,
+}
+
+impl ReadbackRing {
+    /// A ring holding at most `depth` frames in flight.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth: depth.max(1),
+            free: vec![],
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Whether [`pop`](Self::pop) has to be called before the next push.
+    pub fn is_full(&self) -> bool {
+        self.pending.len() >= self.depth
+    }
+
+    /// Copies the top left `width`×`height` pixels of an RGBA8 `texture`
+    /// into a staging buffer and starts mapping it.
+    pub fn push(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        texture: &Texture,
+        width: u32,
+        height: u32,
+    ) {
+        let stride = (width * 4).next_multiple_of(256);
+        let size = u64::from(stride) * u64::from(height);
+        let buffer = match self.free.iter().position(|b| b.size() >= size) {
+            Some(index) => self.free.swap_remove(index),
+            None => device.create_buffer(&BufferDescriptor {
+                label: Some("Flight readback"),
+                size,
+                usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+        };
+        let mut encoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Copy out buffer"),
+            });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(stride),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        let submission = queue.submit([encoder.finish()]);
+        let (tx, mapped) = mpsc::channel();
+        buffer
+            .slice(..size)
+            .map_async(MapMode::Read, move |result| {
+                let _ = tx.send(result);
+            });
+        self.pending.push_back(Pending {
+            buffer,
+            width,
+            height,
+            stride,
+            submission,
+            mapped,
+        });
+    }
+
+    /// Waits for the oldest frame in flight and returns its tightly packed
+    /// pixels, or `None` if nothing is pending.
+    pub fn pop(&mut self, device: &Device) -> Result<Option<Vec<u8>>> {
+        let Some(pending) = self.pending.pop_front() else {
+            return Ok(None);
+        };
+        device.poll(PollType::WaitForSubmissionIndex(pending.submission))?;
+        pending
+            .mapped
+            .recv()
+            .context("readback buffer was dropped")??;
+
+        let row = (pending.width * 4) as usize;
+        let stride = pending.stride as usize;
+        let size = u64::from(pending.stride) * u64::from(pending.height);
+        let data = pending.buffer.slice(..size).get_mapped_range();
+        let mut bytes = Vec::with_capacity(row * pending.height as usize);
+        for line in data.chunks(stride).take(pending.height as usize) {
+            bytes.extend(&line[..row]);
+        }
+        drop(data);
+        pending.buffer.unmap();
+        self.free.push(pending.buffer);
+        Ok(Some(bytes))
+    }
+}
@@ -0,0 +1,385 @@
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail, ensure};
+use flight_core::protocol::PORT;
+use flight_core::{
+    Client, Move, Peer, Phase, RuleSet, ServerEvent, ServerMessage,
+    ServerResponse, ai,
+};
+
+use crate::board::Player;
+
+/// Time a computer player gets to think about a move over the network.
+const BUDGET: Duration = Duration::from_secs(2);
+
+/// Times the connection is tried again after it dropped mid-game, a second
+/// apart.
+const RECONNECTS: u32 = 10;
+
+/// Which room of the lobby to sit in.
+pub enum Room {
+    /// The first room waiting for players without a password.
+    First,
+    Id(u64),
+    /// A new room seating `players` under `rules`.
+    Create {
+        players: usize,
+        rules: RuleSet,
+    },
+    /// The seat a token was given for, after dropping out of its game.
+    Resume(String),
+}
+
+/// Plays a seat in a room of the lobby at `address`, on the default port
+/// unless it names one, asking which move to play in the terminal or
+/// leaving it to the computer player called `ai`.
+pub fn run(
+    address: &str,
+    name: &str,
+    room: Room,
+    password: Option<&str>,
+    ai: Option<&str>,
+) -> Result<()> {
+    if let Some(ai) = ai {
+        ensure!(
+            ai::names().iter().any(|name| name == ai),
+            "unknown computer player {ai:?}"
+        );
+    }
+    let address = with_port(address);
+    let mut client = Client::connect(address.as_str(), name)?;
+    match room {
+        Room::First => {
+            let open = client.rooms().iter().find(|room| {
+                !room.started
+                    && !room.locked
+                    && room.seated.len() < room.players
+            });
+            let Some(open) = open else {
+                bail!("no room to join, open one with --create");
+            };
+            client.join_room(open.id, None)?;
+        }
+        Room::Id(room) => client.join_room(room, password)?,
+        Room::Create { players, rules } => {
+            let room = format!("{name}'s room");
+            client.create_room(&room, players, rules, password)?;
+        }
+        Room::Resume(token) => client.resume(&token)?,
+    }
+    admitted(&mut client)?;
+    let taken = client.seat().expect("seated");
+    let (seat, token) = (taken.seat, taken.token.clone());
+    let table = client.table().expect("seated");
+    println!("seated in seat {seat} of room {}", table.room);
+    println!("should this end, take the seat back with --resume {token}");
+    let mut ai = ai.map(|ai| ai::named(ai, seat as u64)).transpose()?;
+    println!("playing {}", Player::color_name(seat));
+    for (other, name) in &table.players {
+        if *other != seat {
+            println!("{name} sits in seat {other}");
+        }
+    }
+    let mut input = io::stdin().lock();
+    loop {
+        let message = match client.wait() {
+            Ok(message) => message,
+            Err(error) => {
+                println!("lost the server, {error:#}");
+                client = reconnect(&address, name, &token)?;
+                continue;
+            }
+        };
+        tell(&client, &message);
+        let Some(table) = client.table() else {
+            return Ok(());
+        };
+        let state = table.game.state();
+        let ours = state.turn().seat == seat;
+        match message {
+            ServerMessage::Event(ServerEvent::Over { .. }) => return Ok(()),
+            ServerMessage::Event(ServerEvent::Turn { .. })
+            | ServerMessage::Response(ServerResponse::Rejected { .. })
+                if ours =>
+            {
+                match state.phase() {
+                    Phase::Roll => {
+                        if ai.is_none() {
+                            ask(&mut input, "your turn, enter to roll")?;
+                        }
+                        client.roll()?;
+                    }
+                    Phase::Move => {
+                        let legal = state.legal_moves();
+                        let mv = match &mut ai {
+                            Some(ai) => ai.choose_move(state, &legal, BUDGET),
+                            None => choose(&mut input, &legal)?,
+                        };
+                        client.play(mv)?;
+                    }
+                    Phase::Finished => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Which side of a game between friends to take.
+pub enum Friend {
+    /// Hosts the game on `port` under `rules`.
+    Host { port: u16, rules: RuleSet },
+    /// Joins the game hosted at an address.
+    Join(String),
+}
+
+/// Plays a friend directly as `name`, asking which move to play in the
+/// terminal or leaving it to the computer player called `ai`.
+pub fn peer(friend: Friend, name: &str, ai: Option<&str>) -> Result<()> {
+    if let Some(ai) = ai {
+        ensure!(
+            ai::names().iter().any(|name| name == ai),
+            "unknown computer player {ai:?}"
+        );
+    }
+    let mut peer = match friend {
+        Friend::Host { port, rules } => {
+            println!("waiting for a friend on port {port}");
+            Peer::host(("0.0.0.0", port), name, rules)?
+        }
+        Friend::Join(address) => Peer::join(with_port(&address), name)?,
+    };
+    let seat = peer.seat();
+    let mut ai = ai.map(|ai| ai::named(ai, seat as u64)).transpose()?;
+    println!("playing {}", Player::color_name(seat));
+    for (other, name) in peer.players() {
+        if *other != seat {
+            println!("{name} sits in seat {other}");
+        }
+    }
+    let mut input = io::stdin().lock();
+    loop {
+        let state = peer.game().state();
+        if state.phase() == Phase::Finished {
+            let ranking = state.ranking().to_vec();
+            let over = ServerEvent::Over { ranking };
+            if let Some(text) = describe(&over, peer.players()) {
+                println!("{text}");
+            }
+            return Ok(());
+        }
+        if !peer.ours() {
+            let event = peer.wait()?;
+            if let Some(text) = describe(&event, peer.players()) {
+                println!("{text}");
+            }
+            continue;
+        }
+        match state.phase() {
+            Phase::Roll => {
+                if ai.is_none() {
+                    ask(&mut input, "your turn, enter to roll")?;
+                }
+                let mv = peer.roll()?;
+                println!("you {}", verb(mv));
+            }
+            Phase::Move => {
+                let legal = state.legal_moves();
+                let mv = match &mut ai {
+                    Some(ai) => ai.choose_move(state, &legal, BUDGET),
+                    None => choose(&mut input, &legal)?,
+                };
+                peer.play(mv)?;
+                println!("you {}", verb(mv));
+            }
+            Phase::Finished => {}
+        }
+    }
+}
+
+/// Follows the game of room `room` in the lobby at `address` without
+/// playing it, telling what happens in the terminal until it is over.
+pub fn watch(
+    address: &str,
+    name: &str,
+    room: u64,
+    password: Option<&str>,
+) -> Result<()> {
+    let mut client = Client::connect(with_port(address).as_str(), name)?;
+    client.watch(room, password)?;
+    admitted(&mut client)?;
+    let table = client.table().expect("watching");
+    println!("watching room {room}, {} watching", table.spectators);
+    for (seat, name) in &table.players {
+        println!("{name} sits in seat {seat}");
+    }
+    let state = table.game.state();
+    if state.phase() != Phase::Finished {
+        let to_play = Player::color_name(state.turn().seat);
+        println!(
+            "{} plies played, {to_play} to play",
+            table.game.record().plies.len()
+        );
+    }
+    loop {
+        let message = client.wait()?;
+        tell(&client, &message);
+        match message {
+            ServerMessage::Event(ServerEvent::Over { .. }) => return Ok(()),
+            _ if client.table().is_none() => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// `address` with the default port unless it names one.
+fn with_port(address: &str) -> String {
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{address}:{PORT}")
+    }
+}
+
+/// Prints what `message` says happened, if anything.
+fn tell(client: &Client, message: &ServerMessage) {
+    let players = client.table().map_or(&[][..], |table| &table.players);
+    let text = match message {
+        ServerMessage::Event(event) => describe(event, players),
+        ServerMessage::Response(ServerResponse::Rejected {
+            reason, ..
+        }) => Some(format!("refused: {reason}")),
+        ServerMessage::Response(_) => None,
+    };
+    if let Some(text) = text {
+        println!("{text}");
+    }
+}
+
+/// Waits until `client` sits in or watches the room it asked for.
+fn admitted(client: &mut Client) -> Result<()> {
+    loop {
+        match client.wait()? {
+            ServerMessage::Response(
+                ServerResponse::Welcome { .. }
+                | ServerResponse::Resumed { .. }
+                | ServerResponse::Watching { .. },
+            ) => return Ok(()),
+            ServerMessage::Response(ServerResponse::Rejected {
+                reason,
+                ..
+            }) => {
+                bail!("{reason}")
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Connects to `address` again and resumes the seat of `token`, trying
+/// for a while before giving up.
+fn reconnect(address: &str, name: &str, token: &str) -> Result<Client> {
+    let mut failure = None;
+    for _ in 0..RECONNECTS {
+        thread::sleep(Duration::from_secs(1));
+        let resumed = Client::connect(address, name).and_then(|mut client| {
+            client.resume(token)?;
+            admitted(&mut client)?;
+            Ok(client)
+        });
+        match resumed {
+            Ok(client) => {
+                println!("back in the game");
+                return Ok(client);
+            }
+            Err(error) => failure = Some(error),
+        }
+    }
+    let failure = failure.expect("tried at least once");
+    Err(failure).context("failed to resume the seat")
+}
+
+/// What happened for the players to read, if they need to know.
+pub fn describe(
+    event: &ServerEvent,
+    players: &[(usize, String)],
+) -> Option<String> {
+    let who = |seat: usize| {
+        let name = players.iter().find(|(taken, _)| *taken == seat);
+        name.map_or_else(|| format!("seat {seat}"), |(_, name)| name.clone())
+    };
+    Some(match event {
+        ServerEvent::Lobby { .. } | ServerEvent::Turn { .. } => return None,
+        ServerEvent::Joined { seat, name } => {
+            format!("{name} joined in seat {seat}")
+        }
+        &ServerEvent::Left { seat } => format!("{} left", who(seat)),
+        &ServerEvent::Dropped { seat, grace } => {
+            format!("{} dropped out, {grace} s to come back", who(seat))
+        }
+        &ServerEvent::Rejoined { seat } => format!("{} is back", who(seat)),
+        ServerEvent::Spectators { count } => format!("{count} watching"),
+        ServerEvent::Chat(line) => format!("{}: {}", line.from, line.text),
+        ServerEvent::Substituted { seat, ai } => {
+            format!("{ai} plays for {}", who(*seat))
+        }
+        ServerEvent::Started => "the game starts".into(),
+        &ServerEvent::Played { seat, mv, .. } => {
+            format!("{} {}", who(seat), verb(mv))
+        }
+        ServerEvent::Over { ranking } => match ranking.first() {
+            Some(&seat) => format!("{} wins", who(seat)),
+            None => "the game is over".into(),
+        },
+        ServerEvent::Closed { reason } => {
+            format!("the room closed, {reason}")
+        }
+    })
+}
+
+/// `mv` as said of the player making it.
+fn verb(mv: Move) -> String {
+    match mv {
+        Move::Roll(total) => format!("threw {total}"),
+        Move::Launch { plane } => format!("launched plane {}", plane + 1),
+        Move::Advance { plane } => format!("moved plane {}", plane + 1),
+        Move::Pass => "passed".into(),
+    }
+}
+
+/// Asks `question` and returns the answer.
+fn ask(input: &mut impl BufRead, question: &str) -> Result<String> {
+    print!("{question}: ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    if input.read_line(&mut answer)? == 0 {
+        bail!("no more input");
+    }
+    Ok(answer.trim().to_string())
+}
+
+/// Asks which of `legal` to play until one is picked.
+fn choose(input: &mut impl BufRead, legal: &[Move]) -> Result<Move> {
+    if let [mv] = legal[..] {
+        return Ok(mv);
+    }
+    for (index, &mv) in legal.iter().enumerate() {
+        let mv = match mv {
+            Move::Launch { plane } => format!("launch plane {}", plane + 1),
+            Move::Advance { plane } => format!("move plane {}", plane + 1),
+            Move::Roll(_) | Move::Pass => verb(mv),
+        };
+        println!("  {}) {mv}", index + 1);
+    }
+    loop {
+        let answer = ask(input, "your move")?;
+        match answer.parse::<usize>() {
+            Ok(pick) if (1..=legal.len()).contains(&pick) => {
+                return Ok(legal[pick - 1]);
+            }
+            _ => println!("pick a number from 1 to {}", legal.len()),
+        }
+    }
+}
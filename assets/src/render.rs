@@ -0,0 +1,279 @@
+use std::num::NonZeroUsize;
+
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::Context;
+use anyhow::{Result, bail, ensure};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use vello::kurbo::{Affine, Rect};
+use vello::peniko::Color;
+use vello::wgpu::{
+    Extent3d, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages,
+};
+use vello::{
+    AaConfig, AaSupport, RenderParams, Renderer, RendererOptions, Scene,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::board::Board;
+use crate::board::Drawable;
+use crate::canvas::Canvas;
+use crate::gpu::{self, AdapterChoice, Gpu};
+use crate::readback::ReadbackRing;
+use crate::theme::Theme;
+
+/// Antialiasing method of the GPU renderer.
+#[derive(Copy, Clone, PartialEq, Debug, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Antialiasing {
+    /// Analytic area coverage, fast and usually good enough on screen.
+    Area,
+    /// 8x multisampling.
+    Msaa8,
+    /// 16x multisampling, the cleanest diagonal edges for print.
+    Msaa16,
+}
+
+impl Antialiasing {
+    /// Pipelines the renderer has to compile to support this method.
+    pub fn support(self) -> AaSupport {
+        AaSupport {
+            area: matches!(self, Self::Area),
+            msaa8: matches!(self, Self::Msaa8),
+            msaa16: matches!(self, Self::Msaa16),
+        }
+    }
+
+    pub fn config(self) -> AaConfig {
+        match self {
+            Self::Area => AaConfig::Area,
+            Self::Msaa8 => AaConfig::Msaa8,
+            Self::Msaa16 => AaConfig::Msaa16,
+        }
+    }
+}
+
+/// A device and renderer kept alive across frames, with a texture and
+/// staging buffers reused whenever the output size allows.
+pub struct Rasterizer {
+    gpu: Gpu,
+    renderer: Renderer,
+    antialiasing: Antialiasing,
+    target: Option<Texture>,
+    readback: ReadbackRing,
+}
+
+impl Rasterizer {
+    /// Frames that can be in flight before [`submit`](Self::submit) hands
+    /// back the oldest one.
+    const DEPTH: usize = 2;
+
+    pub async fn new(
+        adapter: &AdapterChoice,
+        antialiasing: Antialiasing,
+    ) -> Result<Self> {
+        let gpu = Gpu::new(&gpu::instance(), adapter, None).await?;
+        let renderer = Renderer::new(
+            &gpu.device,
+            RendererOptions {
+                num_init_threads: NonZeroUsize::new(1),
+                antialiasing_support: antialiasing.support(),
+                ..Default::default()
+            },
+        )
+        .or_else(|_| bail!("failed to create renderer"))?;
+        Ok(Self {
+            gpu,
+            renderer,
+            antialiasing,
+            target: None,
+            readback: ReadbackRing::new(Self::DEPTH),
+        })
+    }
+
+    /// Queues `scene` for rasterization over `base_color`. Once the ring is
+    /// full the oldest frame is waited for and returned, so results come
+    /// back in submission order.
+    pub fn submit(
+        &mut self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+        base_color: Color,
+    ) -> Result<Option<Vec<u8>>> {
+        let limit = self.gpu.device.limits().max_texture_dimension_2d;
+        ensure!(
+            width <= limit && height <= limit,
+            "the GPU renders at most {limit} pixels a side, \
+             got {width}x{height}"
+        );
+        let oldest = if self.readback.is_full() {
+            self.readback.pop(&self.gpu.device)?
+        } else {
+            None
+        };
+        let target = match self.target.take() {
+            Some(texture)
+                if texture.width() >= width && texture.height() >= height =>
+            {
+                texture
+            }
+            _ => self.gpu.device.create_texture(&TextureDescriptor {
+                label: Some("Flight texture"),
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8Unorm,
+                usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+                view_formats: &[],
+            }),
+        };
+        self.renderer
+            .render_to_texture(
+                &self.gpu.device,
+                &self.gpu.queue,
+                scene,
+                &target.create_view(&Default::default()),
+                &RenderParams {
+                    base_color,
+                    width,
+                    height,
+                    antialiasing_method: self.antialiasing.config(),
+                },
+            )
+            .or_else(|_| bail!("Got non-Send/Sync error from rendering"))?;
+        self.readback.push(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &target,
+            width,
+            height,
+        );
+        self.target = Some(target);
+        Ok(oldest)
+    }
+
+    /// Waits for the oldest frame still in flight, `None` once all submitted
+    /// frames have been returned.
+    pub fn finish(&mut self) -> Result<Option<Vec<u8>>> {
+        self.readback.pop(&self.gpu.device)
+    }
+}
+
+/// Tightly packed straight-alpha RGBA8 pixels.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Renders the board for `players` over the theme background into a
+/// `size` pixels square image, on the default GPU if one can be opened and
+/// on the CPU otherwise.
+pub fn render_board(theme: &Theme, players: usize, size: u32) -> Result<Image> {
+    ensure!(
+        Board::PLAYERS.contains(&players),
+        "players must be one of {:?}",
+        Board::PLAYERS
+    );
+    ensure!(size > 0, "output size must not be empty");
+    let layout = flight_core::Board::new(players);
+    let metrics = layout.shape().fit(f64::from(size));
+    let board =
+        Board::from_layout(Affine::IDENTITY, layout.with_metrics(metrics));
+    let rasterizer = pollster::block_on(Rasterizer::new(
+        &AdapterChoice::default(),
+        Antialiasing::Area,
+    ));
+    let pixels = match rasterizer {
+        Ok(mut rasterizer) => {
+            let mut scene = Scene::new();
+            board.draw(&mut scene, theme);
+            rasterizer.submit(&scene, size, size, theme.background)?;
+            rasterizer.finish()?.context("no frame was rendered")?
+        }
+        Err(error) => {
+            log::warn!("falling back to the CPU renderer: {error:#}");
+            render_cpu(&board, theme, size, size, theme.background)?
+        }
+    };
+    Ok(Image {
+        width: size,
+        height: size,
+        pixels,
+    })
+}
+
+/// Software counterpart of [`Rasterizer`] for machines without a usable GPU,
+/// drawing `artwork` directly and returning the same straight-alpha RGBA8.
+pub fn render_cpu(
+    artwork: &impl Drawable,
+    theme: &Theme,
+    width: u32,
+    height: u32,
+    base_color: Color,
+) -> Result<Vec<u8>> {
+    let (Ok(w), Ok(h)) = (u16::try_from(width), u16::try_from(height)) else {
+        bail!("the CPU renderer is limited to {} pixels a side", u16::MAX);
+    };
+    let mut context = vello_cpu::RenderContext::new(w, h);
+    if base_color.components[3] > 0.0 {
+        let size = Rect::new(0.0, 0.0, width.into(), height.into());
+        context.fill(Affine::IDENTITY, base_color, &size);
+    }
+    artwork.draw(&mut context, theme);
+    let mut pixmap = vello_cpu::Pixmap::new(w, h);
+    context.render_to_pixmap(&mut pixmap);
+    Ok(pixmap
+        .take_unpremultiplied()
+        .into_iter()
+        .flat_map(|pixel| [pixel.r, pixel.g, pixel.b, pixel.a])
+        .collect())
+}
+
+/// Box filters RGBA8 pixels rendered `factor` times larger than `width` by
+/// `height` into floating point straight-alpha pixels. Averaging the
+/// supersamples recovers edge precision a single 8-bit sample cannot hold.
+pub fn resolve(
+    bytes: &[u8],
+    width: u32,
+    height: u32,
+    factor: u32,
+) -> Vec<[f32; 4]> {
+    let stride = (width * factor) as usize;
+    let samples = (factor * factor) as f32;
+    let mut pixels = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f32; 4];
+            for dy in 0..factor {
+                let row = ((y * factor + dy) as usize) * stride;
+                for dx in 0..factor {
+                    let i = (row + (x * factor + dx) as usize) * 4;
+                    let alpha = f32::from(bytes[i + 3]) / 255.0;
+                    for (sum, value) in sum.iter_mut().zip(&bytes[i..i + 3]) {
+                        *sum += f32::from(*value) / 255.0 * alpha;
+                    }
+                    sum[3] += alpha;
+                }
+            }
+            let alpha = sum[3] / samples;
+            let color = |value: f32| {
+                if alpha > 0.0 {
+                    value / samples / alpha
+                } else {
+                    0.0
+                }
+            };
+            pixels.push([color(sum[0]), color(sum[1]), color(sum[2]), alpha]);
+        }
+    }
+    pixels
+}
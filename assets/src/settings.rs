@@ -0,0 +1,202 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+#[cfg(target_os = "android")]
+use crate::android;
+use crate::lang::Strings;
+use crate::looks;
+use crate::render::Antialiasing;
+use crate::theme::Theme;
+#[cfg(target_arch = "wasm32")]
+use crate::web;
+
+/// What the window remembers between runs, in `settings.toml` of the
+/// configuration directory, or the page's local storage on the web.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Built-in look or theme of the [themes
+    /// directory](Settings::themes_directory), by name, the classic look
+    /// otherwise.
+    pub theme: Option<String>,
+    /// Code of the built-in language of the menus and HUD, English
+    /// otherwise.
+    pub lang: Option<String>,
+    pub antialiasing: Antialiasing,
+    /// Volume of sounds, in percent.
+    pub volume: u8,
+    /// Who plays each seat in seating order, `human` or a computer player
+    /// as `players` lists them. Seats past the list are played by humans.
+    pub players: Vec<String>,
+    /// Whether the board shows which way planes travel and fly.
+    pub arrows: bool,
+    /// Whether every track cell shows its index, to point at exact cells.
+    pub numbers: bool,
+    /// Whether cells and planes are marked with a pattern for each player
+    /// color, whatever the theme says.
+    pub patterns: bool,
+}
+
+impl Settings {
+    /// Name of the seats played with the mouse in [`players`](Self::players).
+    pub const HUMAN: &str = "human";
+
+    /// Directory the settings and themes are kept in, under
+    /// `$XDG_CONFIG_HOME` or `~/.config`, none without either.
+    #[cfg(not(target_os = "android"))]
+    pub fn directory() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CONFIG_HOME")
+            .filter(|base| !base.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME")
+                    .map(|home| PathBuf::from(home).join(".config"))
+            })?;
+        Some(base.join("flight"))
+    }
+
+    /// Directory the settings are kept in, the app's data directory.
+    #[cfg(target_os = "android")]
+    pub fn directory() -> Option<PathBuf> {
+        android::data_directory()
+    }
+
+    /// Directory of the themes to choose from, `themes` next to the
+    /// settings, or among the APK's assets on Android.
+    pub fn themes_directory() -> Option<PathBuf> {
+        if cfg!(target_os = "android") {
+            return Some(PathBuf::from("themes"));
+        }
+        Some(Self::directory()?.join("themes"))
+    }
+
+    /// Key the settings are stored under in the page's local storage.
+    #[cfg(target_arch = "wasm32")]
+    const KEY: &str = "flight.settings";
+
+    /// The settings saved last, the defaults if none were.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::directory().map(|dir| dir.join("settings.toml"))
+        else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&text)
+            .with_context(|| format!("invalid settings {}", path.display()))
+    }
+
+    /// The settings saved last, the defaults if none were.
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Result<Self> {
+        let Some(text) = web::load(Self::KEY) else {
+            return Ok(Self::default());
+        };
+        toml::from_str(&text).context("invalid settings")
+    }
+
+    /// Saves the settings for the next run, if there is anywhere to.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) -> Result<()> {
+        let Some(directory) = Self::directory() else {
+            return Ok(());
+        };
+        fs::create_dir_all(&directory).with_context(|| {
+            format!("failed to create {}", directory.display())
+        })?;
+        let path = directory.join("settings.toml");
+        fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Saves the settings for the next visit.
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) -> Result<()> {
+        web::save(Self::KEY, &toml::to_string(self)?)
+    }
+
+    /// Names of the themes to choose from, the built-in looks followed by
+    /// the file stems of the themes directory in order.
+    pub fn themes() -> Vec<String> {
+        let builtins = looks::all().map(|look| look.name().to_string());
+        let paths = Self::themes_directory()
+            .map(|directory| files(&directory))
+            .unwrap_or_default();
+        let mut names: Vec<String> = paths
+            .into_iter()
+            .filter_map(|path| {
+                if path.extension()? != "toml" {
+                    return None;
+                }
+                Some(path.file_stem()?.to_str()?.to_string())
+            })
+            .filter(|name| !builtins.contains(name))
+            .collect();
+        names.sort();
+        builtins.into_iter().chain(names).collect()
+    }
+
+    /// The theme chosen, with patterns if asked for.
+    pub fn theme(&self) -> Result<Theme> {
+        let builtin = self.theme.as_deref().and_then(looks::named);
+        let path = self.theme.as_ref().and_then(|theme| {
+            let directory = Self::themes_directory()?;
+            Some(directory.join(format!("{theme}.toml")))
+        });
+        let mut theme = match (builtin, path) {
+            (Some(theme), _) => theme,
+            (None, Some(path)) => Theme::load(&path)?,
+            (None, None) => Theme::default(),
+        };
+        theme.patterns |= self.patterns;
+        Ok(theme)
+    }
+
+    /// The strings of the language chosen.
+    pub fn strings(&self) -> Result<Strings> {
+        match &self.lang {
+            Some(code) => Strings::find(code),
+            None => Ok(Strings::default()),
+        }
+    }
+
+    /// Who plays the seat `index`th in seating order.
+    pub fn player(&self, index: usize) -> &str {
+        self.players.get(index).map_or(Self::HUMAN, String::as_str)
+    }
+}
+
+/// Paths of the files in `directory`, among the APK's assets on Android.
+fn files(directory: &Path) -> Vec<PathBuf> {
+    #[cfg(target_os = "android")]
+    return android::list(directory);
+    #[cfg(not(target_os = "android"))]
+    fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect()
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: None,
+            lang: None,
+            antialiasing: Antialiasing::Area,
+            volume: 80,
+            players: vec![],
+            arrows: true,
+            numbers: false,
+            patterns: false,
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use serde::Serialize;
+use vello::kurbo::{Affine, Point, Rect};
+
+use flight_core::Metrics;
+
+use crate::board::{Cell, CellKind, Drawable, Player};
+use crate::canvas::Canvas;
+use crate::dice::Die;
+use crate::pattern::Pattern;
+use crate::plane::Plane;
+use crate::theme::Theme;
+
+enum Piece {
+    Cell(Cell),
+    Base(Player),
+    Plane(Plane),
+    Die(Die),
+}
+
+impl Drawable for Piece {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        match self {
+            Piece::Cell(cell) => cell.draw(canvas, theme),
+            Piece::Base(player) => player.draw_base(canvas, theme),
+            Piece::Plane(plane) => plane.draw(canvas, theme),
+            Piece::Die(die) => die.draw(canvas, theme),
+        }
+    }
+}
+
+/// A single piece of artwork rendered on its own into the sprite sheet.
+pub struct Sprite {
+    name: String,
+    bounds: Rect,
+    piece: Piece,
+}
+
+impl Sprite {
+    /// Every cell kind, home base and plane in each player color, planes
+    /// nose right and as large as on a board of default metrics.
+    pub fn all(theme: &Theme) -> Vec<Sprite> {
+        let mut sprites = vec![];
+        for (index, &color) in theme.players.iter().enumerate() {
+            let name = Player::color_name(index);
+            sprites.push(Sprite {
+                name: format!("base-{name}"),
+                bounds: Metrics::default().base(),
+                piece: Piece::Base(Player::new(
+                    color,
+                    Affine::IDENTITY,
+                    Metrics::default(),
+                )),
+            });
+            let radius = Metrics::default().dim * 0.3;
+            let plane =
+                Plane::new(Affine::IDENTITY, Point::ORIGIN, color, radius, 0.0)
+                    .with_pattern(Pattern::for_color(index));
+            sprites.push(Sprite {
+                name: format!("plane-{name}"),
+                bounds: plane.bounds(),
+                piece: Piece::Plane(plane),
+            });
+            for kind in CellKind::ALL {
+                let shape = flight_core::Cell::new(kind, index, Point::ORIGIN);
+                let cell = Cell::new(shape, color, Affine::IDENTITY);
+                sprites.push(Sprite {
+                    name: format!("cell-{}-{name}", kind.name()),
+                    bounds: cell.bounds(),
+                    piece: Piece::Cell(cell),
+                });
+            }
+        }
+        sprites
+    }
+
+    /// The six faces of a die, as large as the dice on a board of default
+    /// metrics.
+    pub fn dice() -> Vec<Sprite> {
+        let side = Metrics::default().dim * 1.2;
+        (1..=6)
+            .map(|face| {
+                let die =
+                    Die::new(Affine::IDENTITY, Point::ORIGIN, face, side, 0.0);
+                Sprite {
+                    name: format!("die-{face}"),
+                    bounds: die.bounds(),
+                    piece: Piece::Die(die),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Placement of a sprite inside the sheet, in pixels and normalized UVs.
+#[derive(Serialize)]
+pub struct SpriteInfo {
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub uv: [f64; 4],
+}
+
+/// Metadata written next to the packed sprite sheet image.
+#[derive(Serialize)]
+pub struct SpriteSheet {
+    pub image: String,
+    pub width: u32,
+    pub height: u32,
+    pub sprites: Vec<SpriteInfo>,
+    #[serde(skip)]
+    scale: f64,
+    #[serde(skip)]
+    pieces: Vec<Sprite>,
+}
+
+impl SpriteSheet {
+    const PADDING: u32 = 2;
+
+    /// Packs `sprites` rendered at `scale` into shelves, tallest first.
+    pub fn pack(image: String, sprites: Vec<Sprite>, scale: f64) -> Self {
+        let sizes = Self::sizes(&sprites, scale);
+        let area = sizes
+            .iter()
+            .map(|(w, h)| {
+                u64::from(w + Self::PADDING) * u64::from(h + Self::PADDING)
+            })
+            .sum::<u64>();
+        let widest = sizes.iter().map(|(w, _)| *w).max().unwrap_or_default();
+        let width = ((area as f64).sqrt().ceil() as u32)
+            .next_power_of_two()
+            .max(widest + Self::PADDING * 2);
+
+        let mut order = (0..sizes.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+        let mut positions = vec![(0, 0); sizes.len()];
+        let (mut x, mut y, mut row) = (Self::PADDING, Self::PADDING, 0);
+        for i in order {
+            let (w, h) = sizes[i];
+            if x + w + Self::PADDING > width {
+                x = Self::PADDING;
+                y += row + Self::PADDING;
+                row = 0;
+            }
+            positions[i] = (x, y);
+            x += w + Self::PADDING;
+            row = row.max(h);
+        }
+        let height = y + row + Self::PADDING;
+        Self::place(image, sprites, scale, (width, height), sizes, positions)
+    }
+
+    /// Lays `sprites` rendered at `scale` out in a row, in order.
+    pub fn strip(image: String, sprites: Vec<Sprite>, scale: f64) -> Self {
+        let sizes = Self::sizes(&sprites, scale);
+        let mut positions = vec![];
+        let mut x = Self::PADDING;
+        for (w, _) in &sizes {
+            positions.push((x, Self::PADDING));
+            x += w + Self::PADDING;
+        }
+        let tallest = sizes.iter().map(|(_, h)| *h).max().unwrap_or_default();
+        let size = (x, tallest + Self::PADDING * 2);
+        Self::place(image, sprites, scale, size, sizes, positions)
+    }
+
+    /// Pixel size of every sprite rendered at `scale`.
+    fn sizes(sprites: &[Sprite], scale: f64) -> Vec<(u32, u32)> {
+        sprites
+            .iter()
+            .map(|sprite| {
+                let size = sprite.bounds.size() * scale;
+                (size.width.ceil() as u32, size.height.ceil() as u32)
+            })
+            .collect()
+    }
+
+    /// The sheet of `sprites` of `sizes` at `positions` in an image of
+    /// `width` by `height`.
+    fn place(
+        image: String,
+        sprites: Vec<Sprite>,
+        scale: f64,
+        (width, height): (u32, u32),
+        sizes: Vec<(u32, u32)>,
+        positions: Vec<(u32, u32)>,
+    ) -> Self {
+        let infos = sprites
+            .iter()
+            .zip(sizes.into_iter().zip(positions))
+            .map(|(sprite, ((w, h), (x, y)))| SpriteInfo {
+                name: sprite.name.clone(),
+                x,
+                y,
+                width: w,
+                height: h,
+                uv: [
+                    f64::from(x) / f64::from(width),
+                    f64::from(y) / f64::from(height),
+                    f64::from(x + w) / f64::from(width),
+                    f64::from(y + h) / f64::from(height),
+                ],
+            })
+            .collect();
+        Self {
+            image,
+            width,
+            height,
+            sprites: infos,
+            scale,
+            pieces: sprites,
+        }
+    }
+}
+
+/// Sprites leave shadows out, for whatever places them to cast its own.
+impl Drawable for SpriteSheet {
+    fn draw(&self, canvas: &mut impl Canvas, theme: &Theme) {
+        let theme = &Theme {
+            shadows: false,
+            ..theme.clone()
+        };
+        for (sprite, info) in self.pieces.iter().zip(&self.sprites) {
+            let affine =
+                Affine::translate((f64::from(info.x), f64::from(info.y)))
+                    * Affine::scale(self.scale)
+                    * Affine::translate(-sprite.bounds.origin().to_vec2());
+            canvas.group(affine, |canvas| sprite.piece.draw(canvas, theme));
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use std::fmt::Write;
+
+use vello::kurbo::{Affine, Cap, Join, Shape, Stroke};
+use vello::peniko::color::Srgb;
+use vello::peniko::{Color, Extend, Gradient, GradientKind, ImageBrush};
+
+use crate::canvas::Canvas;
+
+/// Canvas that serializes every fill and stroke into an SVG document.
+pub struct SvgCanvas {
+    width: u32,
+    height: u32,
+    body: String,
+    /// Gradients, patterns and clip paths defined so far, numbering their
+    /// ids.
+    paints: usize,
+}
+
+impl SvgCanvas {
+    const TOLERANCE: f64 = 0.1;
+
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        let mut body = String::new();
+        if background.components[3] > 0.0 {
+            let _ = writeln!(
+                body,
+                r#"<rect width="100%" height="100%"{}/>"#,
+                paint("fill", background)
+            );
+        }
+        Self {
+            width,
+            height,
+            body,
+            paints: 0,
+        }
+    }
+
+    pub fn finish(self) -> String {
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" "#,
+                r#"width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+                "\n{2}</svg>\n"
+            ),
+            self.width, self.height, self.body
+        )
+    }
+
+    fn path(&mut self, affine: Affine, shape: &impl Shape, attributes: &str) {
+        let _ = writeln!(
+            self.body,
+            r#"<path d="{}"{}{}/>"#,
+            shape.to_path(Self::TOLERANCE).to_svg(),
+            transform(affine),
+            attributes
+        );
+    }
+}
+
+impl Canvas for SvgCanvas {
+    fn fill(&mut self, affine: Affine, color: Color, shape: &impl Shape) {
+        self.path(affine, shape, &paint("fill", color));
+    }
+
+    fn fill_gradient(
+        &mut self,
+        affine: Affine,
+        gradient: &Gradient,
+        shape: &impl Shape,
+    ) {
+        let position = match gradient.kind {
+            GradientKind::Linear(line) => format!(
+                r#"linearGradient x1="{}" y1="{}" x2="{}" y2="{}""#,
+                line.start.x, line.start.y, line.end.x, line.end.y
+            ),
+            GradientKind::Radial(radial) => format!(
+                concat!(
+                    r#"radialGradient cx="{}" cy="{}" r="{}" "#,
+                    r#"fx="{}" fy="{}" fr="{}""#
+                ),
+                radial.end_center.x,
+                radial.end_center.y,
+                radial.end_radius,
+                radial.start_center.x,
+                radial.start_center.y,
+                radial.start_radius
+            ),
+            // SVG has no sweep gradients, so those fall back to their first
+            // color.
+            GradientKind::Sweep(_) => {
+                let color =
+                    gradient.stops.first().map_or(Color::TRANSPARENT, |stop| {
+                        stop.color.to_alpha_color::<Srgb>()
+                    });
+                return self.fill(affine, color, shape);
+            }
+        };
+        let id = format!("paint{}", self.paints);
+        self.paints += 1;
+        let spread = match gradient.extend {
+            Extend::Pad => "pad",
+            Extend::Repeat => "repeat",
+            Extend::Reflect => "reflect",
+        };
+        let _ = writeln!(
+            self.body,
+            concat!(
+                r#"<defs><{} id="{}" gradientUnits="userSpaceOnUse" "#,
+                r#"spreadMethod="{}">"#
+            ),
+            position, id, spread
+        );
+        for stop in gradient.stops.iter() {
+            let rgba = stop.color.to_alpha_color::<Srgb>().to_rgba8();
+            let _ = writeln!(
+                self.body,
+                concat!(
+                    r##"<stop offset="{}" stop-color="#{:02x}{:02x}{:02x}" "##,
+                    r#"stop-opacity="{}"/>"#
+                ),
+                stop.offset,
+                rgba.r,
+                rgba.g,
+                rgba.b,
+                f32::from(rgba.a) / 255.0
+            );
+        }
+        let tag = position.split(' ').next().unwrap_or_default();
+        let _ = writeln!(self.body, "</{tag}></defs>");
+        self.path(affine, shape, &format!(r#" fill="url(#{id})""#));
+    }
+
+    /// Patterns always repeat, so images do whatever their extend mode.
+    fn fill_image(
+        &mut self,
+        affine: Affine,
+        image: &ImageBrush,
+        brush: Affine,
+        shape: &impl Shape,
+    ) {
+        let data = &image.image;
+        let mut png = vec![];
+        let mut encoder = png::Encoder::new(&mut png, data.width, data.height);
+        encoder.set_color(png::ColorType::Rgba);
+        let written = encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(data.data.data()));
+        if written.is_err() {
+            return;
+        }
+        let id = format!("paint{}", self.paints);
+        self.paints += 1;
+        let [a, b, c, d, e, f] = brush.as_coeffs();
+        let _ = writeln!(
+            self.body,
+            concat!(
+                r#"<defs><pattern id="{}" patternUnits="userSpaceOnUse" "#,
+                r#"width="{2}" height="{3}" "#,
+                r#"patternTransform="matrix({4} {5} {6} {7} {8} {9})">"#,
+                r#"<image width="{2}" height="{3}" opacity="{1}" "#,
+                r#"href="data:image/png;base64,{10}"/></pattern></defs>"#
+            ),
+            id,
+            image.sampler.alpha,
+            data.width,
+            data.height,
+            a,
+            b,
+            c,
+            d,
+            e,
+            f,
+            base64(&png)
+        );
+        self.path(affine, shape, &format!(r#" fill="url(#{id})""#));
+    }
+
+    fn stroke(
+        &mut self,
+        style: &Stroke,
+        affine: Affine,
+        color: Color,
+        shape: &impl Shape,
+    ) {
+        let mut attributes =
+            format!(r#" fill="none"{}"#, paint("stroke", color));
+        let _ = write!(
+            attributes,
+            r#" stroke-width="{}" stroke-linejoin="{}" stroke-linecap="{}""#,
+            style.width,
+            match style.join {
+                Join::Bevel => "bevel",
+                Join::Miter => "miter",
+                Join::Round => "round",
+            },
+            match style.start_cap {
+                Cap::Butt => "butt",
+                Cap::Square => "square",
+                Cap::Round => "round",
+            }
+        );
+        if style.join == Join::Miter {
+            let _ = write!(
+                attributes,
+                r#" stroke-miterlimit="{}""#,
+                style.miter_limit
+            );
+        }
+        if !style.dash_pattern.is_empty() {
+            let dashes = style
+                .dash_pattern
+                .iter()
+                .map(f64::to_string)
+                .collect::<Vec<_>>();
+            let _ = write!(
+                attributes,
+                r#" stroke-dasharray="{}" stroke-dashoffset="{}""#,
+                dashes.join(" "),
+                style.dash_offset
+            );
+        }
+        self.path(affine, shape, &attributes);
+    }
+
+    fn group(&mut self, affine: Affine, draw: impl FnOnce(&mut Self)) {
+        let _ = writeln!(self.body, r#"<g{}>"#, transform(affine));
+        draw(self);
+        self.body.push_str("</g>\n");
+    }
+
+    fn clip(
+        &mut self,
+        affine: Affine,
+        shape: &impl Shape,
+        draw: impl FnOnce(&mut Self),
+    ) {
+        let id = format!("paint{}", self.paints);
+        self.paints += 1;
+        let _ = writeln!(self.body, r#"<clipPath id="{id}">"#);
+        self.path(affine, shape, "");
+        let _ = writeln!(self.body, r#"</clipPath><g clip-path="url(#{id})">"#);
+        draw(self);
+        self.body.push_str("</g>\n");
+    }
+}
+
+fn paint(attribute: &str, color: Color) -> String {
+    let rgba = color.to_rgba8();
+    let mut paint = format!(
+        r##" {attribute}="#{:02x}{:02x}{:02x}""##,
+        rgba.r, rgba.g, rgba.b
+    );
+    if rgba.a != u8::MAX {
+        let _ = write!(
+            paint,
+            r#" {attribute}-opacity="{}""#,
+            f32::from(rgba.a) / 255.0
+        );
+    }
+    paint
+}
+
+/// `bytes` in standard base64 with padding, for data URLs.
+fn base64(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple =
+            chunk.iter().enumerate().fold(0u32, |triple, (i, &byte)| {
+                triple | u32::from(byte) << (16 - 8 * i)
+            });
+        for digit in 0..4 {
+            if digit <= chunk.len() {
+                let index = (triple >> (18 - 6 * digit)) & 0x3f;
+                text.push(char::from(DIGITS[index as usize]));
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn transform(affine: Affine) -> String {
+    if affine == Affine::IDENTITY {
+        return String::new();
+    }
+    let [a, b, c, d, e, f] = affine.as_coeffs();
+    format!(r#" transform="matrix({a} {b} {c} {d} {e} {f})""#)
+}
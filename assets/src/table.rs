@@ -0,0 +1,687 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use anyhow::Result;
+use flight_core::{
+    AiPlayer, CellId, Dice, Game, GameEvent, GameState, Move, Phase, Position,
+    Route, RuleSet, ai,
+};
+use rand::rngs::StdRng;
+use vello::Scene;
+use vello::kurbo::{Affine, Circle, Point, Stroke};
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::board::{Board, Drawable};
+use crate::canvas::Canvas;
+use crate::dice::{DiceFaces, Tumble};
+use crate::hud::Span;
+use crate::lang::Strings;
+use crate::pieces::Pieces;
+use crate::settings::Settings;
+use crate::theme::Theme;
+
+/// A game played in the window: the seats played with the mouse or the
+/// keyboard, those left to computer players, and the moves and throws
+/// being drawn.
+pub struct Table {
+    game: Game,
+    dice: Dice<StdRng>,
+    /// Computer players by seat, the other seats played from the window.
+    ais: Vec<(usize, Box<dyn AiPlayer>)>,
+    /// Plane of the player to move picked to move next.
+    selected: Option<usize>,
+    /// Destination of the picked plane focused with the keyboard, by its
+    /// place among the plane's, none while the mouse is used.
+    target: Option<usize>,
+    /// Time a plane takes for every leg of a move.
+    step: Duration,
+    /// The move last played while its planes fly it, clicks waiting.
+    motion: Option<Motion>,
+    /// What the game raised since the last move was played.
+    events: Rc<RefCell<Vec<GameEvent>>>,
+    /// Faces of the last throw.
+    thrown: Vec<u8>,
+    /// The last throw, tumbling or settled.
+    rolling: Option<Rolling>,
+    /// When the last move or throw was played.
+    played: Instant,
+    /// Seed the dice were thrown from.
+    seed: u64,
+    /// When the game was paused, its clock stopped there.
+    paused: Option<Instant>,
+}
+
+impl Table {
+    /// Time the dice tumble before they settle.
+    const TUMBLE: Duration = Duration::from_millis(800);
+    /// Time computer players think about a move.
+    const BUDGET: Duration = Duration::from_millis(300);
+    /// Time computer players wait after the last move settled, so it can
+    /// be followed.
+    const PAUSE: Duration = Duration::from_millis(400);
+
+    /// A game on `layout` under `rules` with the dice thrown from `seed`,
+    /// its seats played as `settings` say and its planes taking `step` for
+    /// every leg of a move.
+    pub fn new(
+        layout: flight_core::Board,
+        rules: RuleSet,
+        seed: u64,
+        step: Duration,
+        settings: &Settings,
+    ) -> Result<Self> {
+        let mut game = Game::new(layout, rules, Some(seed));
+        let events = Rc::new(RefCell::new(vec![]));
+        let queue = events.clone();
+        game.observe(move |event: &GameEvent, _: &GameState| {
+            queue.borrow_mut().push(event.clone());
+        });
+        Ok(Self {
+            ais: players(game.state(), seed, settings)?,
+            game,
+            dice: Dice::seeded(rules.dice, seed)?,
+            selected: None,
+            target: None,
+            step,
+            motion: None,
+            events,
+            thrown: vec![],
+            rolling: None,
+            played: Instant::now(),
+            seed,
+            paused: None,
+        })
+    }
+
+    /// Starts the game over with the dice thrown from `seed`, its seats
+    /// played as `settings` now say.
+    pub fn restart(&mut self, seed: u64, settings: &Settings) -> Result<()> {
+        self.game.restart(Some(seed));
+        let rules = self.game.state().rules();
+        self.dice = Dice::seeded(rules.dice, seed)?;
+        self.ais = players(self.game.state(), seed, settings)?;
+        self.selected = None;
+        self.target = None;
+        self.motion = None;
+        self.events.take();
+        self.thrown.clear();
+        self.rolling = None;
+        self.played = Instant::now();
+        self.seed = seed;
+        self.paused = None;
+        Ok(())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Pauses the game, or resumes it with the moves and throws being
+    /// drawn and the computer players' wait picking up where they were.
+    pub fn pause(&mut self) {
+        let Some(paused) = self.paused.take() else {
+            self.paused = Some(Instant::now());
+            return;
+        };
+        let stopped = paused.elapsed();
+        self.played += stopped;
+        if let Some(motion) = &mut self.motion {
+            motion.started += stopped;
+        }
+        if let Some(rolling) = &mut self.rolling {
+            rolling.started += stopped;
+        }
+    }
+
+    /// Seat of the player to move if played from the window, who may
+    /// resign while the game is on.
+    pub fn resignable(&self) -> Option<usize> {
+        let state = self.game.state();
+        let playing = state.phase() != Phase::Finished && !self.automated();
+        playing.then(|| state.turn().seat)
+    }
+
+    /// Lets the player to move resign if played from the window, the
+    /// turn passing on or the game ending without them.
+    pub fn resign(&mut self) -> Result<()> {
+        let Some(seat) = self.resignable() else {
+            return Ok(());
+        };
+        self.game.resign(seat)?;
+        self.events.take();
+        self.selected = None;
+        self.target = None;
+        self.played = self.now();
+        Ok(())
+    }
+
+    /// The time on the game's clock, stopped while paused.
+    fn now(&self) -> Instant {
+        self.paused.unwrap_or_else(Instant::now)
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Whether a move or throw is still being drawn.
+    pub fn busy(&self) -> bool {
+        self.motion.is_some() || self.tumbling()
+    }
+
+    /// Whether the game is over and done being drawn.
+    pub fn over(&self) -> bool {
+        self.game.state().phase() == Phase::Finished && !self.busy()
+    }
+
+    fn tumbling(&self) -> bool {
+        let now = self.now();
+        self.rolling
+            .as_ref()
+            .is_some_and(|rolling| rolling.tumbling(now))
+    }
+
+    /// Whether the player to move plays from the window, the game is not
+    /// paused and nothing is being drawn.
+    fn listening(&self) -> bool {
+        self.game.state().phase() != Phase::Finished
+            && self.paused.is_none()
+            && !self.busy()
+            && !self.automated()
+    }
+
+    /// Whether the player to move is one of the computer's.
+    fn automated(&self) -> bool {
+        let turn = self.game.state().turn().seat;
+        self.ais.iter().any(|(seat, _)| *seat == turn)
+    }
+
+    /// Draws the game into `scene` on `board`, fit to the window under
+    /// `offset` and marked up as `settings` ask, highlighting what the
+    /// mouse at `cursor` can do there, in board pixels.
+    pub fn draw(
+        &mut self,
+        scene: &mut Scene,
+        theme: &Theme,
+        offset: Affine,
+        board: &flight_core::Board,
+        settings: &Settings,
+        cursor: Option<Point>,
+    ) {
+        let state = self.game.state();
+        let now = self.now();
+        let mut pieces = Pieces::new(offset, board, state);
+        if let Some(motion) = &self.motion {
+            let elapsed = (now - motion.started).as_secs_f64();
+            let t = elapsed / motion.duration.as_secs_f64();
+            let from = Pieces::new(offset, board, &motion.from);
+            let route = &motion.route;
+            let seat = motion.from.turn().seat;
+            pieces = from.glide(&pieces, board, seat, route, t.min(1.0));
+            if t >= 1.0 {
+                self.motion = None;
+                self.played = now;
+            }
+        }
+        Board::from_layout(offset, board.clone())
+            .with_arrows(settings.arrows)
+            .with_numbers(settings.numbers)
+            .draw(scene, theme);
+        pieces.draw(scene, theme);
+        let tumbling = self.tumbling();
+        if state.phase() == Phase::Move
+            && !tumbling
+            && self.motion.is_none()
+            && !self.automated()
+        {
+            let turn = state.turn().seat;
+            let hovered = cursor
+                .and_then(|cursor| {
+                    Pieces::new(Affine::IDENTITY, board, state).at(cursor)
+                })
+                .filter(|plane| plane.seat == turn)
+                .map(|plane| plane.plane);
+            let radius = pieces.radius();
+            for token in pieces.tokens() {
+                let plane = token.plane.plane;
+                if token.plane.seat != turn || targets(state, plane).is_empty()
+                {
+                    continue;
+                }
+                let width = if self.selected == Some(plane) {
+                    0.3
+                } else {
+                    0.12
+                };
+                let circle = Circle::new(token.center, radius * 1.2);
+                Canvas::stroke(
+                    scene,
+                    &Stroke::new(radius * width),
+                    offset,
+                    theme.accent,
+                    &circle,
+                );
+                if self.selected != Some(plane) && hovered != Some(plane) {
+                    continue;
+                }
+                let ghost = theme.player(turn).with_alpha(0.4);
+                for (_, cell) in targets(state, plane) {
+                    let center = board.track().cells()[cell].center;
+                    let circle = Circle::new(center, radius);
+                    Canvas::fill(scene, offset, ghost, &circle);
+                }
+            }
+            // The keyboard's focus, dashed around the picked plane and the
+            // destination Enter moves it to.
+            if let (Some(plane), Some(index)) = (self.selected, self.target) {
+                let focus = Stroke::new(radius * 0.15)
+                    .with_dashes(0.0, [radius * 0.4, radius * 0.25]);
+                let token = pieces.tokens().iter().find(|token| {
+                    token.plane.seat == turn && token.plane.plane == plane
+                });
+                let cell = targets(state, plane)
+                    .get(index)
+                    .map(|&(_, cell)| board.track().cells()[cell].center);
+                let centers = token.map(|token| token.center).into_iter();
+                for center in centers.chain(cell) {
+                    let circle = Circle::new(center, radius * 1.6);
+                    Canvas::stroke(
+                        scene,
+                        &focus,
+                        offset,
+                        theme.stroke,
+                        &circle,
+                    );
+                }
+            }
+        }
+        match &self.rolling {
+            Some(rolling) if tumbling || state.phase() == Phase::Move => {
+                let (faces, tilt) = rolling.tumble.at(rolling.elapsed(now));
+                let middle = board.dimension() / 2.0;
+                DiceFaces::new(
+                    offset,
+                    Point::new(middle, middle),
+                    faces.to_vec(),
+                    board.metrics().dim * 1.2,
+                    tilt,
+                )
+                .draw(scene, theme);
+            }
+            _ => {}
+        }
+    }
+
+    /// Plays what a click at `point` of `board`, in board pixels, asks
+    /// for: throws the dice, picks a plane of the player to move, or moves
+    /// the picked one to a destination it can reach. Tumbling dice settle
+    /// first, and paused games and the computer's seats take no clicks.
+    pub fn click(
+        &mut self,
+        board: &flight_core::Board,
+        point: Point,
+    ) -> Result<()> {
+        if self.paused.is_some()
+            || self.motion.is_some()
+            || self.skip()
+            || self.automated()
+        {
+            return Ok(());
+        }
+        let state = self.game.state();
+        let turn = state.turn().seat;
+        let mv = match state.phase() {
+            Phase::Finished => None,
+            Phase::Roll => Some(self.roll()),
+            Phase::Move if state.legal_moves() == [Move::Pass] => {
+                Some(Move::Pass)
+            }
+            Phase::Move => {
+                let cell =
+                    board.hit_test(point).or_else(|| board.cell_index(point));
+                let target = self.selected.and_then(|plane| {
+                    let targets = targets(state, plane);
+                    targets.into_iter().find(|&(_, to)| Some(to) == cell)
+                });
+                let picked = Pieces::new(Affine::IDENTITY, board, state)
+                    .at(point)
+                    .filter(|plane| plane.seat == turn)
+                    .filter(|plane| !targets(state, plane.plane).is_empty());
+                self.selected = picked.map(|plane| plane.plane);
+                self.target = None;
+                target.map(|(mv, _)| mv)
+            }
+        };
+        match mv {
+            Some(mv) => self.play(mv),
+            None => Ok(()),
+        }
+    }
+
+    /// Picks the next plane of the player to move able to move, the
+    /// previous one unless `forward`, and focuses its first destination.
+    pub fn cycle(&mut self, forward: bool) {
+        if !self.listening() || self.game.state().phase() != Phase::Move {
+            return;
+        }
+        let state = self.game.state();
+        let movable: Vec<_> = (0..state.turn().planes.len())
+            .filter(|&plane| !targets(state, plane).is_empty())
+            .collect();
+        let current = self
+            .selected
+            .and_then(|plane| movable.iter().position(|&p| p == plane));
+        self.selected =
+            step(movable.len(), current, forward).map(|index| movable[index]);
+        self.target = self.selected.map(|_| 0);
+    }
+
+    /// Focuses the next destination of the picked plane, the previous one
+    /// unless `forward`.
+    pub fn cycle_target(&mut self, forward: bool) {
+        let Some(plane) = self.selected.filter(|_| self.listening()) else {
+            return;
+        };
+        let count = targets(self.game.state(), plane).len();
+        self.target = step(count, self.target, forward);
+    }
+
+    /// Settles the dice if they are tumbling, or throws them if the player
+    /// to move is to roll.
+    pub fn throw(&mut self) -> Result<()> {
+        if self.skip()
+            || !self.listening()
+            || self.game.state().phase() != Phase::Roll
+        {
+            return Ok(());
+        }
+        let mv = self.roll();
+        self.play(mv)
+    }
+
+    /// Plays what the keyboard has focused: the throw when it is time to
+    /// roll, the pass when no plane can move, or the picked plane to its
+    /// focused destination. Picks the first plane able to move if none is.
+    pub fn confirm(&mut self) -> Result<()> {
+        if !self.listening() {
+            return self.throw();
+        }
+        let state = self.game.state();
+        let mv = match state.phase() {
+            Phase::Roll => return self.throw(),
+            Phase::Move if state.legal_moves() == [Move::Pass] => Move::Pass,
+            _ => {
+                let focused = self.selected.zip(self.target);
+                let target = focused.and_then(|(plane, index)| {
+                    targets(state, plane).get(index).copied()
+                });
+                match target {
+                    Some((mv, _)) => mv,
+                    None => {
+                        self.cycle(true);
+                        return Ok(());
+                    }
+                }
+            }
+        };
+        self.play(mv)
+    }
+
+    /// Lets a computer player play its throw or move, once what was played
+    /// last was drawn and had a moment to be followed.
+    pub fn tick(&mut self) -> Result<()> {
+        let state = self.game.state();
+        if state.phase() == Phase::Finished
+            || self.busy()
+            || self.paused.is_some()
+            || self.now() - self.played < Self::PAUSE
+        {
+            return Ok(());
+        }
+        let turn = state.turn().seat;
+        if !self.automated() {
+            return Ok(());
+        }
+        let mv = match state.phase() {
+            Phase::Roll => self.roll(),
+            _ => {
+                let state = self.game.state();
+                let legal = state.legal_moves();
+                let ais = self.ais.iter_mut();
+                let (_, ai) = ais
+                    .into_iter()
+                    .find(|(seat, _)| *seat == turn)
+                    .expect("the computer plays the turn");
+                ai.choose_move(state, &legal, Self::BUDGET)
+            }
+        };
+        self.play(mv)
+    }
+
+    /// Settles the dice at once if they are tumbling, returning whether
+    /// they were.
+    pub fn skip(&mut self) -> bool {
+        let now = self.now();
+        match &mut self.rolling {
+            Some(rolling) if rolling.tumbling(now) => {
+                rolling.skipped = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Throws the dice for the player to move.
+    fn roll(&mut self) -> Move {
+        let throw = self.dice.roll();
+        let total = throw.total();
+        self.thrown = throw.values;
+        Move::Roll(total)
+    }
+
+    /// Plays `mv`, flying its planes and tumbling its dice.
+    fn play(&mut self, mv: Move) -> Result<()> {
+        self.selected = None;
+        self.target = None;
+        self.played = Instant::now();
+        let state = self.game.state();
+        if let Some(route) = state.route(mv)? {
+            let legs = u32::try_from(route.legs.len())?;
+            self.motion = Some(Motion {
+                from: state.clone(),
+                route,
+                started: Instant::now(),
+                duration: self.step * legs.max(1),
+            });
+        }
+        self.game.play(mv)?;
+        let events = self.events.take();
+        for event in events {
+            if let GameEvent::DiceRolled { .. } = event {
+                let spec = self.game.state().rules().dice;
+                let faces = self.thrown.clone();
+                let tumble =
+                    Tumble::new(spec, faces, Self::TUMBLE, &mut rand::rng());
+                self.rolling = Some(Rolling {
+                    tumble,
+                    started: Instant::now(),
+                    skipped: false,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Lines of the HUD: who is to do what with which throw and the turn
+    /// counted in throws, and whether the game is paused, or the ranking
+    /// once the game is over. Throws stay hidden while the dice tumble.
+    pub fn status(&self, theme: &Theme, strings: &Strings) -> Vec<Vec<Span>> {
+        let state = self.game.state();
+        let text = theme.stroke;
+        if state.phase() == Phase::Finished {
+            let over = Span::strong(strings.get("game-over"), text);
+            let mut lines = vec![vec![over]];
+            lines.extend(ranking(state, theme, strings));
+            return lines;
+        }
+        let doing = match state.dice() {
+            _ if state.phase() == Phase::Roll && self.automated() => {
+                strings.get("to-roll").into()
+            }
+            _ if state.phase() == Phase::Roll => {
+                strings.get("to-roll-here").into()
+            }
+            Some(_) if self.tumbling() => strings.get("throws").into(),
+            Some(total) if self.automated() => {
+                strings.fill("threw", &[("total", &total)])
+            }
+            Some(total) => strings.fill("threw-pick", &[("total", &total)]),
+            None => strings.get("to-move").into(),
+        };
+        let plies = self.game.record().plies.len();
+        let turn = plies + usize::from(state.phase() == Phase::Roll);
+        let turn = strings.fill("turn", &[("turn", &turn)]);
+        let mut clock = vec![Span::plain(turn, text)];
+        if self.paused.is_some() {
+            clock.push(Span::strong(strings.get("paused"), text));
+            clock.push(Span::plain(strings.get("resume-hint"), text));
+        }
+        vec![
+            vec![
+                name(state.turn().seat, theme, strings),
+                Span::plain(format!(" {doing}"), text),
+            ],
+            clock,
+        ]
+    }
+}
+
+/// A line for every seat that finished, in the order they did, noting
+/// those who resigned.
+pub fn ranking(
+    state: &GameState,
+    theme: &Theme,
+    strings: &Strings,
+) -> Vec<Vec<Span>> {
+    let text = theme.stroke;
+    let places = state.ranking().iter().enumerate();
+    places
+        .map(|(place, &seat)| {
+            let place = strings.fill("place", &[("place", &(place + 1))]);
+            let mut line = vec![Span::plain(place, text)];
+            line.push(name(seat, theme, strings));
+            if state.resigned().contains(&seat) {
+                line.push(Span::plain(strings.get("resigned"), text));
+            }
+            line
+        })
+        .collect()
+}
+
+/// The color of `seat` as its name, in that color.
+pub fn name(seat: usize, theme: &Theme, strings: &Strings) -> Span {
+    Span::strong(strings.color(seat), theme.player(seat))
+}
+
+/// A move being drawn as its planes fly it.
+struct Motion {
+    /// State the move was played in.
+    from: GameState,
+    route: Route,
+    started: Instant,
+    duration: Duration,
+}
+
+/// A throw of the dice as drawn, tumbling for a while unless skipped.
+struct Rolling {
+    tumble: Tumble,
+    started: Instant,
+    skipped: bool,
+}
+
+impl Rolling {
+    /// Time tumbled by `now`.
+    fn elapsed(&self, now: Instant) -> Duration {
+        if self.skipped {
+            self.tumble.duration()
+        } else {
+            now - self.started
+        }
+    }
+
+    fn tumbling(&self, now: Instant) -> bool {
+        self.elapsed(now) < self.tumble.duration()
+    }
+}
+
+/// Computer players by seat for the seats of `state` that `settings` do
+/// not leave to the window, drawing their moves from `seed`.
+fn players(
+    state: &GameState,
+    seed: u64,
+    settings: &Settings,
+) -> Result<Vec<(usize, Box<dyn AiPlayer>)>> {
+    let mut ais = vec![];
+    for (index, side) in state.sides().iter().enumerate() {
+        let player = settings.player(index);
+        if player != Settings::HUMAN {
+            let seed = seed.wrapping_add(side.seat as u64);
+            ais.push((side.seat, ai::named(player, seed)?));
+        }
+    }
+    Ok(ais)
+}
+
+/// Moves the player to move can make with `plane` and the cell each lands
+/// it on, as `legal_moves` allows them.
+fn targets(state: &GameState, plane: usize) -> Vec<(Move, CellId)> {
+    let seat = state.turn().seat;
+    let mut targets = vec![];
+    for mv in state.legal_moves() {
+        let (Move::Launch { plane: moved } | Move::Advance { plane: moved }) =
+            mv
+        else {
+            continue;
+        };
+        if moved != plane {
+            continue;
+        }
+        let Ok(next) = state.apply(mv) else {
+            continue;
+        };
+        let side = next.sides().iter().find(|side| side.seat == seat);
+        let cell = match side.map(|side| side.planes[plane]) {
+            Some(Position::Track(cell)) => cell,
+            Some(Position::Home) => state.track().goal(seat),
+            Some(Position::Hangar) | None => continue,
+        };
+        targets.push((mv, cell));
+    }
+    targets
+}
+
+/// Index after `current` among `count`, before it unless `forward`,
+/// wrapping around and starting at either end.
+pub(crate) fn step(
+    count: usize,
+    current: Option<usize>,
+    forward: bool,
+) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    Some(match (current, forward) {
+        (None, true) => 0,
+        (None, false) => count - 1,
+        (Some(index), true) => (index + 1) % count,
+        (Some(index), false) => (index + count - 1) % count,
+    })
+}
@@ -0,0 +1,892 @@
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+#[cfg(not(target_os = "android"))]
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail, ensure};
+use serde::{Deserialize, Serialize};
+use vello::kurbo::{
+    Affine, BezPath, Cap, Circle, Join, Point, Rect, Shape, Stroke, Vec2,
+};
+use vello::peniko::color::HueDirection;
+use vello::peniko::color::palette::css;
+use vello::peniko::{
+    Blob, Color, Extend, Gradient, ImageAlphaType, ImageBrush, ImageData,
+    ImageFormat,
+};
+
+/// Colors and stroke styling used to draw the board.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Player colors, in seating order, reused from the first when there
+    /// are more seats than colors.
+    #[serde(with = "colors")]
+    pub players: Vec<Color>,
+    /// Color the board is composited over.
+    #[serde(with = "color")]
+    pub background: Color,
+    /// Color of the spots drawn on cells and home bases.
+    #[serde(with = "color")]
+    pub accent: Color,
+    /// Color of the track outlines.
+    #[serde(with = "color")]
+    pub stroke: Color,
+    /// Width of the track outlines at the default cell size, scaled with
+    /// the cells.
+    pub stroke_width: f64,
+    /// Size of the spots on cells, runways and hangars relative to the
+    /// default.
+    pub spot_scale: f64,
+    /// Shape of the spots on cells, runways and hangars.
+    pub spot_shape: SpotShape,
+    /// Joins, caps and dashes of the track and runway outlines.
+    pub outline: LineStyle,
+    /// Line drawn down the middle of each runway, none if not given.
+    pub runway_line: Option<LineStyle>,
+    /// Line each shortcut flies along.
+    pub shortcut_line: LineStyle,
+    /// Whether the middle of the board is decorated with a compass rose in
+    /// the player colors.
+    pub hub: bool,
+    /// How each home base shades its player's color.
+    pub base: Shading,
+    /// Whether home bases and planes cast soft shadows on the board.
+    pub shadows: bool,
+    /// Whether cells and planes are marked with a pattern for each player
+    /// color, for players who cannot tell the colors apart.
+    pub patterns: bool,
+    /// Image tiled over the board instead of the plain background.
+    pub background_texture: Option<Texture>,
+    /// Decoration filling the whole output around and under the board,
+    /// none if not given.
+    pub backdrop: Option<BackdropStyle>,
+    /// Image tiled over the home bases under their shading.
+    pub base_texture: Option<Texture>,
+    /// Artwork drawn for each plane instead of the built-in silhouette.
+    pub plane_skin: Option<Skin>,
+    /// Artwork drawn for the body of each die instead of the rounded
+    /// square, the pips drawn over it.
+    pub dice_skin: Option<Skin>,
+    /// Typeface of the text drawn over the board.
+    pub font: Font,
+}
+
+impl Theme {
+    /// The theme in `path` with the textures, skins and fonts it names,
+    /// relative to it, all among the APK's assets on Android.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = read_text(path)?;
+        let mut theme: Self = toml::from_str(&text)
+            .with_context(|| format!("invalid theme {}", path.display()))?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let textures = [&mut theme.background_texture, &mut theme.base_texture];
+        for texture in textures.into_iter().flatten() {
+            texture.load(directory)?;
+        }
+        let skins = [&mut theme.plane_skin, &mut theme.dice_skin];
+        for skin in skins.into_iter().flatten() {
+            skin.load(directory)?;
+        }
+        theme.font.load(directory)?;
+        Ok(theme)
+    }
+
+    /// The built-in look named `name`, or else the theme file at that path.
+    pub fn find(name: &Path) -> Result<Self> {
+        let look = name.to_str().and_then(crate::looks::named);
+        look.map_or_else(|| Self::load(name), Ok)
+    }
+
+    pub fn player(&self, index: usize) -> Color {
+        self.players[index % self.players.len()]
+    }
+
+    /// The color called `name`: `player1` and on up to the number of player
+    /// colors, `background`, `accent` or `stroke`.
+    pub fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        match name {
+            "background" => Some(&mut self.background),
+            "accent" => Some(&mut self.accent),
+            "stroke" => Some(&mut self.stroke),
+            _ => {
+                let seat: usize = name.strip_prefix("player")?.parse().ok()?;
+                self.players.get_mut(seat.checked_sub(1)?)
+            }
+        }
+    }
+
+    /// Every pair of colors drawn one over the other, with the contrast
+    /// they need: 4.5 for text, 3 for outlines and spots, as WCAG asks.
+    pub fn color_pairs(&self) -> Vec<ColorPair> {
+        let pair = ColorPair::new;
+        let background = ("background", self.background);
+        let accent = ("accent", self.accent);
+        let stroke = ("stroke", self.stroke);
+        // The HUD writes in the stroke color, cell numbers over a halo of
+        // the accent color.
+        let mut pairs =
+            vec![pair(stroke, background, 4.5), pair(stroke, accent, 4.5)];
+        for (index, &color) in self.players.iter().enumerate() {
+            let name = format!("player{}", index + 1);
+            let player = (name.as_str(), color);
+            // Seats are named in their color in the HUD, their cells are
+            // outlined and spotted.
+            pairs.push(pair(player, background, 4.5));
+            pairs.push(pair(stroke, player, 3.0));
+            pairs.push(pair(accent, player, 3.0));
+        }
+        pairs
+    }
+
+    /// Replaces the colors `overrides` name, later ones winning.
+    pub fn override_colors(
+        &mut self,
+        overrides: &[ColorOverride],
+    ) -> Result<()> {
+        for ColorOverride { name, color } in overrides {
+            let Some(slot) = self.color_mut(name) else {
+                bail!("the theme has {} player colors, no {name}", {
+                    self.players.len()
+                });
+            };
+            *slot = *color;
+        }
+        Ok(())
+    }
+}
+
+/// Two theme colors drawn one over the other.
+pub struct ColorPair {
+    pub front: String,
+    pub back: String,
+    /// WCAG contrast ratio between them, from 1 to 21.
+    pub ratio: f64,
+    /// Least ratio the pair needs to stay legible.
+    pub minimum: f64,
+}
+
+impl ColorPair {
+    /// `front` over `back`, each a color with its name, needing `minimum`.
+    fn new(
+        (front, over): (&str, Color),
+        (back, under): (&str, Color),
+        minimum: f64,
+    ) -> Self {
+        Self {
+            front: front.into(),
+            back: back.into(),
+            ratio: contrast(over, under),
+            minimum,
+        }
+    }
+}
+
+/// WCAG contrast ratio between two opaque colors.
+pub fn contrast(a: Color, b: Color) -> f64 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// WCAG relative luminance of an sRGB color.
+fn luminance(color: Color) -> f64 {
+    let [red, green, blue, _] = color.components.map(|channel| {
+        let channel = f64::from(channel);
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    });
+    0.2126 * red + 0.7152 * green + 0.0722 * blue
+}
+
+/// One color of a theme replaced, written `name=color` such as
+/// `player1=#ff5533` or `stroke=#222222`. Player colors count from 1.
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ColorOverride {
+    pub name: String,
+    pub color: Color,
+}
+
+impl ColorOverride {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let Some((name, color)) = text.split_once('=') else {
+            return Err("expected name=color".into());
+        };
+        let name = name.trim();
+        let seat = name.strip_prefix("player").map(str::parse::<usize>);
+        let known = matches!(name, "background" | "accent" | "stroke")
+            || matches!(seat, Some(Ok(seat)) if seat > 0);
+        if !known {
+            return Err(format!(
+                "unknown color {name:?}, expected player1, player2 and on, \
+                 background, accent or stroke"
+            ));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            color: color::parse(color.trim())?,
+        })
+    }
+}
+
+impl TryFrom<String> for ColorOverride {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        Self::parse(&text)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            players: vec![
+                css::RED,
+                css::YELLOW,
+                css::BLUE,
+                css::GREEN,
+                css::PURPLE,
+                css::ORANGE,
+            ],
+            background: css::GRAY.lerp(
+                css::WHITE,
+                0.5,
+                HueDirection::Increasing,
+            ),
+            accent: css::WHITE,
+            stroke: css::BLACK,
+            stroke_width: 5.0,
+            spot_scale: 1.0,
+            spot_shape: SpotShape::Circle,
+            outline: LineStyle::default(),
+            runway_line: Some(LineStyle {
+                width: Some(3.0),
+                dashes: vec![16.0, 16.0],
+                ..LineStyle::default()
+            }),
+            shortcut_line: LineStyle {
+                cap: Cap::Butt,
+                dashes: vec![32.0, 32.0],
+                ..LineStyle::default()
+            },
+            hub: true,
+            base: Shading::default(),
+            shadows: true,
+            patterns: false,
+            background_texture: None,
+            backdrop: None,
+            base_texture: None,
+            plane_skin: None,
+            dice_skin: None,
+            font: Font::default(),
+        }
+    }
+}
+
+/// A raster image such as wood, felt or metal, repeated over an area.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Texture {
+    /// PNG file of the image, relative to the theme.
+    pub path: PathBuf,
+    /// Board pixels an image pixel covers at the default cell size,
+    /// scaled with the cells.
+    #[serde(default = "Texture::default_scale")]
+    pub scale: f64,
+    #[serde(skip)]
+    image: Option<ImageBrush>,
+}
+
+impl Texture {
+    fn default_scale() -> f64 {
+        1.0
+    }
+
+    /// Decodes the image, found relative to `directory`.
+    fn load(&mut self, directory: &Path) -> Result<()> {
+        let path = directory.join(&self.path);
+        let image = decode(&read(&path)?)
+            .with_context(|| format!("invalid texture {}", path.display()))?;
+        self.image = Some(ImageBrush::new(image).with_extend(Extend::Repeat));
+        Ok(())
+    }
+
+    /// The repeating image and the transform laying its pixels out on a
+    /// board of cells `scale` times the default size, none before it is
+    /// loaded.
+    pub fn brush(&self, scale: f64) -> Option<(&ImageBrush, Affine)> {
+        let image = self.image.as_ref()?;
+        Some((image, Affine::scale(self.scale * scale)))
+    }
+}
+
+/// Typeface and size of text, looked up among the system fonts and the
+/// font files the theme brings.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Font {
+    /// Families to write in, as a CSS `font-family` list such as
+    /// `"Inter, sans-serif"`. The bundled font stands in for whatever they
+    /// lack, and is used alone without them.
+    pub family: Option<String>,
+    /// Size of the text relative to the default.
+    pub scale: f32,
+    /// TrueType or OpenType files providing families, relative to the
+    /// theme.
+    pub files: Vec<PathBuf>,
+    /// Contents of `files`.
+    #[serde(skip)]
+    data: Vec<Blob<u8>>,
+}
+
+impl Font {
+    /// Family of the bundled font, the same on every platform.
+    pub const BUNDLED_FAMILY: &str = "DejaVu Sans";
+    /// Regular and bold faces of the bundled font.
+    pub const BUNDLED: [&[u8]; 2] = [
+        include_bytes!("../fonts/DejaVuSans.ttf"),
+        include_bytes!("../fonts/DejaVuSans-Bold.ttf"),
+    ];
+
+    /// Families to look up, in CSS `font-family` form, ending with the
+    /// bundled one.
+    pub fn families(&self) -> String {
+        match &self.family {
+            Some(family) => format!("{family}, {}", Self::BUNDLED_FAMILY),
+            None => Self::BUNDLED_FAMILY.into(),
+        }
+    }
+
+    /// Reads the font files, found relative to `directory`.
+    fn load(&mut self, directory: &Path) -> Result<()> {
+        self.data = self
+            .files
+            .iter()
+            .map(|file| read(&directory.join(file)).map(Blob::from))
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+
+    /// Contents of the font files, none before they are loaded.
+    pub fn data(&self) -> &[Blob<u8>] {
+        &self.data
+    }
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self {
+            family: None,
+            scale: 1.0,
+            files: vec![],
+            data: vec![],
+        }
+    }
+}
+
+/// Vector artwork replacing the outline of a piece, filled and stroked in
+/// the piece's colors like the outline it replaces.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Skin {
+    /// SVG file of the artwork, relative to the theme. Its shapes make up
+    /// the outline, in place but without their styles.
+    pub path: PathBuf,
+    /// The outline and the view box it is drawn in.
+    #[serde(skip)]
+    shape: Option<(BezPath, Rect)>,
+}
+
+impl Skin {
+    /// Parses the artwork, found relative to `directory`.
+    fn load(&mut self, directory: &Path) -> Result<()> {
+        let path = directory.join(&self.path);
+        let text = read_text(&path)?;
+        let shape = outline(&text)
+            .with_context(|| format!("invalid skin {}", path.display()))?;
+        self.shape = Some(shape);
+        Ok(())
+    }
+
+    /// The outline scaled to fit `area` and centered in it, none before
+    /// it is loaded.
+    pub fn fit(&self, area: Rect) -> Option<BezPath> {
+        let (path, view) = self.shape.as_ref()?;
+        let scale =
+            (area.width() / view.width()).min(area.height() / view.height());
+        let affine = Affine::translate(area.center().to_vec2())
+            * Affine::scale(scale)
+            * Affine::translate(-view.center().to_vec2());
+        Some(affine * path)
+    }
+}
+
+/// The shapes of an SVG document joined into one outline, placed by
+/// their transforms in the document's view, with the view's size. Shapes
+/// and `<use>` copies become paths, and sizes in percents or units are
+/// resolved. Documents with images or text are refused, as they have no
+/// outline.
+fn outline(text: &str) -> Result<(BezPath, Rect)> {
+    let tree = usvg::Tree::from_str(text, &usvg::Options::default())?;
+    let mut outline = BezPath::new();
+    flatten(tree.root(), &mut outline)?;
+    ensure!(!outline.is_empty(), "no shapes");
+    let size = tree.size();
+    let view = Rect::new(0.0, 0.0, size.width().into(), size.height().into());
+    ensure!(view.area() > 0.0, "empty view box");
+    Ok((outline, view))
+}
+
+/// Adds the paths of `group` and its descendants to `outline`, with every
+/// transform above them applied.
+fn flatten(group: &usvg::Group, outline: &mut BezPath) -> Result<()> {
+    for node in group.children() {
+        let path = match node {
+            usvg::Node::Group(group) => {
+                flatten(group, outline)?;
+                continue;
+            }
+            usvg::Node::Path(path) => path,
+            usvg::Node::Image(_) => bail!("images have no outline"),
+            usvg::Node::Text(_) => bail!("text has no outline"),
+        };
+        let transform = path.abs_transform();
+        let data = path.data().clone().transform(transform);
+        let data = data.context("a transform collapses a path")?;
+        let point =
+            |p: usvg::tiny_skia_path::Point| Point::new(p.x.into(), p.y.into());
+        for segment in data.segments() {
+            use usvg::tiny_skia_path::PathSegment;
+            match segment {
+                PathSegment::MoveTo(p) => outline.move_to(point(p)),
+                PathSegment::LineTo(p) => outline.line_to(point(p)),
+                PathSegment::QuadTo(p1, p) => {
+                    outline.quad_to(point(p1), point(p));
+                }
+                PathSegment::CubicTo(p1, p2, p) => {
+                    outline.curve_to(point(p1), point(p2), point(p));
+                }
+                PathSegment::Close => outline.close_path(),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Contents of the file at `path`, or of the asset packaged in the APK
+/// there on Android.
+fn read(path: &Path) -> Result<Vec<u8>> {
+    #[cfg(target_os = "android")]
+    let bytes = crate::android::read(path);
+    #[cfg(not(target_os = "android"))]
+    let bytes = fs::read(path);
+    bytes.with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// The text of the file at `path`, as [`read`] finds it.
+fn read_text(path: &Path) -> Result<String> {
+    String::from_utf8(read(path)?)
+        .with_context(|| format!("invalid text in {}", path.display()))
+}
+
+/// The 8-bit RGBA pixels of a PNG image.
+fn decode(bytes: &[u8]) -> Result<ImageData> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(
+        png::Transformations::EXPAND
+            | png::Transformations::STRIP_16
+            | png::Transformations::ALPHA,
+    );
+    let mut reader = decoder.read_info()?;
+    let size = reader.output_buffer_size().context("image too large")?;
+    let mut pixels = vec![0; size];
+    let info = reader.next_frame(&mut pixels)?;
+    pixels.truncate(info.buffer_size());
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => pixels,
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|gray| [gray[0], gray[0], gray[0], gray[1]])
+            .collect(),
+        other => bail!("unsupported color type {other:?}"),
+    };
+    Ok(ImageData {
+        data: Blob::from(pixels),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: info.width,
+        height: info.height,
+    })
+}
+
+/// How a line is stroked, its lengths authored for the default cell size
+/// and scaled with the cells.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LineStyle {
+    /// Width of the line, the theme's stroke width if not given.
+    pub width: Option<f64>,
+    /// Shape of the corners, `miter`, `round` or `bevel`.
+    #[serde(with = "JoinDef")]
+    pub join: Join,
+    /// Shape of the ends, `butt`, `round` or `square`.
+    #[serde(with = "CapDef")]
+    pub cap: Cap,
+    /// Lengths of the dashes and the gaps between them in turn, solid if
+    /// empty.
+    pub dashes: Vec<f64>,
+}
+
+impl LineStyle {
+    /// The stroke of a line on cells `scale` times the default size, as
+    /// wide as `width` unless the style says otherwise.
+    pub fn stroke(&self, width: f64, scale: f64) -> Stroke {
+        let stroke = Stroke::new(self.width.unwrap_or(width) * scale)
+            .with_join(self.join)
+            .with_caps(self.cap);
+        if self.dashes.is_empty() {
+            return stroke;
+        }
+        let dashes = self.dashes.iter().map(|length| length * scale);
+        stroke.with_dashes(0.0, dashes)
+    }
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self {
+            width: None,
+            join: Join::Round,
+            cap: Cap::Round,
+            dashes: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Join", rename_all = "lowercase")]
+enum JoinDef {
+    Bevel,
+    Miter,
+    Round,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Cap", rename_all = "lowercase")]
+enum CapDef {
+    Butt,
+    Square,
+    Round,
+}
+
+/// Kind of gradient an area is filled with.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShadingKind {
+    /// The plain color.
+    Flat,
+    /// From the outer corner across to the opposite one.
+    Linear,
+    /// From the middle out to the corners.
+    Radial,
+}
+
+/// A gradient shading a player's color over an area of the board.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shading {
+    pub kind: ShadingKind,
+    /// Offsets along the gradient from 0 to 1, each with how far the color
+    /// is lightened toward white there, or darkened toward black when
+    /// negative.
+    pub stops: Vec<(f32, f32)>,
+}
+
+impl Shading {
+    /// Gradient filling `area` in shades of `color`, none when flat.
+    pub fn gradient(&self, color: Color, area: Rect) -> Option<Gradient> {
+        let gradient = match self.kind {
+            ShadingKind::Flat => return None,
+            ShadingKind::Linear => {
+                Gradient::new_linear(area.origin(), (area.x1, area.y1))
+            }
+            ShadingKind::Radial => {
+                let radius = area.size().to_vec2().hypot() / 2.0;
+                Gradient::new_radial(area.center(), radius as f32)
+            }
+        };
+        let stops = self.stops.iter().map(|&(offset, shade)| {
+            let toward = if shade < 0.0 { css::BLACK } else { css::WHITE };
+            let shaded =
+                color.lerp(toward, shade.abs(), HueDirection::default());
+            (offset, shaded)
+        });
+        Some(gradient.with_stops(stops.collect::<Vec<_>>().as_slice()))
+    }
+}
+
+impl Default for Shading {
+    fn default() -> Self {
+        Self {
+            kind: ShadingKind::Linear,
+            stops: vec![(0.0, 0.35), (0.6, 0.0), (1.0, -0.15)],
+        }
+    }
+}
+
+/// Outline of the accent spots.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpotShape {
+    Circle,
+    /// A five-pointed star, point up.
+    Star,
+    /// Five round petals around a heart.
+    Flower,
+    /// Six arms with a pair of branches each.
+    Snowflake,
+    /// A pointed leaf, tipped up and to the right.
+    Leaf,
+}
+
+impl SpotShape {
+    /// Flattening accuracy of the circles making up shapes.
+    const TOLERANCE: f64 = 0.1;
+
+    /// The shape fitting in the circle on `center` of `radius`, none for a
+    /// circle, which canvases draw as such.
+    pub fn path(self, center: Point, radius: f64) -> Option<BezPath> {
+        let mut path = BezPath::new();
+        match self {
+            Self::Circle => return None,
+            Self::Star => {
+                let points = (0..10).map(|index| {
+                    let length = if index % 2 == 0 { 1.0 } else { 0.45 };
+                    let angle = f64::from(index) * PI / 5.0 - FRAC_PI_2;
+                    (Vec2::from_angle(angle) * length).to_point()
+                });
+                for (index, point) in points.enumerate() {
+                    if index == 0 {
+                        path.move_to(point);
+                    } else {
+                        path.line_to(point);
+                    }
+                }
+                path.close_path();
+            }
+            Self::Flower => {
+                for petal in 0..5 {
+                    let angle = f64::from(petal) * TAU / 5.0 - FRAC_PI_2;
+                    let middle = (Vec2::from_angle(angle) * 0.55).to_point();
+                    path.extend(
+                        Circle::new(middle, 0.45)
+                            .path_elements(Self::TOLERANCE / radius),
+                    );
+                }
+                path.extend(
+                    Circle::new(Point::ZERO, 0.4)
+                        .path_elements(Self::TOLERANCE / radius),
+                );
+            }
+            Self::Snowflake => {
+                let arm = Rect::new(0.0, -0.1, 1.0, 0.1);
+                let branch = Rect::new(0.0, -0.08, 0.4, 0.08);
+                for spoke in 0..6 {
+                    let turn = Affine::rotate(f64::from(spoke) * TAU / 6.0);
+                    path.extend(turn * arm.to_path(Self::TOLERANCE));
+                    for side in [-1.0, 1.0] {
+                        let affine = turn
+                            * Affine::translate((0.55, 0.0))
+                            * Affine::rotate(side * PI / 3.0);
+                        path.extend(affine * branch.to_path(Self::TOLERANCE));
+                    }
+                }
+            }
+            Self::Leaf => {
+                let (tip, stem) = ((0.7, -0.7), (-0.7, 0.7));
+                path.move_to(stem);
+                path.quad_to((0.7, 0.7), tip);
+                path.quad_to((-0.7, -0.7), stem);
+                path.close_path();
+            }
+        }
+        Some(Affine::translate(center.to_vec2()) * Affine::scale(radius) * path)
+    }
+}
+
+/// Kind of decoration a backdrop draws.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BackdropKind {
+    /// Darkening toward the edges of the output.
+    Vignette,
+    /// Small planes repeated in staggered rows.
+    Motif,
+    /// Snowflakes of varied sizes scattered over the output.
+    Snowflakes,
+    /// Bursts of rays scattered over the output.
+    Fireworks,
+}
+
+/// Decoration laid over the background color of a whole output, so the
+/// board can sit on a wallpaper or menu background of any size.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackdropStyle {
+    pub kind: BackdropKind,
+    /// Color of the vignette or the motif.
+    #[serde(with = "color")]
+    pub color: Color,
+    /// Opacity of the color where it is strongest, a default suiting the
+    /// kind if not given.
+    pub opacity: Option<f32>,
+    /// Distance between the planes of a motif at the default cell size,
+    /// scaled with the cells.
+    pub spacing: f64,
+}
+
+impl BackdropStyle {
+    /// Opacity of the color where it is strongest.
+    pub fn opacity(&self) -> f32 {
+        self.opacity.unwrap_or(match self.kind {
+            BackdropKind::Vignette => 0.45,
+            BackdropKind::Motif => 0.1,
+            BackdropKind::Snowflakes | BackdropKind::Fireworks => 0.5,
+        })
+    }
+}
+
+impl Default for BackdropStyle {
+    fn default() -> Self {
+        Self {
+            kind: BackdropKind::Vignette,
+            color: css::BLACK,
+            opacity: None,
+            spacing: 256.0,
+        }
+    }
+}
+
+/// Serde adapter storing a [`Color`] as a CSS color string.
+pub mod color {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use vello::peniko::Color;
+    use vello::peniko::color::parse_color;
+
+    pub fn serialize<S: Serializer>(
+        color: &Color,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_hex(*color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).map_err(D::Error::custom)
+    }
+
+    pub fn to_hex(color: Color) -> String {
+        let rgba = color.to_rgba8();
+        if rgba.a == u8::MAX {
+            format!("#{:02x}{:02x}{:02x}", rgba.r, rgba.g, rgba.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", rgba.r, rgba.g, rgba.b, rgba.a)
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Color, String> {
+        parse_color(text)
+            .map(|color| color.to_alpha_color())
+            .map_err(|e| format!("invalid color {text:?}: {e}"))
+    }
+}
+
+/// Serde adapter storing a palette of at least one color as CSS color
+/// strings.
+mod colors {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use vello::peniko::Color;
+
+    pub fn serialize<S: Serializer>(
+        colors: &[Color],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(colors.iter().copied().map(super::color::to_hex))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Color>, D::Error> {
+        let texts = Vec::<String>::deserialize(deserializer)?;
+        if texts.is_empty() {
+            return Err(D::Error::custom("expected at least one color"));
+        }
+        texts
+            .iter()
+            .map(|text| super::color::parse(text))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vello::kurbo::{Rect, Shape};
+
+    use super::outline;
+    use crate::looks;
+
+    #[test]
+    fn skins_place_shapes_by_their_transforms() {
+        let text = r##"<svg xmlns="http://www.w3.org/2000/svg"
+            xmlns:xlink="http://www.w3.org/1999/xlink"
+            width="100%" height="100%" viewBox="0 0 100 50">
+            <defs><rect id="r" width="10" height="10"/></defs>
+            <g transform="translate(20 10)">
+                <circle cx="5" cy="5" r="5"/>
+            </g>
+            <use xlink:href="#r" x="80" y="30"/>
+        </svg>"##;
+        let (path, view) = outline(text).unwrap();
+        assert_eq!(view, Rect::new(0.0, 0.0, 100.0, 50.0));
+        let bounds = path.bounding_box();
+        assert!((bounds.x0 - 20.0).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.y0 - 10.0).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.x1 - 90.0).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.y1 - 40.0).abs() < 1e-3, "{bounds:?}");
+    }
+
+    #[test]
+    fn skins_without_outlines_are_refused() {
+        let empty = r#"<svg xmlns="http://www.w3.org/2000/svg"/>"#;
+        assert!(outline(empty).is_err());
+        let image = r#"<svg xmlns="http://www.w3.org/2000/svg"
+            width="10" height="10">
+            <image width="10" height="10" href="data:image/png;base64,"/>
+        </svg>"#;
+        assert!(outline(image).is_err());
+    }
+
+    #[test]
+    fn high_contrast_meets_every_minimum() {
+        let theme = looks::named("high-contrast").expect("a built-in look");
+        for pair in theme.color_pairs() {
+            assert!(
+                pair.ratio >= pair.minimum,
+                "{} on {}: {:.2}:1, needs {}:1",
+                pair.front,
+                pair.back,
+                pair.ratio,
+                pair.minimum
+            );
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use vello::peniko::Color;
+use vello::peniko::color::HueDirection;
+use vello::peniko::color::palette::css;
+
+use crate::theme::{
+    BackdropKind, BackdropStyle, ColorOverride, SpotShape, Theme, color,
+};
+
+/// Changes deriving a variant of any theme, such as a seasonal one, so
+/// every look gets the variant without a hand-made copy.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Variant {
+    /// Degrees the hue of every player color turns by.
+    pub hue_shift: f32,
+    /// How much lighter every player color gets, from -1 to 1, darker when
+    /// negative.
+    pub lighten: f32,
+    /// Color blended into the player colors and the background.
+    pub tint: Option<Tint>,
+    /// Colors replaced outright after the shift, as `name=color` like
+    /// `--color`.
+    pub colors: Vec<ColorOverride>,
+    /// Shape of the spots, the theme's if not given.
+    pub spot_shape: Option<SpotShape>,
+    /// Decoration around and under the board, the theme's if not given.
+    pub backdrop: Option<BackdropStyle>,
+}
+
+/// A color and how much of it, from 0 to 1, is blended in.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tint {
+    #[serde(with = "color")]
+    pub color: Color,
+    pub amount: f32,
+}
+
+impl Tint {
+    fn apply(&self, color: Color) -> Color {
+        color.lerp(self.color, self.amount, HueDirection::default())
+    }
+}
+
+impl Variant {
+    /// The built-in season called `name`, if there is one.
+    pub fn season(name: &str) -> Option<Self> {
+        let variant = match name {
+            "winter" => Self {
+                tint: Some(Tint {
+                    color: Color::from_rgb8(0xdc, 0xee, 0xff),
+                    amount: 0.35,
+                }),
+                spot_shape: Some(SpotShape::Snowflake),
+                backdrop: Some(BackdropStyle {
+                    kind: BackdropKind::Snowflakes,
+                    color: css::WHITE,
+                    opacity: Some(0.7),
+                    spacing: 192.0,
+                }),
+                ..Self::default()
+            },
+            "spring" => Self {
+                lighten: 0.08,
+                tint: Some(Tint {
+                    color: Color::from_rgb8(0xff, 0xd6, 0xe8),
+                    amount: 0.2,
+                }),
+                spot_shape: Some(SpotShape::Flower),
+                ..Self::default()
+            },
+            "summer" => Self {
+                tint: Some(Tint {
+                    color: Color::from_rgb8(0xff, 0xe0, 0x80),
+                    amount: 0.15,
+                }),
+                spot_shape: Some(SpotShape::Star),
+                backdrop: Some(BackdropStyle {
+                    kind: BackdropKind::Vignette,
+                    color: Color::from_rgb8(0xff, 0x90, 0x00),
+                    opacity: Some(0.35),
+                    ..BackdropStyle::default()
+                }),
+                ..Self::default()
+            },
+            "autumn" => Self {
+                hue_shift: -10.0,
+                lighten: -0.05,
+                tint: Some(Tint {
+                    color: Color::from_rgb8(0xb5, 0x65, 0x1d),
+                    amount: 0.3,
+                }),
+                spot_shape: Some(SpotShape::Leaf),
+                ..Self::default()
+            },
+            "new-year" => Self {
+                colors: vec![ColorOverride {
+                    name: "background".into(),
+                    color: Color::from_rgb8(0x10, 0x14, 0x28),
+                }],
+                spot_shape: Some(SpotShape::Star),
+                backdrop: Some(BackdropStyle {
+                    kind: BackdropKind::Fireworks,
+                    color: Color::from_rgb8(0xff, 0xd7, 0x00),
+                    opacity: Some(0.6),
+                    spacing: 320.0,
+                }),
+                ..Self::default()
+            },
+            _ => return None,
+        };
+        Some(variant)
+    }
+
+    /// The built-in season called `name`, or else the TOML file of changes
+    /// at that path.
+    pub fn find(name: &Path) -> Result<Self> {
+        if let Some(season) = name.to_str().and_then(Self::season) {
+            return Ok(season);
+        }
+        let text = fs::read_to_string(name)
+            .with_context(|| format!("failed to read {}", name.display()))?;
+        toml::from_str(&text)
+            .with_context(|| format!("invalid variant {}", name.display()))
+    }
+
+    /// Applies the changes to `theme`.
+    pub fn apply(&self, theme: &mut Theme) -> Result<()> {
+        for player in &mut theme.players {
+            let shifted = player
+                .map_hue(|hue| hue + self.hue_shift)
+                .map_lightness(|lightness| lightness + self.lighten);
+            *player = clamp(shifted);
+        }
+        if let Some(tint) = &self.tint {
+            for player in &mut theme.players {
+                *player = tint.apply(*player);
+            }
+            theme.background = tint.apply(theme.background);
+        }
+        theme.override_colors(&self.colors)?;
+        if let Some(shape) = self.spot_shape {
+            theme.spot_shape = shape;
+        }
+        if let Some(backdrop) = &self.backdrop {
+            theme.backdrop = Some(backdrop.clone());
+        }
+        Ok(())
+    }
+}
+
+/// `color` brought into the sRGB gamut, which hue and lightness shifts can
+/// leave.
+fn clamp(color: Color) -> Color {
+    Color::new(color.components.map(|component| component.clamp(0.0, 1.0)))
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use flight_core::{Board, RuleSet};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::{HtmlCanvasElement, Storage};
+
+use crate::gpu::AdapterChoice;
+use crate::play;
+
+/// Id of the page's canvas the game draws into. A canvas is appended to
+/// the page if it has none.
+pub const CANVAS: &str = "flight";
+
+/// Starts the game when the module is loaded: the window of `assets play`
+/// on the classic board for four, its settings kept in the page's local
+/// storage and its errors logged to the console.
+///
+/// Built with `cargo build -p assets --lib --target wasm32-unknown-unknown`
+/// and bound with `wasm-bindgen --target web`, see `web/index.html`.
+#[wasm_bindgen(start)]
+pub fn start() {
+    std::panic::set_hook(Box::new(|info| report(&info.to_string())));
+    let result = play::run(
+        None,
+        None,
+        Board::new(4),
+        RuleSet::default(),
+        rand::random(),
+        Duration::from_millis(150),
+        AdapterChoice::default(),
+    );
+    if let Err(error) = result {
+        report(&format!("{error:#}"));
+    }
+}
+
+/// The page's canvas with the id [`CANVAS`], none if it has none.
+pub fn canvas() -> Option<HtmlCanvasElement> {
+    let document = web_sys::window()?.document()?;
+    document.get_element_by_id(CANVAS)?.dyn_into().ok()
+}
+
+/// Logs `message` to the browser console as an error.
+pub fn report(message: &str) {
+    web_sys::console::error_1(&message.into());
+}
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// The text kept under `key` in the page's local storage, none if there is
+/// none or the browser keeps no storage.
+pub fn load(key: &str) -> Option<String> {
+    storage()?.get_item(key).ok()?
+}
+
+/// Keeps `text` under `key` in the page's local storage.
+pub fn save(key: &str, text: &str) -> Result<()> {
+    let storage = storage().context("the browser keeps no local storage")?;
+    storage
+        .set_item(key, text)
+        .map_err(|_| anyhow!("failed to store {key}"))
+}
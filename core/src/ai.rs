@@ -0,0 +1,204 @@
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Result, bail, ensure};
+use rand::rngs::StdRng;
+use rand::{Rng, RngExt, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::expectimax::ExpectimaxPlayer;
+use crate::heuristic::HeuristicPlayer;
+use crate::mcts::MctsPlayer;
+use crate::rules::{GameState, Move};
+
+/// A computer player, deciding what to do with each throw. It is only
+/// asked once the player to move threw, and answers with one of `legal`,
+/// the [`legal_moves`](GameState::legal_moves) of the state, so whichever
+/// moves the rules force, such as passing, are the only ones it sees.
+///
+/// Players of other crates join the built in ones by [`register`]ing.
+pub trait AiPlayer {
+    /// Picks one of `legal`, never empty, thinking for about `budget` at
+    /// most. [`Duration::MAX`] leaves the time to the player.
+    fn choose_move(
+        &mut self,
+        state: &GameState,
+        legal: &[Move],
+        budget: Duration,
+    ) -> Move;
+}
+
+/// Picks among the legal moves at random, each as likely as the next. The
+/// weakest player possible short of losing on purpose, which any other
+/// should beat.
+pub struct RandomPlayer<R> {
+    rng: R,
+}
+
+impl<R: Rng> RandomPlayer<R> {
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+impl RandomPlayer<StdRng> {
+    /// A player making the same choices for the same seed.
+    pub fn seeded(seed: u64) -> Self {
+        Self::new(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng> AiPlayer for RandomPlayer<R> {
+    fn choose_move(
+        &mut self,
+        _state: &GameState,
+        legal: &[Move],
+        _budget: Duration,
+    ) -> Move {
+        legal[self.rng.random_range(0..legal.len())]
+    }
+}
+
+/// Makes a computer player, making the same choices for the same seed.
+pub type Factory = dyn Fn(u64) -> Box<dyn AiPlayer> + Send + Sync;
+
+/// Computer players by name.
+type Registry = Vec<(String, Arc<Factory>)>;
+
+/// The computer players known, the built in ones weakest first and then
+/// the difficulties, followed by those registered.
+static REGISTRY: LazyLock<RwLock<Registry>> = LazyLock::new(|| {
+    let mut players: Registry = vec![
+        (
+            "random".into(),
+            Arc::new(|seed| Box::new(RandomPlayer::seeded(seed))),
+        ),
+        (
+            "heuristic".into(),
+            Arc::new(|_| Box::new(HeuristicPlayer::default())),
+        ),
+        (
+            "expectimax".into(),
+            Arc::new(|_| Box::new(ExpectimaxPlayer::default())),
+        ),
+        (
+            "mcts".into(),
+            Arc::new(|seed| Box::new(MctsPlayer::seeded(seed))),
+        ),
+    ];
+    for difficulty in Difficulty::ALL {
+        let factory = move |seed| difficulty.player(seed);
+        players.push((difficulty.name().into(), Arc::new(factory)));
+    }
+    RwLock::new(players)
+});
+
+/// Makes `factory` the computer player called `name`, so whatever picks
+/// players by name, such as the simulator, can pick it like the built in
+/// ones. Names are taken once.
+pub fn register(
+    name: &str,
+    factory: impl Fn(u64) -> Box<dyn AiPlayer> + Send + Sync + 'static,
+) -> Result<()> {
+    let mut registry = REGISTRY.write().expect("registering does not panic");
+    ensure!(
+        registry.iter().all(|(taken, _)| taken != name),
+        "there already is a computer player called {name:?}"
+    );
+    registry.push((name.into(), Arc::new(factory)));
+    Ok(())
+}
+
+/// Names of the computer players [`named`] makes, in the order they were
+/// registered.
+pub fn names() -> Vec<String> {
+    let registry = REGISTRY.read().expect("registering does not panic");
+    registry.iter().map(|(name, _)| name.clone()).collect()
+}
+
+/// The computer player called `name`, one of [`names`], making the same
+/// choices for the same seed.
+pub fn named(name: &str, seed: u64) -> Result<Box<dyn AiPlayer>> {
+    let factory = {
+        let registry = REGISTRY.read().expect("registering does not panic");
+        match registry.iter().find(|(taken, _)| taken == name) {
+            Some((_, factory)) => factory.clone(),
+            None => bail!(
+                "unknown computer player {name:?}, expected one of {:?}",
+                registry.iter().map(|(name, _)| name).collect::<Vec<_>>()
+            ),
+        }
+    };
+    Ok(factory(seed))
+}
+
+/// How hard a computer opponent is to beat, each level a different player
+/// with a budget fit for playing against people.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    /// Moves at random.
+    Easy,
+    /// Plays the move the heuristic likes best.
+    Normal,
+    /// Searches two throws ahead with expectimax.
+    Hard,
+    /// Searches with Monte Carlo playouts on every core, for up to two
+    /// seconds a move.
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    /// A player of the difficulty, making the same choices for the same
+    /// seed but for the expert, whose search depends on the cores and the
+    /// time it gets.
+    pub fn player(self, seed: u64) -> Box<dyn AiPlayer> {
+        match self {
+            Difficulty::Easy => Box::new(RandomPlayer::seeded(seed)),
+            Difficulty::Normal => Box::new(HeuristicPlayer::default()),
+            Difficulty::Hard => Box::new(ExpectimaxPlayer::default()),
+            Difficulty::Expert => {
+                let mut player = MctsPlayer::seeded(seed);
+                player.iterations = 4000;
+                player.time = Some(Duration::from_secs(2));
+                player.threads =
+                    thread::available_parallelism().map_or(1, usize::from);
+                Box::new(player)
+            }
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name() == text)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(Difficulty::name);
+                format!(
+                    "unknown difficulty {text:?}, expected one of {names:?}"
+                )
+            })
+    }
+}
@@ -0,0 +1,203 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail, ensure};
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::record::{Action, GameRecord};
+use crate::rules::{GameState, Move, Phase, RuleSet};
+
+/// Bytes starting every binary record.
+const MAGIC: &[u8; 4] = b"FLTB";
+
+/// Version of the binary format, following the magic.
+const VERSION: u8 = 1;
+
+/// What a binary record holds besides its plies, as TOML.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    board: Board,
+    rules: RuleSet,
+    seed: Option<u64>,
+}
+
+impl GameRecord {
+    /// Packs the record into a few bits a ply, compressed with zstd. Only
+    /// the throws and which legal move followed each are kept, the rest
+    /// of every ply and its checksum come back by playing the game again,
+    /// so records the rules do not play are refused, as are records of
+    /// games a player resigned.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.replay()?;
+        ensure!(
+            self.resignations.is_empty(),
+            "resignations have no binary form"
+        );
+        let header = toml::to_string(&Header {
+            board: self.board.clone(),
+            rules: self.rules,
+            seed: self.seed,
+        })?;
+        let totals = self.rules.dice.totals();
+        let throw_bits = width(totals.len());
+        let mut bits = BitWriter::default();
+        let mut state = GameState::new(&self.board).with_rules(self.rules);
+        for ply in &self.plies {
+            let mut moves = ply.moves().into_iter();
+            bits.write(u32::from(ply.dice - totals.start()), throw_bits);
+            state =
+                state.apply(moves.next().expect("plies start with a throw"))?;
+            if let Some(mv) = moves.next() {
+                let legal = state.legal_moves();
+                let index = legal.iter().position(|&m| m == mv);
+                let index = index.context("the record was checked")?;
+                bits.write(index as u32, width(legal.len()));
+                state = state.apply(mv)?;
+            }
+        }
+        let pending = self
+            .plies
+            .last()
+            .is_some_and(|ply| ply.action == Action::Pending);
+
+        let mut payload = vec![];
+        payload.extend((header.len() as u32).to_le_bytes());
+        payload.extend(header.as_bytes());
+        payload.extend((self.plies.len() as u32).to_le_bytes());
+        payload.push(u8::from(pending));
+        payload.extend(bits.bytes);
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend(zstd::encode_all(&payload[..], 19)?);
+        Ok(bytes)
+    }
+
+    /// Unpacks a record written by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let Some(rest) = bytes.strip_prefix(MAGIC) else {
+            bail!("not a binary game record");
+        };
+        let (&version, compressed) =
+            rest.split_first().context("missing format version")?;
+        ensure!(
+            version == VERSION,
+            "binary record in format {version}, this version reads {VERSION}"
+        );
+        let payload = zstd::decode_all(compressed)?;
+        let mut reader = BitReader::new(&payload);
+        let length = reader.word()? as usize;
+        let header = reader.bytes(length)?;
+        let header: Header = toml::from_str(std::str::from_utf8(header)?)?;
+        let plies = reader.word()?;
+        let pending = reader.bytes(1)? == [1];
+
+        let mut record = Self {
+            board: header.board,
+            rules: header.rules,
+            seed: header.seed,
+            plies: vec![],
+            checksums: vec![],
+            resignations: vec![],
+        };
+        let totals = record.rules.dice.totals();
+        let throw_bits = width(totals.len());
+        let mut state = GameState::new(&record.board).with_rules(record.rules);
+        for ply in 0..plies {
+            let offset = u8::try_from(reader.read(throw_bits)?)?;
+            let dice = totals.start().checked_add(offset);
+            let roll = Move::Roll(dice.context("throw out of range")?);
+            let next = state.apply(roll)?;
+            record.push(&state, roll, &next);
+            state = next;
+            if state.phase() != Phase::Move || pending && ply + 1 == plies {
+                continue;
+            }
+            let legal = state.legal_moves();
+            let index = reader.read(width(legal.len()))? as usize;
+            let mv = *legal.get(index).context("move out of range")?;
+            let next = state.apply(mv)?;
+            record.push(&state, mv, &next);
+            state = next;
+        }
+        Ok(record)
+    }
+
+    /// Loads a record in either form, binary when the file starts like
+    /// one and TOML otherwise.
+    pub fn read(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let record = if bytes.starts_with(MAGIC) {
+            Self::from_bytes(&bytes)
+        } else {
+            Self::parse(std::str::from_utf8(&bytes)?)
+        };
+        record
+            .with_context(|| format!("invalid game record {}", path.display()))
+    }
+}
+
+/// Bits needed to tell apart `n` values.
+fn width(n: usize) -> u32 {
+    usize::BITS - n.saturating_sub(1).leading_zeros()
+}
+
+/// Bits packed least significant first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits used in the last byte, 8 when it is full.
+    used: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, width: u32) {
+        for bit in 0..width {
+            if self.bytes.is_empty() || self.used == 8 {
+                self.bytes.push(0);
+                self.used = 0;
+            }
+            let last = self.bytes.last_mut().expect("a byte was pushed");
+            *last |= (((value >> bit) & 1) as u8) << self.used;
+            self.used += 1;
+        }
+    }
+}
+
+/// Reads bytes and then bits packed by [`BitWriter`].
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// Position in bits.
+    at: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, at: 0 }
+    }
+
+    /// The next `length` whole bytes, only valid before any bits are read.
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8]> {
+        let start = self.at / 8;
+        let bytes = self.bytes.get(start..start + length);
+        self.at += length * 8;
+        bytes.context("the record ends early")
+    }
+
+    fn word(&mut self) -> Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into()?))
+    }
+
+    fn read(&mut self, width: u32) -> Result<u32> {
+        let mut value = 0;
+        for bit in 0..width {
+            let byte = self.bytes.get(self.at / 8);
+            let byte = byte.context("the record ends early")?;
+            value |= u32::from((byte >> (self.at % 8)) & 1) << bit;
+            self.at += 1;
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,625 @@
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+use kurbo::{Affine, BezPath, Point, Rect, Shape, Triangle, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::layout::Layout;
+use crate::metrics::Metrics;
+use crate::track::{CellId, Spot, Track};
+
+/// Arrangement of the quadrants around the board center.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardShape {
+    /// Four quadrants at right angles.
+    Square,
+    /// Six quadrants sheared into 60° sectors, each track meeting the
+    /// next where a quadrant's track meets the next on the square board.
+    Hex6,
+}
+
+impl BoardShape {
+    pub const ALL: [BoardShape; 2] = [BoardShape::Square, BoardShape::Hex6];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BoardShape::Square => "square",
+            BoardShape::Hex6 => "hex6",
+        }
+    }
+
+    /// The shape seating `players` when none is asked for.
+    pub fn for_players(players: usize) -> Self {
+        if players.is_multiple_of(3) {
+            BoardShape::Hex6
+        } else {
+            BoardShape::Square
+        }
+    }
+
+    /// Number of quadrants around the board.
+    pub fn seats(self) -> usize {
+        match self {
+            BoardShape::Square => 4,
+            BoardShape::Hex6 => 6,
+        }
+    }
+
+    /// Player counts that can be spread evenly over the seats.
+    pub fn players(self) -> &'static [usize] {
+        match self {
+            BoardShape::Square => &[2, 4],
+            BoardShape::Hex6 => &[2, 3, 6],
+        }
+    }
+
+    /// Side of the square area covered by the board.
+    pub fn dimension(self, metrics: &Metrics) -> f64 {
+        match self {
+            BoardShape::Square => metrics.quadrant(),
+            // Wide enough for the bases at the tips of the sectors.
+            BoardShape::Hex6 => {
+                let base = metrics.base();
+                let corners = [
+                    Point::new(base.x0, base.y0),
+                    Point::new(base.x1, base.y0),
+                    Point::new(base.x0, base.y1),
+                    Point::new(base.x1, base.y1),
+                ];
+                let reach = Self::sectors(metrics)
+                    .into_iter()
+                    .flat_map(|sector| corners.map(|corner| sector * corner))
+                    .map(|corner| corner.x.abs().max(corner.y.abs()))
+                    .fold(0.0, f64::max);
+                reach * 2.0
+            }
+        }
+    }
+
+    /// Metrics making the board `side` pixels wide.
+    pub fn fit(self, side: f64) -> Metrics {
+        let unit = self.dimension(&Metrics::new(1.0));
+        Metrics::new(side / unit)
+    }
+
+    /// Transforms placing every quadrant, in seating order.
+    fn seat_affines(self, metrics: &Metrics) -> Vec<Affine> {
+        let dimension = metrics.quadrant();
+        if self == BoardShape::Square {
+            return vec![
+                Affine::IDENTITY,
+                Affine::rotate(PI / 2.0)
+                    .then_translate((dimension, 0.0).into()),
+                Affine::rotate(PI)
+                    .then_translate((dimension, dimension).into()),
+                Affine::rotate(PI * 3.0 / 2.0)
+                    .then_translate((0.0, dimension).into()),
+            ];
+        }
+        let center = self.dimension(metrics) / 2.0;
+        Self::sectors(metrics)
+            .into_iter()
+            .map(|sector| Affine::translate((center, center)) * sector)
+            .collect()
+    }
+
+    /// Transforms placing the quadrants of the hexagonal board around the
+    /// origin. A quadrant is sheared so its sides meet at 60°, turning the
+    /// quarter turn between quadrants of the square board into a sixth,
+    /// and moved so the edge its track enters by lies on the edge the
+    /// previous quadrant's track leaves by.
+    fn sectors(metrics: &Metrics) -> Vec<Affine> {
+        let (x, y) = (
+            Vec2::from_angle(PI / 12.0),
+            Vec2::from_angle(PI * 5.0 / 12.0),
+        );
+        let shear = Affine::new([x.x, x.y, y.x, y.y, 0.0, 0.0]);
+        let turn = Affine::rotate(PI / 3.0);
+        // The track enters six cells down the left edge and leaves as far
+        // along the top edge from the other corner.
+        let entry = shear * Point::new(0.0, metrics.dim * 6.0);
+        let exit =
+            shear * Point::new(metrics.quadrant() - metrics.dim * 6.0, 0.0);
+        // Solves `turn * (entry + offset) == exit + offset`, one minus a
+        // sixth of a turn being a sixth of a turn back.
+        let offset = (turn * (turn * entry - exit.to_vec2())).to_vec2();
+        (0..6)
+            .map(|seat| {
+                Affine::rotate(PI / 3.0 * seat as f64)
+                    * Affine::translate(offset)
+                    * shear
+            })
+            .collect()
+    }
+
+    /// Transforms placing every runway, in seating order. The runways of
+    /// the hexagonal board, which its shear would bend, leave the track
+    /// where the square board's do and run straight for its center,
+    /// stopping short of it where six arrows would not fit.
+    fn runway_affines(self, metrics: &Metrics) -> Vec<Affine> {
+        let seats = self.seat_affines(metrics);
+        if self == BoardShape::Square {
+            return seats;
+        }
+        let middle = metrics.quadrant() / 2.0;
+        let center = self.dimension(metrics) / 2.0;
+        let center = Point::new(center, center);
+        // Where the runway leaves the previous quadrant's track, a quarter
+        // turn back from where it does in its own, heading away from the
+        // cell it leaves.
+        let start = Vec2::new(metrics.dim_x2(), middle);
+        let (cell, end) = (
+            Point::new(middle, metrics.dim),
+            Point::new(middle, metrics.dim_x2()),
+        );
+        (0..seats.len())
+            .map(|seat| {
+                let previous = seats[(seat + seats.len() - 1) % seats.len()];
+                let entry = previous * end;
+                Affine::translate(entry.to_vec2())
+                    * Affine::rotate((center - previous * cell).atan2())
+                    * Affine::translate(-start)
+            })
+            .collect()
+    }
+}
+
+impl FromStr for BoardShape {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|shape| shape.name() == text)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(BoardShape::name);
+                format!(
+                    "unknown board shape {text:?}, expected one of {names:?}"
+                )
+            })
+    }
+}
+
+/// Layout of the whole board for a number of players, in board pixels of
+/// its metrics. It serializes without its track, which is derived again
+/// when loaded.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "BoardDef", into = "BoardDef")]
+pub struct Board {
+    shape: BoardShape,
+    players: usize,
+    layout: Layout,
+    metrics: Metrics,
+    track: Track,
+}
+
+impl Board {
+    /// Player counts seated by some board shape.
+    pub const PLAYERS: [usize; 4] = [2, 3, 4, 6];
+
+    /// A board with the classic track.
+    pub fn new(players: usize) -> Self {
+        let shape = BoardShape::for_players(players);
+        Self::with_shape(shape, players, Layout::classic(shape))
+    }
+
+    /// A board of the shape seating `players` by default.
+    pub fn with_layout(players: usize, layout: Layout) -> Self {
+        Self::with_shape(BoardShape::for_players(players), players, layout)
+    }
+
+    /// A board at the default cell size.
+    pub fn with_shape(
+        shape: BoardShape,
+        players: usize,
+        layout: Layout,
+    ) -> Self {
+        let mut board = Self {
+            shape,
+            players,
+            layout,
+            metrics: Metrics::default(),
+            track: Track::default(),
+        };
+        board.track = Track::new(&board);
+        board
+    }
+
+    /// The same board laid out at another cell size.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self.track = Track::new(&self);
+        self
+    }
+
+    pub fn shape(&self) -> BoardShape {
+        self.shape
+    }
+
+    pub fn players(&self) -> usize {
+        self.players
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Side of the square area covered by the board.
+    pub fn dimension(&self) -> f64 {
+        self.shape.dimension(&self.metrics)
+    }
+
+    /// Transforms placing every quadrant, in seating order.
+    pub fn seats(&self) -> Vec<Affine> {
+        self.shape.seat_affines(&self.metrics)
+    }
+
+    /// Transforms placing every runway, in seating order, in the local
+    /// coordinates of [`Player::runway`].
+    pub fn runways(&self) -> Vec<Affine> {
+        self.shape.runway_affines(&self.metrics)
+    }
+
+    /// Track cells of the quadrant in `seat`, in its local coordinates.
+    pub fn cells(&self, seat: usize) -> Vec<Cell> {
+        self.layout.cells(seat, &self.metrics)
+    }
+
+    /// Edges of every quadrant's track in its local coordinates.
+    pub fn edges(&self) -> Vec<BezPath> {
+        self.layout.edges(&self.metrics)
+    }
+
+    /// Walkable cells of the board.
+    pub fn track(&self) -> &Track {
+        &self.track
+    }
+
+    /// Center of track cell `index` in board pixels, where a UI places
+    /// plane tokens after applying its root transform.
+    pub fn cell_center(&self, index: CellId) -> Option<Point> {
+        self.track.get(index).map(|cell| cell.center)
+    }
+
+    /// Track cell whose center spot covers `point`, in board pixels.
+    pub fn cell_index(&self, point: Point) -> Option<CellId> {
+        let radius = self.metrics.cell_radius();
+        self.track
+            .cells()
+            .iter()
+            .position(|cell| cell.center.distance(point) <= radius)
+    }
+
+    /// Track cell whose shape contains `point`, in board pixels.
+    /// Runway spots cover the square of the runway around them.
+    pub fn hit_test(&self, point: Point) -> Option<CellId> {
+        for (seat, affine) in self.seats().into_iter().enumerate() {
+            let local = affine.inverse() * point;
+            let cells = self.cells(seat);
+            if let Some(index) = cells.iter().position(|c| c.contains(local)) {
+                return self.track.index(Spot::Ring { seat, index });
+            }
+        }
+        for (seat, affine) in self.runways().into_iter().enumerate() {
+            let local = affine.inverse() * point;
+            let size = (self.metrics.dim, self.metrics.dim);
+            let runway = Player::runway_spots(&self.metrics);
+            if let Some(step) = runway.into_iter().position(|center| {
+                Rect::from_center_size(center, size).contains(local)
+            }) {
+                return self.track.index(Spot::Runway { seat, step });
+            }
+        }
+        None
+    }
+
+    /// Whether `seat` hosts a player. Players are spread evenly, so two
+    /// players sit in opposite quadrants.
+    pub fn is_seated(&self, seat: usize) -> bool {
+        let step = self.shape.seats() / self.players.max(1);
+        seat.is_multiple_of(step.max(1))
+    }
+}
+
+/// Serialized form of a [`Board`].
+#[derive(Serialize, Deserialize)]
+struct BoardDef {
+    shape: BoardShape,
+    players: usize,
+    /// The classic layout of the shape when missing.
+    layout: Option<Layout>,
+    #[serde(default)]
+    metrics: Metrics,
+}
+
+impl TryFrom<BoardDef> for Board {
+    type Error = String;
+
+    fn try_from(def: BoardDef) -> Result<Self, Self::Error> {
+        if !def.shape.players().contains(&def.players) {
+            return Err(format!(
+                "a {} board seats {:?} players, not {}",
+                def.shape.name(),
+                def.shape.players(),
+                def.players
+            ));
+        }
+        let layout = def.layout.unwrap_or_else(|| Layout::classic(def.shape));
+        Ok(Board::with_shape(def.shape, def.players, layout)
+            .with_metrics(def.metrics))
+    }
+}
+
+impl From<Board> for BoardDef {
+    fn from(board: Board) -> Self {
+        BoardDef {
+            shape: board.shape,
+            players: board.players,
+            layout: Some(board.layout),
+            metrics: board.metrics,
+        }
+    }
+}
+
+/// The quadrant of the board owned by the player in `seat`, in its local
+/// coordinates before the seat transform.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Player {
+    pub seat: usize,
+}
+
+impl Player {
+    pub const COLOR_NAMES: [&str; 6] =
+        ["red", "yellow", "blue", "green", "purple", "orange"];
+
+    /// Spots along the runway leading home.
+    pub const RUNWAY_SPOTS: usize = 6;
+
+    pub const fn new(seat: usize) -> Self {
+        Player { seat }
+    }
+
+    /// Centers of the four hangar spots on the home base.
+    pub fn hangars(metrics: &Metrics) -> [Point; 4] {
+        let (dim, dim_x2) = (metrics.dim, metrics.dim_x2());
+        let p = Point::new(dim, dim);
+        [
+            p,
+            p + (0.0, dim_x2),
+            p + (dim_x2, 0.0),
+            p + (dim_x2, dim_x2),
+        ]
+    }
+
+    /// Arrow shaped runway leading from the track to the board center.
+    pub fn runway(metrics: &Metrics) -> BezPath {
+        let dim = metrics.dim;
+        let mut path = BezPath::new();
+        let mut p = Point::new(metrics.dim_x2(), metrics.dim_x4() * 2.0);
+        path.move_to(p);
+        p += (dim * 5.0, 0.0);
+        path.line_to(p);
+        p -= (0.0, dim);
+        path.line_to(p);
+        path.line_to(p + (dim * 1.5, dim * 1.5));
+        p += (0.0, dim * 3.0);
+        path.line_to(p);
+        p -= (0.0, dim);
+        path.line_to(p);
+        p -= (dim * 5.0, 0.0);
+        path.line_to(p);
+        path.close_path();
+        path
+    }
+
+    /// Centers of the spots along the runway, outermost first.
+    pub fn runway_spots(metrics: &Metrics) -> [Point; Self::RUNWAY_SPOTS] {
+        let dim = metrics.dim;
+        let p = Point::new(dim * 2.5, dim * 8.5);
+        std::array::from_fn(|i| p + (dim * i as f64, 0.0))
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CellKind {
+    Triangle0,
+    Triangle90,
+    Triangle180,
+    Triangle270,
+    VBlock,
+    HBlock,
+}
+
+impl CellKind {
+    pub const ALL: [CellKind; 6] = [
+        CellKind::Triangle0,
+        CellKind::Triangle90,
+        CellKind::Triangle180,
+        CellKind::Triangle270,
+        CellKind::VBlock,
+        CellKind::HBlock,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CellKind::Triangle0 => "triangle0",
+            CellKind::Triangle90 => "triangle90",
+            CellKind::Triangle180 => "triangle180",
+            CellKind::Triangle270 => "triangle270",
+            CellKind::VBlock => "vblock",
+            CellKind::HBlock => "hblock",
+        }
+    }
+}
+
+/// A track cell in its quadrant's local coordinates.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Cell {
+    pub kind: CellKind,
+    /// Palette index, wrapping around the player colors.
+    pub color: usize,
+    pub origin: Point,
+    /// Side of the square the cell's shape is built from.
+    pub dim: f64,
+}
+
+impl Cell {
+    /// A cell at the default size.
+    pub fn new(kind: CellKind, color: usize, origin: Point) -> Cell {
+        Self {
+            kind,
+            color,
+            origin,
+            dim: Metrics::DEFAULT_DIM,
+        }
+    }
+
+    pub fn triangle(&self) -> Option<Triangle> {
+        let (origin, side) = (self.origin, self.dim * 2.0);
+        match self.kind {
+            CellKind::Triangle0 => Triangle::new(
+                origin,
+                origin + (side, 0.0),
+                origin + (0.0, side),
+            )
+            .into(),
+            CellKind::Triangle90 => Triangle::new(
+                origin,
+                origin + (side, 0.0),
+                origin + (side, side),
+            )
+            .into(),
+            CellKind::Triangle180 => Triangle::new(
+                origin,
+                origin + (0.0, side),
+                origin + (-side, side),
+            )
+            .into(),
+            CellKind::Triangle270 => Triangle::new(
+                origin,
+                origin + (side, side),
+                origin + (0.0, side),
+            )
+            .into(),
+            _ => None,
+        }
+    }
+
+    pub fn block(&self) -> Option<Rect> {
+        let (origin, dim) = (self.origin, self.dim);
+        match self.kind {
+            CellKind::VBlock => {
+                Rect::from_origin_size(origin, (dim, dim * 2.0)).into()
+            }
+            CellKind::HBlock => {
+                Rect::from_origin_size(origin, (dim * 2.0, dim)).into()
+            }
+            _ => None,
+        }
+    }
+
+    /// Radius of the accent spot in the middle of the cell.
+    pub fn radius(&self) -> f64 {
+        Metrics::new(self.dim).cell_radius()
+    }
+
+    /// Bounding box of the cell.
+    pub fn bounds(&self) -> Rect {
+        self.triangle()
+            .map(|shape| shape.bounding_box())
+            .or_else(|| self.block())
+            .unwrap_or_default()
+    }
+
+    /// Corners of the cell's shape in order around it.
+    pub fn vertices(&self) -> Vec<Point> {
+        if let Some(shape) = self.triangle() {
+            return vec![shape.a, shape.b, shape.c];
+        }
+        self.block()
+            .map(|shape| {
+                vec![
+                    shape.origin(),
+                    Point::new(shape.x1, shape.y0),
+                    Point::new(shape.x1, shape.y1),
+                    Point::new(shape.x0, shape.y1),
+                ]
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether `point` lies inside the cell's shape.
+    pub fn contains(&self, point: Point) -> bool {
+        self.triangle().is_some_and(|shape| shape.contains(point))
+            || self.block().is_some_and(|shape| shape.contains(point))
+    }
+
+    /// Center of the accent spot: the incircle center of triangles and the
+    /// middle of blocks.
+    pub fn center(&self) -> Point {
+        match (self.triangle(), self.block()) {
+            (Some(shape), _) => shape.inscribed_circle().center,
+            (_, Some(shape)) => shape.center(),
+            _ => Point::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_hit_at_their_centers() {
+        for board in [Board::new(4), Board::new(6)] {
+            let track = board.track();
+            let seats = board.seats();
+            for (seat, affine) in seats.iter().enumerate() {
+                for (index, cell) in board.cells(seat).iter().enumerate() {
+                    let spot = Spot::Ring { seat, index };
+                    let hit = board.hit_test(*affine * cell.center());
+                    assert_eq!(hit, track.index(spot), "{spot:?}");
+                }
+            }
+            let runways = board.runways();
+            for (seat, affine) in runways.iter().enumerate() {
+                let spots = Player::runway_spots(board.metrics());
+                for (step, center) in spots.into_iter().enumerate() {
+                    let spot = Spot::Runway { seat, step };
+                    let hit = board.hit_test(*affine * center);
+                    assert_eq!(hit, track.index(spot), "{spot:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn points_off_the_cells_miss() {
+        for board in [Board::new(4), Board::new(6)] {
+            let metrics = *board.metrics();
+            for (seat, affine) in board.seats().into_iter().enumerate() {
+                // Just past the outer edge of the blocks along the top of
+                // the quadrant, and on the home base.
+                let cells = board.cells(seat);
+                let top = cells.iter().filter(|cell| {
+                    matches!(cell.kind, CellKind::VBlock)
+                        && cell.origin.y == 0.0
+                });
+                for cell in top {
+                    let outside = Point::new(cell.center().x, -1.0);
+                    assert_eq!(board.hit_test(affine * outside), None);
+                    let inside = Point::new(cell.center().x, 1.0);
+                    assert!(board.hit_test(affine * inside).is_some());
+                }
+                let base = metrics.base().center();
+                assert_eq!(board.hit_test(affine * base), None);
+            }
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::VecDeque;
+use std::io::BufReader;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+
+use crate::game::Game;
+use crate::protocol::{
+    self, ChatLine, ClientRequest, RoomInfo, ServerEvent, ServerMessage,
+    ServerResponse, VERSION,
+};
+use crate::record::GameRecord;
+use crate::rules::{GameState, Move, RuleSet};
+
+/// A connection to the lobby of a [`Server`](crate::server::Server),
+/// following the rooms and, once it sits in one or watches it, its game as
+/// the server tells it how they go. Messages are read on a thread of their
+/// own, so the client can be polled without waiting, from a frame loop say.
+pub struct Client {
+    stream: TcpStream,
+    incoming: Receiver<Result<ServerMessage>>,
+    rooms: Vec<RoomInfo>,
+    table: Option<Table>,
+    seat: Option<Seat>,
+    /// Sequence number of the next roll or move asked for.
+    seq: u64,
+}
+
+/// The room a client sits in or watches.
+///
+/// Moves the client plays are predicted, [`view`](Table::view) showing
+/// them before the server answers. Once it does, what it played is held
+/// against the prediction: the predictions after a refused or different
+/// move are taken back and the game shown is the server's again.
+pub struct Table {
+    pub room: u64,
+    /// The game as the server played it so far.
+    pub game: Game,
+    /// Seat and name of every player seated.
+    pub players: Vec<(usize, String)>,
+    /// Connections watching the game.
+    pub spectators: usize,
+    /// What was said in the room since the client came in, oldest first.
+    pub chat: Vec<ChatLine>,
+    /// Times predictions were taken back.
+    pub rollbacks: u32,
+    /// Rolls and moves asked for and not answered yet, oldest first, by
+    /// sequence number. Throws are not known until the server makes them.
+    pending: VecDeque<(u64, Option<Move>)>,
+    /// The game with the pending moves played, none without any.
+    predicted: Option<Game>,
+}
+
+/// A seat taken at the table.
+pub struct Seat {
+    pub seat: usize,
+    /// Secret to resume the seat with, from another connection say.
+    pub token: String,
+}
+
+impl Client {
+    /// Connects to the server at `address` and enters its lobby as
+    /// `name`.
+    pub fn connect(address: impl ToSocketAddrs, name: &str) -> Result<Self> {
+        let stream =
+            TcpStream::connect(address).context("failed to connect")?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            loop {
+                let message = match protocol::read_frame(&mut reader) {
+                    Ok(Some(message)) => Ok(message),
+                    Ok(None) => return,
+                    Err(error) => Err(error),
+                };
+                let failed = message.is_err();
+                if sender.send(message).is_err() || failed {
+                    return;
+                }
+            }
+        });
+        let mut client = Self {
+            stream,
+            incoming,
+            rooms: vec![],
+            table: None,
+            seat: None,
+            seq: 0,
+        };
+        client.send(&ClientRequest::Hello {
+            version: VERSION,
+            name: name.into(),
+        })?;
+        match client.wait()? {
+            ServerMessage::Response(ServerResponse::Hello {
+                version, ..
+            }) => {
+                ensure!(
+                    version == VERSION,
+                    "the server speaks protocol {version}, not {VERSION}"
+                );
+                Ok(client)
+            }
+            ServerMessage::Response(ServerResponse::Rejected {
+                reason,
+                ..
+            }) => {
+                bail!("{reason}")
+            }
+            _ => bail!("the server did not answer the hello"),
+        }
+    }
+
+    /// The rooms of the lobby, as of the last time the server told.
+    pub fn rooms(&self) -> &[RoomInfo] {
+        &self.rooms
+    }
+
+    /// The room sat in or watched, once the server let the client in.
+    pub fn table(&self) -> Option<&Table> {
+        self.table.as_ref()
+    }
+
+    /// The seat taken, once the server welcomed the client to a room.
+    pub fn seat(&self) -> Option<&Seat> {
+        self.seat.as_ref()
+    }
+
+    pub fn list_rooms(&mut self) -> Result<()> {
+        self.send(&ClientRequest::ListRooms)
+    }
+
+    /// Asks for a room of `players` under `rules`, taking its first seat.
+    pub fn create_room(
+        &mut self,
+        name: &str,
+        players: usize,
+        rules: RuleSet,
+        password: Option<&str>,
+    ) -> Result<()> {
+        self.send(&ClientRequest::CreateRoom {
+            name: name.into(),
+            players,
+            rules,
+            password: password.map(String::from),
+        })
+    }
+
+    pub fn join_room(
+        &mut self,
+        room: u64,
+        password: Option<&str>,
+    ) -> Result<()> {
+        self.send(&ClientRequest::JoinRoom {
+            room,
+            password: password.map(String::from),
+        })
+    }
+
+    /// Asks for the seat `token` was given for back, after dropping out
+    /// of its game.
+    pub fn resume(&mut self, token: &str) -> Result<()> {
+        self.send(&ClientRequest::Resume {
+            token: token.into(),
+        })
+    }
+
+    /// Asks to follow the game of room `room` without playing it.
+    pub fn watch(&mut self, room: u64, password: Option<&str>) -> Result<()> {
+        self.send(&ClientRequest::Watch {
+            room,
+            password: password.map(String::from),
+        })
+    }
+
+    pub fn leave_room(&mut self) -> Result<()> {
+        self.table = None;
+        self.seat = None;
+        self.send(&ClientRequest::LeaveRoom)
+    }
+
+    /// Says `text` to everyone in the room. The server refuses those
+    /// chatting too fast.
+    pub fn chat(&mut self, text: &str) -> Result<()> {
+        self.send(&ClientRequest::Chat { text: text.into() })
+    }
+
+    /// Asks the server to throw the dice for the seat.
+    pub fn roll(&mut self) -> Result<()> {
+        let seq = self.predict(None);
+        self.send(&ClientRequest::Roll { seq: Some(seq) })
+    }
+
+    /// Asks the server to play `mv` for the seat, playing it in the
+    /// [`view`](Table::view) of the table until the server answers.
+    pub fn play(&mut self, mv: Move) -> Result<()> {
+        let seq = self.predict(Some(mv));
+        self.send(&ClientRequest::Play { mv, seq: Some(seq) })
+    }
+
+    /// Numbers the next roll or move asked for, predicting `mv` at the
+    /// table if there is one.
+    fn predict(&mut self, mv: Option<Move>) -> u64 {
+        let seq = self.seq;
+        self.seq += 1;
+        if let Some(table) = &mut self.table {
+            table.pending.push_back((seq, mv));
+            table.replay();
+        }
+        seq
+    }
+
+    /// Waits for the next message of the server, following the rooms and
+    /// the game with it. Fails once the server hangs up.
+    pub fn wait(&mut self) -> Result<ServerMessage> {
+        let message = self
+            .incoming
+            .recv()
+            .map_err(|_| anyhow!("the server hung up"))??;
+        self.follow(&message)?;
+        Ok(message)
+    }
+
+    /// The next message of the server if one came, followed like with
+    /// [`wait`](Self::wait).
+    pub fn poll(&mut self) -> Result<Option<ServerMessage>> {
+        let message = match self.incoming.try_recv() {
+            Ok(message) => message?,
+            Err(TryRecvError::Empty) => return Ok(None),
+            Err(TryRecvError::Disconnected) => bail!("the server hung up"),
+        };
+        self.follow(&message)?;
+        Ok(Some(message))
+    }
+
+    fn send(&mut self, request: &ClientRequest) -> Result<()> {
+        protocol::write_frame(&mut self.stream, request)
+    }
+
+    /// Keeps the rooms, the game and the players in step with `message`.
+    fn follow(&mut self, message: &ServerMessage) -> Result<()> {
+        let event = match message {
+            ServerMessage::Response(
+                ServerResponse::Hello { rooms, .. }
+                | ServerResponse::Rooms { rooms },
+            )
+            | ServerMessage::Event(ServerEvent::Lobby { rooms }) => {
+                self.rooms = rooms.clone();
+                return Ok(());
+            }
+            ServerMessage::Response(ServerResponse::Welcome {
+                room,
+                seat,
+                token,
+                board,
+                rules,
+                players,
+                spectators,
+            }) => {
+                let game = Game::new(board.as_ref().clone(), *rules, None);
+                let table = Table::new(*room, game, players, *spectators);
+                self.table = Some(table);
+                self.seat = Some(Seat {
+                    seat: *seat,
+                    token: token.clone(),
+                });
+                return Ok(());
+            }
+            ServerMessage::Response(ServerResponse::Resumed {
+                room,
+                seat,
+                token,
+                record,
+                state,
+                players,
+                spectators,
+            }) => {
+                let game = resumed(record, state)?;
+                let table = Table::new(*room, game, players, *spectators);
+                self.table = Some(table);
+                self.seat = Some(Seat {
+                    seat: *seat,
+                    token: token.clone(),
+                });
+                return Ok(());
+            }
+            ServerMessage::Response(ServerResponse::Watching {
+                room,
+                record,
+                state,
+                players,
+                spectators,
+            }) => {
+                let game = resumed(record, state)?;
+                let table = Table::new(*room, game, players, *spectators);
+                self.table = Some(table);
+                self.seat = None;
+                return Ok(());
+            }
+            ServerMessage::Response(ServerResponse::Rejected {
+                seq: Some(seq),
+                ..
+            }) => {
+                if let Some(table) = &mut self.table {
+                    table.settle(*seq, None);
+                }
+                return Ok(());
+            }
+            ServerMessage::Response(ServerResponse::Rejected { .. }) => {
+                return Ok(());
+            }
+            ServerMessage::Event(event) => event,
+        };
+        match (event, &mut self.table) {
+            (ServerEvent::Joined { seat, name }, Some(table)) => {
+                table.players.push((*seat, name.clone()));
+                table.players.sort();
+            }
+            (ServerEvent::Left { seat }, Some(table)) => {
+                table.players.retain(|(other, _)| other != seat);
+            }
+            (&ServerEvent::Spectators { count }, Some(table)) => {
+                table.spectators = count;
+            }
+            (ServerEvent::Chat(line), Some(table)) => {
+                table.chat.push(line.clone());
+            }
+            (
+                &ServerEvent::Played {
+                    seat,
+                    mv,
+                    checksum,
+                    seq,
+                },
+                Some(table),
+            ) => {
+                let game = &mut table.game;
+                game.play(mv).context("out of step with the server")?;
+                ensure!(
+                    game.state().checksum() == checksum,
+                    "out of step with the server"
+                );
+                let ours = self.seat.as_ref().is_some_and(|s| s.seat == seat);
+                match seq {
+                    Some(seq) if ours => table.settle(seq, Some(mv)),
+                    _ => table.replay(),
+                }
+            }
+            (ServerEvent::Closed { .. }, _) => {
+                self.table = None;
+                self.seat = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl Table {
+    fn new(
+        room: u64,
+        game: Game,
+        players: &[(usize, String)],
+        spectators: usize,
+    ) -> Self {
+        Self {
+            room,
+            game,
+            players: players.to_vec(),
+            spectators,
+            chat: vec![],
+            rollbacks: 0,
+            pending: VecDeque::new(),
+            predicted: None,
+        }
+    }
+
+    /// The game as the player sees it: the server's, with the moves asked
+    /// for and not answered yet played on top.
+    pub fn view(&self) -> &Game {
+        self.predicted.as_ref().unwrap_or(&self.game)
+    }
+
+    /// Whether rolls or moves asked for wait for the server to answer.
+    pub fn waiting(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Answers the request numbered `seq` with `played`, none if it was
+    /// refused. Predictions from a refused or different move on are taken
+    /// back, those before it were answered already.
+    fn settle(&mut self, seq: u64, played: Option<Move>) {
+        while self.pending.front().is_some_and(|&(next, _)| next < seq) {
+            self.pending.pop_front();
+        }
+        match self.pending.pop_front() {
+            Some((next, predicted)) if next == seq => {
+                let held = match (predicted, played) {
+                    // Throws are never predicted.
+                    (None, Some(Move::Roll(_))) => true,
+                    (Some(predicted), Some(played)) => predicted == played,
+                    _ => false,
+                };
+                if !held {
+                    self.take_back();
+                }
+            }
+            Some(later) => self.pending.push_front(later),
+            None => {}
+        }
+        self.replay();
+    }
+
+    /// Plays the pending moves again on top of the server's game, taking
+    /// them back if one no longer applies.
+    fn replay(&mut self) {
+        if self.pending.is_empty() {
+            self.predicted = None;
+            return;
+        }
+        let mut predicted = self.game.clone();
+        for &(_, mv) in &self.pending {
+            // Nothing after a throw can be known before the throw.
+            let Some(mv) = mv else {
+                break;
+            };
+            if predicted.play(mv).is_err() {
+                self.take_back();
+                return;
+            }
+        }
+        self.predicted = Some(predicted);
+    }
+
+    fn take_back(&mut self) {
+        if !self.pending.is_empty() || self.predicted.is_some() {
+            self.rollbacks += 1;
+        }
+        self.pending.clear();
+        self.predicted = None;
+    }
+}
+
+/// The game `record` leads to, checked against the `state` the server
+/// says it is in.
+fn resumed(record: &GameRecord, state: &GameState) -> Result<Game> {
+    let game = Game::from_record(record.clone())
+        .context("the server sent an illegal game")?;
+    ensure!(
+        game.state().checksum() == state.checksum(),
+        "out of step with the server"
+    );
+    Ok(game)
+}
@@ -0,0 +1,150 @@
+use std::ops::RangeInclusive;
+
+use anyhow::{Result, ensure};
+use rand::rngs::StdRng;
+use rand::{Rng, RngExt, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// How many dice are thrown at once and how many faces each has. Specs
+/// read from rules, saves and records are checked like [`DiceSpec::new`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "SpecDef")]
+pub struct DiceSpec {
+    pub count: u8,
+    pub faces: u8,
+}
+
+/// Serialized form of a [`DiceSpec`], before it is checked.
+#[derive(Deserialize)]
+struct SpecDef {
+    count: u8,
+    faces: u8,
+}
+
+impl TryFrom<SpecDef> for DiceSpec {
+    type Error = anyhow::Error;
+
+    fn try_from(def: SpecDef) -> Result<Self> {
+        Self::new(def.count, def.faces)
+    }
+}
+
+impl DiceSpec {
+    /// A single six-sided die.
+    pub const CLASSIC: DiceSpec = DiceSpec { count: 1, faces: 6 };
+
+    /// `count` dice of `faces` faces, refused unless at least one die of
+    /// at least two faces is thrown and the totals fit a byte.
+    pub fn new(count: u8, faces: u8) -> Result<Self> {
+        ensure!(count > 0, "throws need at least one die");
+        ensure!(faces > 1, "dice need at least two faces");
+        ensure!(
+            count.checked_mul(faces).is_some(),
+            "{count} dice of {faces} faces total more than {}",
+            u8::MAX
+        );
+        Ok(Self { count, faces })
+    }
+
+    /// Totals a throw can show.
+    pub fn totals(&self) -> RangeInclusive<u8> {
+        self.count..=self.count.saturating_mul(self.faces)
+    }
+
+    /// Whether `total` is the highest throw, a six on the classic die,
+    /// which launches planes.
+    pub fn is_top(&self, total: u8) -> bool {
+        total == *self.totals().end()
+    }
+
+    /// Every total a throw can show with its chance, lowest first.
+    pub fn odds(&self) -> Vec<(u8, f64)> {
+        // Ways to reach each sum with the dice thrown so far.
+        let mut ways = vec![1.0];
+        for _ in 0..self.count {
+            let mut next = vec![0.0; ways.len() + usize::from(self.faces)];
+            for (sum, &count) in ways.iter().enumerate() {
+                for face in 1..=usize::from(self.faces) {
+                    next[sum + face] += count;
+                }
+            }
+            ways = next;
+        }
+        let throws = f64::from(self.faces).powi(i32::from(self.count));
+        self.totals()
+            .map(|total| (total, ways[usize::from(total)] / throws))
+            .collect()
+    }
+
+    /// Whether a throw of `total` earns its player another throw after
+    /// they move.
+    pub fn extra_turn(&self, total: u8) -> bool {
+        self.is_top(total)
+    }
+}
+
+impl Default for DiceSpec {
+    fn default() -> Self {
+        Self::CLASSIC
+    }
+}
+
+/// The faces shown by one throw of the dice.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Throw {
+    pub values: Vec<u8>,
+}
+
+impl Throw {
+    pub fn total(&self) -> u8 {
+        self.values.iter().sum()
+    }
+}
+
+/// Dice drawing from an injected random number generator, so a game is
+/// reproduced by seeding it the same way.
+pub struct Dice<R> {
+    spec: DiceSpec,
+    rng: R,
+}
+
+impl<R: Rng> Dice<R> {
+    pub fn new(spec: DiceSpec, rng: R) -> Result<Self> {
+        // Specs built field by field have not been checked yet.
+        let spec = DiceSpec::new(spec.count, spec.faces)?;
+        Ok(Self { spec, rng })
+    }
+
+    pub fn spec(&self) -> DiceSpec {
+        self.spec
+    }
+
+    pub fn roll(&mut self) -> Throw {
+        let values = (0..self.spec.count)
+            .map(|_| self.rng.random_range(1..=self.spec.faces))
+            .collect();
+        Throw { values }
+    }
+}
+
+impl Dice<StdRng> {
+    /// Dice replaying the same throws for the same seed.
+    pub fn seeded(spec: DiceSpec, seed: u64) -> Result<Self> {
+        Self::new(spec, StdRng::seed_from_u64(seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::RuleSet;
+
+    #[test]
+    fn rules_with_unplayable_dice_are_refused() {
+        for dice in ["count = 16\nfaces = 16", "count = 0\nfaces = 6"] {
+            let text = format!("[dice]\n{dice}\n");
+            assert!(RuleSet::parse(&text).is_err(), "{dice}");
+        }
+        let text = "[dice]\ncount = 2\nfaces = 6\n";
+        assert_eq!(RuleSet::parse(text).unwrap().dice.totals(), 2..=12);
+    }
+}
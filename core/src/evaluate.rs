@@ -0,0 +1,72 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::Serialize;
+
+use crate::mcts;
+use crate::rules::GameState;
+
+/// Playouts [`evaluate`] plays, a fifth of a second or so of them.
+pub const ROLLOUTS: u32 = 500;
+
+/// Who is ahead in a position, as the share of random playouts from it
+/// each seated player won.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct PlayerScores {
+    /// Seats of the seated players, in seat order.
+    pub seats: Vec<usize>,
+    /// Estimated chance each seated player wins, in seat order, adding up
+    /// to one.
+    pub wins: Vec<f64>,
+    pub rollouts: u32,
+}
+
+impl PlayerScores {
+    /// Estimated chance the player in `seat` wins, if seated.
+    pub fn win(&self, seat: usize) -> Option<f64> {
+        let side = self.seats.iter().position(|&s| s == seat)?;
+        Some(self.wins[side])
+    }
+
+    /// Seat of the player most likely to win.
+    pub fn leader(&self) -> usize {
+        let side = (0..self.wins.len())
+            .max_by(|&a, &b| self.wins[a].total_cmp(&self.wins[b]));
+        self.seats[side.expect("games seat players")]
+    }
+}
+
+/// Estimates the chance each player of `state` wins from [`ROLLOUTS`]
+/// random playouts. The playouts are seeded from the position, so the
+/// same position always scores the same.
+pub fn evaluate(state: &GameState) -> PlayerScores {
+    evaluate_with(state, ROLLOUTS, state.checksum())
+}
+
+/// Estimates the chance each player of `state` wins from `rollouts`
+/// random playouts seeded with `seed`. A finished game scores one for its
+/// winner.
+pub fn evaluate_with(
+    state: &GameState,
+    rollouts: u32,
+    seed: u64,
+) -> PlayerScores {
+    let seats: Vec<usize> =
+        state.sides().iter().map(|side| side.seat).collect();
+    let rollouts = rollouts.max(1);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut wins = vec![0.0; seats.len()];
+    for _ in 0..rollouts {
+        let won = mcts::playout(state.clone(), &mut rng);
+        for (total, win) in wins.iter_mut().zip(won) {
+            *total += win;
+        }
+    }
+    for win in &mut wins {
+        *win /= f64::from(rollouts);
+    }
+    PlayerScores {
+        seats,
+        wins,
+        rollouts,
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::ai::AiPlayer;
+use crate::heuristic::HeuristicPlayer;
+use crate::rules::{GameState, Move, Phase};
+
+/// Looks a few throws ahead, weighing every total by its chance and
+/// scoring the positions it stops at with the heuristic. Opponents are
+/// assumed to play against it, which keeps the search to one value per
+/// position however many players are seated.
+#[derive(Clone, Debug)]
+pub struct ExpectimaxPlayer {
+    pub heuristic: HeuristicPlayer,
+    /// Throws looked ahead past the one being used.
+    pub depth: u32,
+    /// Moves searched after each throw further down, those the heuristic
+    /// likes best. Every move of the throw being used is.
+    pub breadth: usize,
+    /// Values of the positions searched for the current choice, by
+    /// checksum and depth left.
+    cache: HashMap<(u64, u32), f64>,
+}
+
+impl Default for ExpectimaxPlayer {
+    fn default() -> Self {
+        Self::new(HeuristicPlayer::default(), 2, 3)
+    }
+}
+
+impl ExpectimaxPlayer {
+    pub fn new(heuristic: HeuristicPlayer, depth: u32, breadth: usize) -> Self {
+        Self {
+            heuristic,
+            depth,
+            breadth: breadth.max(1),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// The `breadth` legal moves of `state` the heuristic likes best, with
+    /// the states they lead to, best first.
+    fn ordered(
+        &self,
+        state: &GameState,
+        breadth: usize,
+    ) -> Vec<(Move, GameState)> {
+        let mut moves: Vec<(f64, Move, GameState)> = state
+            .legal_moves()
+            .into_iter()
+            .filter_map(|mv| {
+                let next = state.apply(mv).ok()?;
+                Some((self.heuristic.score(state, mv), mv, next))
+            })
+            .collect();
+        moves.sort_by(|a, b| b.0.total_cmp(&a.0));
+        moves.truncate(breadth);
+        moves.into_iter().map(|(_, mv, next)| (mv, next)).collect()
+    }
+
+    /// Value of `state` for the player in `seat`, with `depth` more throws
+    /// to look at.
+    fn value(&mut self, state: &GameState, depth: u32, seat: usize) -> f64 {
+        let leaf = match state.phase() {
+            Phase::Roll => depth == 0,
+            Phase::Move => false,
+            Phase::Finished => true,
+        };
+        if leaf {
+            return self.heuristic.evaluate(state, seat);
+        }
+        let key = (state.checksum(), depth);
+        if let Some(&value) = self.cache.get(&key) {
+            return value;
+        }
+        let value = if state.phase() == Phase::Roll {
+            let mut value = 0.0;
+            for (total, chance) in state.rules().dice.odds() {
+                if let Ok(next) = state.apply(Move::Roll(total)) {
+                    value += chance * self.value(&next, depth - 1, seat);
+                }
+            }
+            value
+        } else {
+            let maximize = state.turn().seat == seat;
+            let mut best = None;
+            for (_, next) in self.ordered(state, self.breadth) {
+                let value = self.value(&next, depth, seat);
+                if best.is_none_or(|best| (value > best) == maximize) {
+                    best = Some(value);
+                }
+            }
+            best.unwrap_or_else(|| self.heuristic.evaluate(state, seat))
+        };
+        self.cache.insert(key, value);
+        value
+    }
+}
+
+impl AiPlayer for ExpectimaxPlayer {
+    fn choose_move(
+        &mut self,
+        state: &GameState,
+        legal: &[Move],
+        _budget: Duration,
+    ) -> Move {
+        if let [mv] = legal[..] {
+            return mv;
+        }
+        let seat = state.turn().seat;
+        let moves = self.ordered(state, usize::MAX);
+        let moves = moves.into_iter().filter(|(mv, _)| legal.contains(mv));
+        self.cache.clear();
+        let mut best = None;
+        for (mv, next) in moves {
+            let value = self.value(&next, self.depth, seat);
+            if best.is_none_or(|(_, top)| value > top) {
+                best = Some((mv, value));
+            }
+        }
+        self.cache.clear();
+        best.expect("a throw always leaves a move").0
+    }
+}
@@ -0,0 +1,324 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail, ensure};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::AiPlayer;
+use crate::board::Board;
+use crate::dice::Dice;
+use crate::observe::{GameEvent, Observer};
+use crate::record::{GameRecord, Ply};
+use crate::rules::{GameState, Move, Phase, RuleSet};
+
+/// A game being played, keeping the record of every move next to the
+/// state they led to. Moves are taken back and played again a ply at a
+/// time, a throw together with what its player did with it.
+pub struct Game {
+    record: GameRecord,
+    state: GameState,
+    /// State before each ply of the record.
+    history: Vec<GameState>,
+    /// Plies taken back, the latest last.
+    undone: Vec<Ply>,
+    observers: Vec<Box<dyn Observer>>,
+}
+
+/// Copies the game without its observers, which keep following the
+/// original.
+impl Clone for Game {
+    fn clone(&self) -> Self {
+        Self {
+            record: self.record.clone(),
+            state: self.state.clone(),
+            history: self.history.clone(),
+            undone: self.undone.clone(),
+            observers: vec![],
+        }
+    }
+}
+
+/// The version of a save, read before the rest to tell which format
+/// follows.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// A saved game. Keys this version does not know are skipped, in the
+/// record and everything in it too, so saves gaining fields stay loadable
+/// as long as the version is the same.
+#[derive(Serialize, Deserialize)]
+struct Save {
+    version: u32,
+    record: GameRecord,
+}
+
+impl Game {
+    /// Version of the save format written by [`Game::save`].
+    pub const VERSION: u32 = 1;
+
+    pub fn new(board: Board, rules: RuleSet, seed: Option<u64>) -> Self {
+        let state = GameState::new(&board).with_rules(rules);
+        let record = GameRecord {
+            board,
+            rules,
+            seed,
+            plies: vec![],
+            checksums: vec![],
+            resignations: vec![],
+        };
+        Self {
+            record,
+            state,
+            history: vec![],
+            undone: vec![],
+            observers: vec![],
+        }
+    }
+
+    /// The game a record leads to, checked move by move.
+    pub fn from_record(record: GameRecord) -> Result<Self> {
+        record.replay()?;
+        let mut game =
+            Self::new(record.board.clone(), record.rules, record.seed);
+        for seat in record.resigning(0) {
+            game.resign(seat)?;
+        }
+        for (index, mv) in record.moves().into_iter().enumerate() {
+            game.play(mv)?;
+            for seat in record.resigning(index + 1) {
+                game.resign(seat)?;
+            }
+        }
+        Ok(game)
+    }
+
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Registers `observer` to be told of every move played from now on,
+    /// including plies played again by [`redo`](Self::redo).
+    pub fn observe(&mut self, observer: impl Observer + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Plays `mv` if it is legal, noting it in the record. Plies taken
+    /// back can no longer be played again.
+    pub fn play(&mut self, mv: Move) -> Result<()> {
+        self.advance(mv)?;
+        self.undone.clear();
+        Ok(())
+    }
+
+    fn advance(&mut self, mv: Move) -> Result<()> {
+        let next = self.state.apply(mv)?;
+        if let Move::Roll(_) = mv {
+            self.history.push(self.state.clone());
+        }
+        self.record.push(&self.state, mv, &next);
+        if !self.observers.is_empty() {
+            for event in GameEvent::between(&self.state, mv, &next) {
+                for observer in &mut self.observers {
+                    observer.notify(&event, &next);
+                }
+            }
+        }
+        self.state = next;
+        Ok(())
+    }
+
+    /// Lets the player in `seat` resign, noting it in the record. Neither
+    /// the resignation nor any ply before it can be taken back.
+    pub fn resign(&mut self, seat: usize) -> Result<()> {
+        let next = self.state.resign(seat)?;
+        self.record.resign(seat, &next);
+        if !self.observers.is_empty() {
+            for event in GameEvent::resigning(&self.state, seat, &next) {
+                for observer in &mut self.observers {
+                    observer.notify(&event, &next);
+                }
+            }
+        }
+        self.state = next;
+        self.history.clear();
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Starts the game over on the same board under the same rules, the
+    /// dice thrown from `seed`, its observers still following it.
+    pub fn restart(&mut self, seed: Option<u64>) {
+        let board = self.record.board.clone();
+        let observers = std::mem::take(&mut self.observers);
+        *self = Self::new(board, self.record.rules, seed);
+        self.observers = observers;
+    }
+
+    /// Plays a ply for the player to move with the computer, throwing
+    /// `dice` and letting `player` choose what to do with the throw within
+    /// `budget`. Returns whether the game goes on.
+    pub fn play_ai<R: Rng>(
+        &mut self,
+        dice: &mut Dice<R>,
+        player: &mut dyn AiPlayer,
+        budget: Duration,
+    ) -> Result<bool> {
+        if self.state.phase() == Phase::Roll {
+            self.play(Move::Roll(dice.roll().total()))?;
+        }
+        if self.state.phase() == Phase::Move {
+            let legal = self.state.legal_moves();
+            let mv = player.choose_move(&self.state, &legal, budget);
+            self.play(mv)?;
+        }
+        Ok(self.state.phase() != Phase::Finished)
+    }
+
+    /// Plays the game to its end with the computer, `players` taking the
+    /// seated players in seat order with `budget` for each choice.
+    pub fn play_out<R: Rng>(
+        &mut self,
+        dice: &mut Dice<R>,
+        players: &mut [Box<dyn AiPlayer>],
+        budget: Duration,
+    ) -> Result<()> {
+        let sides = self.state.sides().len();
+        ensure!(
+            players.len() == sides,
+            "{} computer players for {sides} seated players",
+            players.len()
+        );
+        loop {
+            let seat = self.state.turn().seat;
+            let side = self.state.sides().iter().position(|s| s.seat == seat);
+            let player = &mut players[side.expect("the player to move sits")];
+            if !self.play_ai(dice, player.as_mut(), budget)? {
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Takes back the last ply, or the throw of one still being played,
+    /// returning whether there was one.
+    pub fn undo(&mut self) -> bool {
+        let Some(state) = self.history.pop() else {
+            return false;
+        };
+        let ply = self.record.pop().expect("history follows plies");
+        self.undone.push(ply);
+        self.state = state;
+        true
+    }
+
+    /// Plays the last ply taken back again, returning whether there was
+    /// one.
+    pub fn redo(&mut self) -> bool {
+        let Some(ply) = self.undone.pop() else {
+            return false;
+        };
+        for mv in ply.moves() {
+            self.advance(mv).expect("undone plies were legal");
+        }
+        true
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let save = Save {
+            version: Self::VERSION,
+            record: self.record.clone(),
+        };
+        fs::write(path, toml::to_string(&save)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Loads a saved game, replaying it so saves the rules can no longer
+    /// play fail here rather than later.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&text)
+            .with_context(|| format!("invalid saved game {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let Header { version } = toml::from_str(text)?;
+        let save: Save = match version {
+            1 => toml::from_str(text)?,
+            version if version > Self::VERSION => bail!(
+                "saved by a newer version in format {version}, this one \
+                 reads up to {}",
+                Self::VERSION
+            ),
+            version => bail!("unknown save format {version}"),
+        };
+        Self::from_record(save.record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_with_unknown_keys_load() {
+        let mut game = Game::new(Board::new(4), RuleSet::default(), Some(7));
+        game.play(Move::Roll(6)).unwrap();
+        let save = Save {
+            version: Game::VERSION,
+            record: game.record().clone(),
+        };
+        let mut save: toml::Table =
+            toml::from_str(&toml::to_string(&save).unwrap()).unwrap();
+        let extra = |table: &mut toml::Table| {
+            table.insert("added_later".into(), true.into());
+        };
+        extra(&mut save);
+        let record = save["record"].as_table_mut().unwrap();
+        extra(record);
+        for key in ["board", "rules"] {
+            let nested = record[key].as_table_mut().unwrap();
+            extra(nested);
+        }
+        let board = record["board"].as_table_mut().unwrap();
+        extra(board["layout"].as_table_mut().unwrap());
+        let rules = record["rules"].as_table_mut().unwrap();
+        extra(rules["dice"].as_table_mut().unwrap());
+
+        let loaded = Game::parse(&toml::to_string(&save).unwrap()).unwrap();
+        assert_eq!(loaded.state().checksum(), game.state().checksum());
+    }
+
+    #[test]
+    fn saves_seating_players_the_board_cannot_are_refused() {
+        let game = Game::new(Board::new(4), RuleSet::default(), Some(7));
+        let save = Save {
+            version: Game::VERSION,
+            record: game.record().clone(),
+        };
+        let text = toml::to_string(&save).unwrap();
+        for players in [5, 9, 40] {
+            let mut save: toml::Table = toml::from_str(&text).unwrap();
+            let record = save["record"].as_table_mut().unwrap();
+            let board = record["board"].as_table_mut().unwrap();
+            board.insert("players".into(), players.into());
+            let text = toml::to_string(&save).unwrap();
+            assert!(Game::parse(&text).is_err(), "{players}");
+        }
+    }
+}
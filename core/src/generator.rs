@@ -0,0 +1,229 @@
+use anyhow::{Result, bail, ensure};
+use kurbo::Point;
+
+use crate::board::{Board, BoardShape, Cell, CellKind};
+use crate::layout::{Layout, Shortcut};
+use crate::metrics::Metrics;
+use crate::track::Spot;
+
+/// Stairs the track can climb from the entry of a quadrant to its exit
+/// without crossing the home base or the next seat's runway, as cells run
+/// right then up. A last run right reaches the exit.
+const STAIRS: [&[(usize, usize)]; 3] =
+    [&[(2, 2)], &[(3, 2)], &[(2, 0), (0, 0)]];
+
+/// Quadrant cells left of the exit on the top edge.
+const EXIT: usize = 11;
+
+/// Randomized square boards, the same for the same seed and parameters.
+/// Each quadrant gets a track climbing from its entry to its exit in one
+/// of a few ways, random colors with launch and landing cells of their
+/// owners' colors, and shortcuts between cells of one color.
+#[derive(Copy, Clone, Debug)]
+pub struct BoardGenerator {
+    pub seed: u64,
+    pub players: usize,
+    /// Cells around the outer ring, picked from the seed when `None`.
+    pub ring: Option<usize>,
+    /// Shortcuts starting in each quadrant.
+    pub shortcuts: usize,
+}
+
+impl BoardGenerator {
+    pub fn new(seed: u64, players: usize) -> Self {
+        Self {
+            seed,
+            players,
+            ring: None,
+            shortcuts: 0,
+        }
+    }
+
+    /// Ring lengths generated boards can have.
+    pub fn ring_lengths() -> Vec<usize> {
+        let seats = BoardShape::Square.seats();
+        let mut lengths: Vec<usize> = STAIRS
+            .iter()
+            .map(|stairs| quadrant(stairs).0.len() * seats)
+            .collect();
+        lengths.sort_unstable();
+        lengths.dedup();
+        lengths
+    }
+
+    pub fn generate(&self) -> Result<Board> {
+        let shape = BoardShape::Square;
+        ensure!(
+            shape.players().contains(&self.players),
+            "generated boards seat one of {:?} players",
+            shape.players()
+        );
+        Ok(Board::with_shape(shape, self.players, self.layout()?))
+    }
+
+    /// The track layout of the generated board.
+    pub fn layout(&self) -> Result<Layout> {
+        let mut rng = Rng(self.seed);
+        let seats = BoardShape::Square.seats();
+        let candidates: Vec<_> = STAIRS
+            .iter()
+            .filter(|stairs| {
+                self.ring
+                    .is_none_or(|ring| quadrant(stairs).0.len() * seats == ring)
+            })
+            .collect();
+        if candidates.is_empty() {
+            bail!(
+                "generated rings have one of {:?} cells",
+                Self::ring_lengths()
+            );
+        }
+        let (places, edges) = quadrant(candidates[rng.below(candidates.len())]);
+        let cell = |(kind, origin), color| Cell::new(kind, color, origin);
+
+        // Launch and landing cells are found on the uncolored track.
+        let blank = places.iter().map(|&place| cell(place, 0)).collect();
+        let blank = Layout::new(blank, vec![], vec![])?;
+        let board = Board::with_shape(BoardShape::Square, seats, blank);
+        let track = board.track();
+        let landing = track.cells()[..track.ring_len()]
+            .iter()
+            .position(|cell| {
+                cell.branch.is_some_and(|runway| {
+                    track.cells()[runway].spot
+                        == (Spot::Runway { seat: 0, step: 0 })
+                })
+            })
+            .expect("seat 0 has a runway");
+        // Quadrant cells whose color is set by the seat 0 cells they hold.
+        let colors = seats;
+        let fixed: Vec<(usize, usize)> = [track.launch(0), landing]
+            .into_iter()
+            .map(|id| {
+                let Spot::Ring { seat, index } = track.cells()[id].spot else {
+                    unreachable!("launches and landings are on the ring");
+                };
+                (index, (colors - seat % colors) % colors)
+            })
+            .collect();
+        let fixed_color = |index| {
+            fixed
+                .iter()
+                .find(|(fixed, _)| *fixed == index)
+                .map(|(_, color)| *color)
+        };
+
+        // Neighbors differ, also across quadrants where the colors shift.
+        let len = places.len();
+        let mut palette: Vec<usize> = vec![];
+        for index in 0..len {
+            let color = fixed_color(index).unwrap_or_else(|| {
+                let allowed: Vec<usize> = (0..colors)
+                    .filter(|&color| {
+                        index.checked_sub(1).is_none_or(|p| palette[p] != color)
+                            && fixed_color(index + 1) != Some(color)
+                            && (index + 1 < len
+                                || color != (palette[0] + 1) % colors)
+                    })
+                    .collect();
+                allowed[rng.below(allowed.len())]
+            });
+            palette.push(color);
+        }
+        let ring_color =
+            |ring: usize| (palette[ring % len] + ring / len) % colors;
+
+        let mut shortcuts: Vec<Shortcut> = vec![];
+        let mut starts: Vec<Shortcut> = (0..len)
+            .filter(|&from| fixed_color(from).is_none())
+            .flat_map(|from| {
+                (4..=len).map(move |ahead| Shortcut { from, ahead })
+            })
+            .filter(|s| ring_color(s.from + s.ahead) == palette[s.from])
+            .collect();
+        while shortcuts.len() < self.shortcuts {
+            if starts.is_empty() {
+                bail!(
+                    "only {} shortcuts fit the generated quadrant",
+                    shortcuts.len()
+                );
+            }
+            let shortcut = starts.swap_remove(rng.below(starts.len()));
+            starts.retain(|s| s.from != shortcut.from);
+            shortcuts.push(shortcut);
+        }
+        shortcuts.sort_by_key(|s| s.from);
+
+        let cells = places
+            .into_iter()
+            .zip(palette)
+            .map(|(place, color)| cell(place, color))
+            .collect();
+        let layout = Layout::new(cells, edges, shortcuts)?;
+        if let Err(errors) = layout.validate() {
+            bail!("generated an invalid board: {}", errors[0]);
+        }
+        Ok(layout)
+    }
+}
+
+/// Cells and edges of a quadrant's track climbing `stairs`, at the default
+/// cell size.
+fn quadrant(
+    stairs: &[(usize, usize)],
+) -> (Vec<(CellKind, Point)>, Vec<Vec<Point>>) {
+    let dim = Metrics::DEFAULT_DIM;
+    let at = |x: usize, y: usize| Point::new(x as f64 * dim, y as f64 * dim);
+    // The track enters on the left edge below the base, two cells wide.
+    let mut places = vec![(CellKind::Triangle180, at(2, 4))];
+    let mut outer = vec![at(0, 6), at(2, 4)];
+    let mut inner = vec![at(2, 6)];
+    let (mut x, mut y) = (2, 4);
+    for &(right, up) in stairs {
+        for _ in 0..right {
+            places.push((CellKind::VBlock, at(x, y)));
+            x += 1;
+        }
+        outer.push(at(x, y));
+        places.push((CellKind::Triangle270, at(x, y)));
+        places.push((CellKind::Triangle90, at(x, y)));
+        inner.push(at(x + 2, y + 2));
+        for _ in 0..up {
+            y -= 1;
+            places.push((CellKind::HBlock, at(x, y)));
+        }
+        outer.push(at(x, y));
+        inner.push(at(x + 2, y));
+        y -= 2;
+        places.push((CellKind::Triangle180, at(x + 2, y)));
+        x += 2;
+        outer.push(at(x, y));
+    }
+    for x in x..EXIT {
+        places.push((CellKind::VBlock, at(x, y)));
+    }
+    outer.push(at(EXIT, 0));
+    inner.push(at(EXIT, 2));
+    for edge in [&mut outer, &mut inner] {
+        edge.dedup();
+    }
+    (places, vec![outer, inner])
+}
+
+/// SplitMix64, so boards stay the same for a seed across platforms.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number below `n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
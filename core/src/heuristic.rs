@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai::AiPlayer;
+use crate::rules::{Event, GameState, Move, Position};
+
+/// What the heuristic player makes of a position, in points. Weights are
+/// TOML files listing any of the fields, those left out taking the
+/// defaults, so its strength is tuned without building it again.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Weights {
+    /// Per spot a plane flew toward its goal.
+    pub progress: f64,
+    /// Per plane out of its hangar.
+    pub launched: f64,
+    /// Per plane home.
+    pub home: f64,
+    /// Per opponent plane a move sends back to its hangar.
+    pub capture: f64,
+    /// Per jump a move takes from a cell of the player's color.
+    pub jump: f64,
+    /// Per shortcut a move flies.
+    pub shortcut: f64,
+    /// Per opponent plane able to capture one of the player's with its
+    /// next throw, taken off.
+    pub exposure: f64,
+    /// How much the average position of the opponents counts against the
+    /// player's own, from 0 for not at all to 1 for as much.
+    pub opponents: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            progress: 1.0,
+            launched: 8.0,
+            home: 10.0,
+            capture: 12.0,
+            jump: 2.0,
+            shortcut: 2.0,
+            exposure: 5.0,
+            opponents: 0.5,
+        }
+    }
+}
+
+impl Weights {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&text)
+            .with_context(|| format!("invalid weights {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+}
+
+/// Plays the move leading to the position its [`Weights`] score best,
+/// looking no further than the throw it has to use.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct HeuristicPlayer {
+    pub weights: Weights,
+}
+
+impl HeuristicPlayer {
+    pub fn new(weights: Weights) -> Self {
+        Self { weights }
+    }
+
+    /// How good `state` looks for the player in `seat`: where their
+    /// planes are and how exposed, less the opponents doing as well.
+    pub fn evaluate(&self, state: &GameState, seat: usize) -> f64 {
+        let sides = state.sides();
+        let opponents: f64 = sides
+            .iter()
+            .filter(|side| side.seat != seat)
+            .map(|side| self.side(state, side.seat))
+            .sum();
+        let count = sides.len().saturating_sub(1).max(1) as f64;
+        self.side(state, seat) - self.weights.opponents * opponents / count
+    }
+
+    /// How good the player to move in `state` finds `mv`: the change of
+    /// their position, with captures, jumps and shortcuts on top.
+    pub fn score(&self, state: &GameState, mv: Move) -> f64 {
+        let seat = state.turn().seat;
+        let Ok(next) = state.apply(mv) else {
+            return f64::NEG_INFINITY;
+        };
+        let mut score = self.evaluate(&next, seat) - self.evaluate(state, seat);
+        for event in next.events() {
+            score += match event {
+                Event::Capture { planes, .. } => {
+                    self.weights.capture * planes.len() as f64
+                }
+                Event::Jump { .. } => self.weights.jump,
+                Event::Fly { .. } => self.weights.shortcut,
+                Event::ExtraTurn { .. } | Event::Penalty { .. } => 0.0,
+            };
+        }
+        score
+    }
+
+    /// Points of the planes of `seat` on their own.
+    fn side(&self, state: &GameState, seat: usize) -> f64 {
+        let track = state.track();
+        let Some(side) = state.sides().iter().find(|side| side.seat == seat)
+        else {
+            return 0.0;
+        };
+        let route = track.distance(track.launch(seat), seat) as f64;
+        let weights = &self.weights;
+        let mut points = 0.0;
+        for &plane in &side.planes {
+            points += match plane {
+                Position::Hangar => 0.0,
+                Position::Track(cell) => {
+                    let left = track.distance(cell, seat) as f64;
+                    weights.launched + weights.progress * (route - left)
+                        - weights.exposure * threats(state, seat, cell) as f64
+                }
+                Position::Home => {
+                    weights.launched + weights.progress * route + weights.home
+                }
+            };
+        }
+        points
+    }
+}
+
+impl AiPlayer for HeuristicPlayer {
+    fn choose_move(
+        &mut self,
+        state: &GameState,
+        legal: &[Move],
+        _budget: Duration,
+    ) -> Move {
+        let mut best = None;
+        for &mv in legal {
+            let score = self.score(state, mv);
+            if best.is_none_or(|(_, top)| score > top) {
+                best = Some((mv, score));
+            }
+        }
+        best.expect("a throw always leaves a move").0
+    }
+}
+
+/// Opponent planes of `seat` able to land on ring `cell` with their next
+/// throw, launching onto it or flying there spot by spot.
+fn threats(state: &GameState, seat: usize, cell: usize) -> usize {
+    let track = state.track();
+    if cell >= track.ring_len() {
+        return 0;
+    }
+    let dice = state.rules().dice;
+    let launches = dice.totals().any(|total| dice.is_top(total));
+    let mut threats = 0;
+    for side in state.sides().iter().filter(|side| side.seat != seat) {
+        for &plane in &side.planes {
+            threats += match plane {
+                Position::Hangar => {
+                    usize::from(launches && track.launch(side.seat) == cell)
+                }
+                Position::Track(mut from) => {
+                    let mut hit = false;
+                    for steps in 1..=*dice.totals().end() {
+                        match track.successor(from, side.seat) {
+                            Some(next) => from = next,
+                            None => break,
+                        }
+                        hit |= from == cell && dice.totals().contains(&steps);
+                    }
+                    usize::from(hit)
+                }
+                Position::Home => 0,
+            };
+        }
+    }
+    threats
+}
@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, ensure};
+use kurbo::{BezPath, Point};
+use serde::{Deserialize, Serialize, Serializer, ser};
+
+use crate::board::{BoardShape, Cell, CellKind, Player};
+use crate::metrics::Metrics;
+
+/// Track cells of one quadrant, shared by every seat. Layouts are TOML
+/// files listing `[[cell]]` tables, optionally followed by `[[edge]]` and
+/// `[[shortcut]]` tables, see `layouts/classic.toml`, and are serialized
+/// in the same form.
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "File")]
+pub struct Layout {
+    /// Cells of the quadrant in seat 0, colors indexing
+    /// [`Player::COLOR_NAMES`].
+    cells: Vec<Cell>,
+    /// Polylines stroked along the track, at the default cell size.
+    edges: Vec<Vec<Point>>,
+    shortcuts: Vec<Shortcut>,
+}
+
+/// A jump from a cell of the quadrant to a cell of the same color further
+/// along the ring, repeated in every quadrant.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Shortcut {
+    /// Index of the layout cell the jump starts from.
+    pub from: usize,
+    /// Ring cells between the start and the end of the jump.
+    pub ahead: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct File {
+    #[serde(rename = "cell")]
+    cells: Vec<Entry>,
+    #[serde(default, rename = "edge", skip_serializing_if = "Vec::is_empty")]
+    edges: Vec<Edge>,
+    #[serde(
+        default,
+        rename = "shortcut",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    shortcuts: Vec<Shortcut>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    kind: CellKind,
+    /// One of [`Player::COLOR_NAMES`].
+    color: String,
+    /// Origin in the quadrant's local board pixels at the default cell
+    /// size.
+    origin: [f64; 2],
+}
+
+#[derive(Serialize, Deserialize)]
+struct Edge {
+    /// Corners of the polyline in the quadrant's local board pixels at the
+    /// default cell size.
+    points: Vec<[f64; 2]>,
+}
+
+impl Layout {
+    /// A layout from cells and edges at the default cell size.
+    pub fn new(
+        cells: Vec<Cell>,
+        edges: Vec<Vec<Point>>,
+        shortcuts: Vec<Shortcut>,
+    ) -> Result<Self> {
+        ensure!(!cells.is_empty(), "a layout needs at least one cell");
+        for cell in &cells {
+            ensure!(
+                cell.color < Player::COLOR_NAMES.len(),
+                "cell color {} past the {} colors",
+                cell.color,
+                Player::COLOR_NAMES.len()
+            );
+        }
+        for shortcut in &shortcuts {
+            ensure!(
+                shortcut.from < cells.len(),
+                "shortcut from cell {} of a layout of {} cells",
+                shortcut.from,
+                cells.len()
+            );
+            ensure!(shortcut.ahead > 0, "shortcuts must move ahead");
+        }
+        Ok(Self {
+            cells,
+            edges,
+            shortcuts,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&text)
+            .with_context(|| format!("invalid layout {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Track cells of the quadrant in `seat` sized by `metrics`, with
+    /// colors shifted by the seat so neighboring quadrants continue the
+    /// sequence.
+    pub fn cells(&self, seat: usize, metrics: &Metrics) -> Vec<Cell> {
+        self.cells
+            .iter()
+            .map(|cell| Cell {
+                color: cell.color + seat,
+                origin: (cell.origin.to_vec2() * metrics.scale()).to_point(),
+                dim: metrics.dim,
+                ..*cell
+            })
+            .collect()
+    }
+
+    /// Edges of the quadrant's track sized by `metrics`, stroked but not
+    /// filled.
+    pub fn edges(&self, metrics: &Metrics) -> Vec<BezPath> {
+        self.edges
+            .iter()
+            .map(|points| {
+                let mut path = BezPath::new();
+                for (i, point) in points.iter().enumerate() {
+                    let point = (point.to_vec2() * metrics.scale()).to_point();
+                    if i == 0 {
+                        path.move_to(point);
+                    } else {
+                        path.line_to(point);
+                    }
+                }
+                path
+            })
+            .collect()
+    }
+
+    pub fn shortcuts(&self) -> &[Shortcut] {
+        &self.shortcuts
+    }
+}
+
+impl TryFrom<File> for Layout {
+    type Error = anyhow::Error;
+
+    fn try_from(file: File) -> Result<Self> {
+        let cells = file
+            .cells
+            .into_iter()
+            .map(|entry| {
+                let color = Player::COLOR_NAMES
+                    .iter()
+                    .position(|name| *name == entry.color)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "unknown color {:?}, expected one of {:?}",
+                            entry.color,
+                            Player::COLOR_NAMES
+                        )
+                    })?;
+                let [x, y] = entry.origin;
+                Ok(Cell::new(entry.kind, color, Point::new(x, y)))
+            })
+            .collect::<Result<_>>()?;
+        let edges = file
+            .edges
+            .into_iter()
+            .map(|edge| {
+                edge.points
+                    .into_iter()
+                    .map(|[x, y]| Point::new(x, y))
+                    .collect()
+            })
+            .collect();
+        Self::new(cells, edges, file.shortcuts)
+    }
+}
+
+impl Serialize for Layout {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        File::try_from(self.clone())
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl TryFrom<Layout> for File {
+    type Error = anyhow::Error;
+
+    fn try_from(layout: Layout) -> Result<Self> {
+        let cells = layout
+            .cells
+            .into_iter()
+            .map(|cell| {
+                let color =
+                    Player::COLOR_NAMES.get(cell.color).ok_or_else(|| {
+                        anyhow!("cell color {} has no name", cell.color)
+                    })?;
+                Ok(Entry {
+                    kind: cell.kind,
+                    color: (*color).into(),
+                    origin: [cell.origin.x, cell.origin.y],
+                })
+            })
+            .collect::<Result<_>>()?;
+        let edges = layout
+            .edges
+            .into_iter()
+            .map(|points| Edge {
+                points: points.into_iter().map(|p| [p.x, p.y]).collect(),
+            })
+            .collect();
+        Ok(File {
+            cells,
+            edges,
+            shortcuts: layout.shortcuts,
+        })
+    }
+}
+
+impl Layout {
+    /// The classic aeroplane chess track for boards of `shape`, in as many
+    /// colors as it has seats.
+    pub fn classic(shape: BoardShape) -> Self {
+        let text = match shape {
+            BoardShape::Square => include_str!("../layouts/classic.toml"),
+            BoardShape::Hex6 => include_str!("../layouts/hex6.toml"),
+        };
+        Self::parse(text).expect("the classic layouts are valid")
+    }
+}
+
+impl Default for Layout {
+    /// The classic aeroplane chess track of the square board.
+    fn default() -> Self {
+        Self::classic(BoardShape::Square)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_need_a_named_color() {
+        let cell = |color| Cell::new(CellKind::VBlock, color, Point::ORIGIN);
+        let named = Player::COLOR_NAMES.len() - 1;
+        assert!(Layout::new(vec![cell(named)], vec![], vec![]).is_ok());
+        let unnamed = Player::COLOR_NAMES.len();
+        assert!(Layout::new(vec![cell(unnamed)], vec![], vec![]).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+//! Geometry of the flight board, shared by the game logic, the renderers
+//! and the asset tooling.
+//!
+//! On `wasm32` the networking, the binary records and the parallel
+//! simulations are left out, as browsers offer no sockets, threads or C
+//! compression to build them on.
+
+pub mod ai;
+#[cfg(not(target_arch = "wasm32"))]
+mod binary;
+pub mod board;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod client;
+pub mod dice;
+pub mod evaluate;
+pub mod expectimax;
+pub mod game;
+pub mod generator;
+pub mod heuristic;
+pub mod layout;
+pub mod mcts;
+pub mod metrics;
+mod notation;
+pub mod observe;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod peer;
+pub mod protocol;
+pub mod record;
+pub mod replay;
+pub mod rules;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod simulate;
+pub mod stats;
+pub mod track;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tune;
+pub mod validate;
+
+pub use ai::{AiPlayer, Difficulty, Factory, RandomPlayer};
+pub use board::{Board, BoardShape, Cell, CellKind, Player};
+#[cfg(not(target_arch = "wasm32"))]
+pub use client::{Client, Seat, Table};
+pub use dice::{Dice, DiceSpec, Throw};
+pub use evaluate::{PlayerScores, evaluate, evaluate_with};
+pub use expectimax::ExpectimaxPlayer;
+pub use game::Game;
+pub use generator::BoardGenerator;
+pub use heuristic::{HeuristicPlayer, Weights};
+pub use layout::{Layout, Shortcut};
+pub use mcts::MctsPlayer;
+pub use metrics::Metrics;
+pub use observe::{GameEvent, Observer};
+#[cfg(not(target_arch = "wasm32"))]
+pub use peer::Peer;
+pub use protocol::{
+    ChatLine, ClientRequest, PeerMessage, RoomInfo, ServerEvent, ServerMessage,
+    ServerResponse,
+};
+pub use record::{Action, Flight, GameRecord, Ply, Resignation};
+pub use replay::{Frame, Replay};
+pub use rules::{
+    Ending, Event, GameState, Leg, Move, Phase, PlaneId, Position, Route,
+    RuleSet, Side, Stack,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::Server;
+#[cfg(not(target_arch = "wasm32"))]
+pub use simulate::{Simulation, Summary};
+pub use stats::Stats;
+pub use track::{CellId, Spot, Track, TrackCell};
+#[cfg(not(target_arch = "wasm32"))]
+pub use tune::{Generation, Tuning};
+pub use validate::BoardError;
@@ -0,0 +1,253 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, RngExt, SeedableRng};
+
+use crate::ai::AiPlayer;
+use crate::rules::{GameState, Move, Phase};
+
+/// Plies a playout takes at most before calling the game a draw.
+const PLAYOUT_PLIES: usize = 2000;
+
+/// Monte Carlo tree search: plays the game out at random from the moves
+/// it could take over and over, growing a tree of the positions met
+/// toward the moves winning most often, and plays the move tried most.
+/// The strongest player and the slowest, so also the yardstick others
+/// are measured against.
+#[derive(Debug)]
+pub struct MctsPlayer {
+    /// Playouts for each choice.
+    pub iterations: u32,
+    /// Time each choice may take at most, stopping before the playouts
+    /// are all done.
+    pub time: Option<Duration>,
+    /// Threads searching trees of their own, the playouts split between
+    /// them, whose counts are added up at the end.
+    pub threads: usize,
+    /// How much less tried moves are favored over those winning most, √2
+    /// in theory.
+    pub exploration: f64,
+    rng: StdRng,
+}
+
+/// A position of a search tree.
+struct Node {
+    state: GameState,
+    visits: u32,
+    /// Playouts won from here by each seated player, in seat order.
+    wins: Vec<f64>,
+    /// Moves taken from here, throws when the dice are to be thrown.
+    children: Vec<(Move, usize)>,
+    /// Moves not taken yet, when the player to move chooses.
+    untried: Vec<Move>,
+}
+
+impl Node {
+    fn new(state: GameState) -> Self {
+        let untried = match state.phase() {
+            Phase::Move => state.legal_moves(),
+            Phase::Roll | Phase::Finished => vec![],
+        };
+        Self {
+            wins: vec![0.0; state.sides().len()],
+            state,
+            visits: 0,
+            children: vec![],
+            untried,
+        }
+    }
+}
+
+impl MctsPlayer {
+    /// A player making the same choices for the same seed, with a budget
+    /// of a thousand playouts a choice on a single thread.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            iterations: 1000,
+            time: None,
+            threads: 1,
+            exploration: std::f64::consts::SQRT_2,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Searches a tree from `state` with `iterations` playouts, returning
+    /// how often each of its moves was tried.
+    fn search(
+        &self,
+        state: &GameState,
+        iterations: u32,
+        deadline: Option<Instant>,
+        rng: &mut impl Rng,
+    ) -> Vec<(Move, u32)> {
+        let mut nodes = vec![Node::new(state.clone())];
+        for _ in 0..iterations {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            let path = self.descend(&mut nodes, rng);
+            let last = &nodes[*path.last().expect("paths start at the root")];
+            let wins = playout(last.state.clone(), rng);
+            for index in path {
+                let node = &mut nodes[index];
+                node.visits += 1;
+                for (total, win) in node.wins.iter_mut().zip(&wins) {
+                    *total += win;
+                }
+            }
+        }
+        nodes[0]
+            .children
+            .iter()
+            .map(|&(mv, child)| (mv, nodes[child].visits))
+            .collect()
+    }
+
+    /// Walks down the tree to a position not searched yet, adding it,
+    /// and returns the way there.
+    fn descend(&self, nodes: &mut Vec<Node>, rng: &mut impl Rng) -> Vec<usize> {
+        let mut path = vec![0];
+        loop {
+            let index = *path.last().expect("paths start at the root");
+            let added = nodes.len();
+            let node = &mut nodes[index];
+            let mv = match node.state.phase() {
+                Phase::Finished => return path,
+                Phase::Roll => Move::Roll(throw(&node.state, rng)),
+                Phase::Move if !node.untried.is_empty() => {
+                    let pick = rng.random_range(0..node.untried.len());
+                    node.untried.swap_remove(pick)
+                }
+                Phase::Move => {
+                    let child = self.select(nodes, index);
+                    path.push(child);
+                    continue;
+                }
+            };
+            if let Some(&(_, child)) =
+                node.children.iter().find(|(taken, _)| *taken == mv)
+            {
+                path.push(child);
+                continue;
+            }
+            let next = node.state.apply(mv).expect("the move is legal");
+            node.children.push((mv, added));
+            path.push(added);
+            nodes.push(Node::new(next));
+            return path;
+        }
+    }
+
+    /// The child of a fully expanded node most worth trying again, by
+    /// the upper confidence bound of its wins for the player to move.
+    fn select(&self, nodes: &[Node], index: usize) -> usize {
+        let node = &nodes[index];
+        let seat = node.state.turn().seat;
+        let side = node.state.sides().iter().position(|s| s.seat == seat);
+        let side = side.expect("the player to move sits");
+        let log = f64::from(node.visits.max(1)).ln();
+        let bound = |child: usize| {
+            let child = &nodes[child];
+            let visits = f64::from(child.visits.max(1));
+            child.wins[side] / visits + self.exploration * (log / visits).sqrt()
+        };
+        let (_, best) = node
+            .children
+            .iter()
+            .max_by(|a, b| bound(a.1).total_cmp(&bound(b.1)))
+            .expect("chosen moves have children");
+        *best
+    }
+}
+
+impl AiPlayer for MctsPlayer {
+    fn choose_move(
+        &mut self,
+        state: &GameState,
+        legal: &[Move],
+        budget: Duration,
+    ) -> Move {
+        if let [mv] = legal[..] {
+            return mv;
+        }
+        let time = self.time.map_or(budget, |time| time.min(budget));
+        let deadline = Instant::now().checked_add(time);
+        let threads = self.threads.max(1);
+        let share = self.iterations.div_ceil(threads as u32);
+        let seeds: Vec<u64> = (0..threads).map(|_| self.rng.random()).collect();
+        let player = &*self;
+        let search = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            player.search(state, share, deadline, &mut rng)
+        };
+        // Browsers run wasm on one thread, so the searches take turns.
+        #[cfg(target_arch = "wasm32")]
+        let searches: Vec<_> = seeds.into_iter().map(search).collect();
+        #[cfg(not(target_arch = "wasm32"))]
+        let searches: Vec<_> = thread::scope(|scope| {
+            let searches: Vec<_> = seeds
+                .into_iter()
+                .map(|seed| scope.spawn(move || search(seed)))
+                .collect();
+            searches
+                .into_iter()
+                .map(|search| search.join().expect("searches do not panic"))
+                .collect()
+        });
+        let mut counts = vec![0; legal.len()];
+        for tried in searches {
+            for (mv, visits) in tried {
+                let index = legal.iter().position(|&m| m == mv);
+                counts[index.expect("searched moves are legal")] += visits;
+            }
+        }
+        let best = (0..legal.len()).max_by_key(|&index| counts[index]);
+        legal[best.expect("a throw always leaves a move")]
+    }
+}
+
+/// A total of the dice thrown in `state`, as likely as it is on them.
+fn throw(state: &GameState, rng: &mut impl Rng) -> u8 {
+    let odds = state.rules().dice.odds();
+    let mut roll = rng.random::<f64>();
+    for &(total, chance) in &odds {
+        if roll < chance {
+            return total;
+        }
+        roll -= chance;
+    }
+    odds.last().expect("dice show some total").0
+}
+
+/// Plays `state` out at random, returning the win of each seated player
+/// in seat order, shared out when the game drags on too long.
+pub(crate) fn playout(mut state: GameState, rng: &mut impl Rng) -> Vec<f64> {
+    let sides = state.sides().len();
+    for _ in 0..PLAYOUT_PLIES {
+        let mv = match state.phase() {
+            Phase::Finished => break,
+            Phase::Roll => Move::Roll(throw(&state, rng)),
+            Phase::Move => {
+                let moves = state.legal_moves();
+                moves[rng.random_range(0..moves.len())]
+            }
+        };
+        state = state.apply(mv).expect("the move is legal");
+    }
+    let mut wins = vec![0.0; sides];
+    match state.winner() {
+        Some(winner) => {
+            let side = state.sides().iter().position(|s| s.seat == winner);
+            wins[side.expect("winners sit")] = 1.0;
+        }
+        None => wins.fill(1.0 / sides as f64),
+    }
+    wins
+}
@@ -0,0 +1,62 @@
+use kurbo::Rect;
+use serde::{Deserialize, Serialize};
+
+/// Sizes of the board geometry, all derived from the side of a cell so the
+/// board can be laid out directly at any resolution.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Metrics {
+    /// Side of a cell in board pixels.
+    pub dim: f64,
+}
+
+impl Metrics {
+    /// Side of a cell layouts and themes are authored for.
+    pub const DEFAULT_DIM: f64 = 128.0;
+
+    /// Cells along the side of a quadrant.
+    pub const QUADRANT_CELLS: f64 = 17.0;
+
+    pub const fn new(dim: f64) -> Self {
+        Self { dim }
+    }
+
+    pub fn dim_x2(&self) -> f64 {
+        self.dim * 2.0
+    }
+
+    pub fn dim_x4(&self) -> f64 {
+        self.dim * 4.0
+    }
+
+    /// Factor from the default cell size to this one, applied to lengths
+    /// authored for the default such as layout origins and stroke widths.
+    pub fn scale(&self) -> f64 {
+        self.dim / Self::DEFAULT_DIM
+    }
+
+    /// Side of a quadrant.
+    pub fn quadrant(&self) -> f64 {
+        self.dim * Self::QUADRANT_CELLS
+    }
+
+    /// Radius of the accent spot in the middle of a cell.
+    pub fn cell_radius(&self) -> f64 {
+        self.dim * 0.35
+    }
+
+    /// Radius of the hangar spots on a home base.
+    pub fn hangar_radius(&self) -> f64 {
+        self.dim * 0.6
+    }
+
+    /// Home base square in a quadrant's local coordinates.
+    pub fn base(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.dim_x4(), self.dim_x4())
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DIM)
+    }
+}
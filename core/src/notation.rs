@@ -0,0 +1,298 @@
+use anyhow::{Context, Result, anyhow, bail, ensure};
+
+use crate::board::{Board, BoardShape};
+use crate::dice::DiceSpec;
+use crate::layout::Layout;
+use crate::metrics::Metrics;
+use crate::record::{Action, GameRecord, Ply};
+use crate::rules::{Ending, GameState, Phase, RuleSet};
+
+/// The switches of `rules` by the names they take in the `Rules` header.
+fn switches(rules: &mut RuleSet) -> [(&'static str, &mut bool); 7] {
+    [
+        ("jumps", &mut rules.jumps),
+        ("jump-chains", &mut rules.jump_chains),
+        ("shortcuts", &mut rules.shortcuts),
+        ("stacks-move", &mut rules.stacks_move),
+        ("stacks-block", &mut rules.stacks_block),
+        ("bounce", &mut rules.bounce),
+        ("three-sixes", &mut rules.three_sixes),
+    ]
+}
+
+/// Names of the endings in the `Rules` header.
+const ENDINGS: [(&str, Ending); 2] = [
+    ("first-home", Ending::FirstHome),
+    ("ranking", Ending::Ranking),
+];
+
+impl GameRecord {
+    /// Writes the record in game notation, a text made to be read and
+    /// shared, in the spirit of chess PGN. Headers in brackets name the
+    /// board, the players, the rules and the seed, then every ply follows
+    /// on a line of its own, numbered and in [`Ply`] notation, with the
+    /// players finishing noted in comments after a `;`:
+    ///
+    /// ```text
+    /// [Board "square"]
+    /// [Players "4"]
+    /// [Rules "1d6 jumps no-jump-chains shortcuts ... first-home"]
+    /// [Seed "7"]
+    ///
+    /// 1. 0:6 2 H-1
+    /// 2. 0:4 2 1-9jx
+    /// ```
+    ///
+    /// Boards of any other layout carry it after the plies, as TOML on
+    /// lines starting with `%`. Checksums are left out, they come back
+    /// when the text is read. Games a player resigned have no notation.
+    pub fn to_text(&self) -> Result<String> {
+        self.replay()?;
+        ensure!(
+            self.resignations.is_empty(),
+            "resignations have no game notation"
+        );
+        let mut rules = self.rules;
+        let dice = rules.dice;
+        let mut text = format!(
+            "[Board \"{}\"]\n[Players \"{}\"]\n[Rules \"{}d{}",
+            self.board.shape().name(),
+            self.board.players(),
+            dice.count,
+            dice.faces
+        );
+        for (name, on) in switches(&mut rules) {
+            text += &format!(" {}{name}", if *on { "" } else { "no-" });
+        }
+        let ending = ENDINGS.iter().find(|(_, e)| *e == self.rules.ending);
+        text += &format!(" {}\"]\n", ending.expect("every ending is named").0);
+        if let Some(seed) = self.seed {
+            text += &format!("[Seed \"{seed}\"]\n");
+        }
+        let dim = self.board.metrics().dim;
+        if dim != Metrics::DEFAULT_DIM {
+            text += &format!("[Cell \"{dim}\"]\n");
+        }
+        text += "\n";
+
+        let mut state = GameState::new(&self.board).with_rules(self.rules);
+        for (index, ply) in self.plies.iter().enumerate() {
+            let ranked = state.ranking().len();
+            for mv in ply.moves() {
+                state = state.apply(mv)?;
+            }
+            text += &format!("{}. {ply}", index + 1);
+            let finished: Vec<String> = state
+                .ranking()
+                .iter()
+                .enumerate()
+                .skip(ranked)
+                .map(|(place, seat)| match place {
+                    0 => format!("seat {seat} wins"),
+                    place => format!("seat {seat} finishes {}", place + 1),
+                })
+                .collect();
+            if !finished.is_empty() {
+                text += &format!(" ; {}", finished.join(", "));
+            }
+            text += "\n";
+        }
+
+        let layout = toml::to_string(self.board.layout())?;
+        if layout != toml::to_string(&Layout::classic(self.board.shape()))? {
+            text += "\n; The board layout\n";
+            for line in layout.lines() {
+                text += &format!("% {line}\n");
+            }
+        }
+        Ok(text)
+    }
+
+    /// Reads a record in game notation, as written by
+    /// [`to_text`](Self::to_text) or by hand. The parser is lenient:
+    /// header names are matched ignoring case and unknown ones skipped,
+    /// missing headers take a four player square board with the classic
+    /// rules, ply numbers are optional and comments run from `;` to the
+    /// end of the line or between braces. Only which plane moved with
+    /// each throw is read, the rest of a ply is rewritten from the rules.
+    pub fn from_text(text: &str) -> Result<Self> {
+        let mut shape = None;
+        let mut players = None;
+        let mut rules = RuleSet::default();
+        let mut seed = None;
+        let mut metrics = Metrics::default();
+        let mut layout = String::new();
+        let mut plies = vec![];
+        // Layout lines are taken out first, braces being TOML too.
+        let mut body = String::new();
+        for line in text.lines() {
+            if let Some(toml) = line.trim_start().strip_prefix('%') {
+                layout += toml.strip_prefix(' ').unwrap_or(toml);
+                layout += "\n";
+            } else {
+                body += line;
+            }
+            body += "\n";
+        }
+        for (number, line) in strip_braces(&body).lines().enumerate() {
+            let context = || format!("line {}: {line:?}", number + 1);
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header.trim_end_matches(']');
+                let (name, value) = header
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((header, ""));
+                let value = value.trim().trim_matches('"');
+                match name.to_lowercase().as_str() {
+                    "board" => {
+                        shape = Some(
+                            value
+                                .to_lowercase()
+                                .parse::<BoardShape>()
+                                .map_err(|error| anyhow!(error))
+                                .with_context(context)?,
+                        )
+                    }
+                    "players" => {
+                        players = Some(value.parse().with_context(context)?)
+                    }
+                    "rules" => {
+                        rules = parse_rules(value).with_context(context)?
+                    }
+                    "seed" => seed = Some(value.parse().with_context(context)?),
+                    "cell" => {
+                        metrics =
+                            Metrics::new(value.parse().with_context(context)?)
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            let mut ply = line;
+            if let Some((number, rest)) = line.split_once('.')
+                && number.trim().chars().all(|c| c.is_ascii_digit())
+            {
+                ply = rest.trim();
+            }
+            // Results and other markers ending a game in PGN.
+            if ply.is_empty() || ply == "*" {
+                continue;
+            }
+            plies.push(ply.parse::<Ply>().with_context(context)?);
+        }
+
+        let players = players.unwrap_or(4);
+        let shape = shape.unwrap_or(BoardShape::for_players(players));
+        ensure!(
+            shape.players().contains(&players),
+            "a {} board seats {:?} players, not {players}",
+            shape.name(),
+            shape.players()
+        );
+        let layout = match layout.trim() {
+            "" => Layout::classic(shape),
+            toml => Layout::parse(toml).context("invalid board layout")?,
+        };
+        let board =
+            Board::with_shape(shape, players, layout).with_metrics(metrics);
+        Self::rewrite(board, rules, seed, &plies)
+    }
+
+    /// The record of `plies` as the rules play them.
+    fn rewrite(
+        board: Board,
+        rules: RuleSet,
+        seed: Option<u64>,
+        plies: &[Ply],
+    ) -> Result<Self> {
+        let mut record = Self {
+            board,
+            rules,
+            seed,
+            plies: vec![],
+            checksums: vec![],
+            resignations: vec![],
+        };
+        let mut state = GameState::new(&record.board).with_rules(rules);
+        for (index, ply) in plies.iter().enumerate() {
+            ensure!(
+                ply.seat == state.turn().seat,
+                "ply {} {ply}: seat {} is to move",
+                index + 1,
+                state.turn().seat
+            );
+            for mv in ply.moves() {
+                let next = state
+                    .apply(mv)
+                    .with_context(|| format!("ply {} {ply}", index + 1))?;
+                record.push(&state, mv, &next);
+                state = next;
+            }
+            let pending = state.phase() == Phase::Move;
+            match ply.action {
+                Action::Penalty if pending => {
+                    bail!("ply {} {ply}: the throw is not a penalty", index + 1)
+                }
+                Action::Pending if index + 1 < plies.len() => {
+                    bail!("ply {} {ply}: only the last ply waits", index + 1)
+                }
+                _ => {}
+            }
+        }
+        Ok(record)
+    }
+}
+
+/// A rule set from the `Rules` header: the dice as `NdF`, switches by
+/// name or prefixed with `no-` to turn them off, and the ending, any
+/// left out taking the classic rules.
+fn parse_rules(text: &str) -> Result<RuleSet> {
+    let mut rules = RuleSet::default();
+    for word in text.split_whitespace() {
+        let word = word.to_lowercase();
+        if let Some((count, faces)) = word.split_once('d')
+            && let (Ok(count), Ok(faces)) = (count.parse(), faces.parse())
+        {
+            rules.dice = DiceSpec::new(count, faces)?;
+            continue;
+        }
+        if let Some(&(_, ending)) =
+            ENDINGS.iter().find(|(name, _)| *name == word)
+        {
+            rules.ending = ending;
+            continue;
+        }
+        let (name, on) = match word.strip_prefix("no-") {
+            Some(name) => (name, false),
+            None => (word.as_str(), true),
+        };
+        let mut switches = switches(&mut rules);
+        let Some((_, switch)) =
+            switches.iter_mut().find(|(known, _)| *known == name)
+        else {
+            bail!("unknown rule {word:?}");
+        };
+        **switch = on;
+    }
+    Ok(rules)
+}
+
+/// `text` without comments between braces, keeping their line breaks so
+/// lines keep their numbers.
+fn strip_braces(text: &str) -> String {
+    let mut depth = 0usize;
+    text.chars()
+        .filter(|&c| {
+            match c {
+                '{' => depth += 1,
+                '}' if depth > 0 => {
+                    depth -= 1;
+                    return false;
+                }
+                '\n' => return true,
+                _ => {}
+            }
+            depth == 0
+        })
+        .collect()
+}
@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{Event, GameState, Move, Phase, PlaneId, Position};
+use crate::track::CellId;
+
+/// A change of the game, raised in the order it happens so renderers,
+/// sound, networking and statistics all follow the same stream.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameEvent {
+    /// The player in `seat` threw the dice.
+    DiceRolled { seat: usize, total: u8 },
+    /// `planes` of `seat` launched or advanced together from `from` to
+    /// `to`, before any jump or flight.
+    PieceMoved {
+        seat: usize,
+        planes: Vec<usize>,
+        from: Position,
+        to: Position,
+    },
+    /// The player in `seat` could not use the throw.
+    Passed { seat: usize },
+    /// The player in `seat` threw the top throw three times in a row.
+    Penalized { seat: usize },
+    /// A plane of `seat` sent the opponent `planes` on `cell` back to
+    /// their hangars.
+    Captured {
+        seat: usize,
+        cell: CellId,
+        planes: Vec<PlaneId>,
+    },
+    /// `planes` of `seat` jumped ahead to the next cell of their color.
+    Jumped {
+        seat: usize,
+        planes: Vec<usize>,
+        from: CellId,
+        to: CellId,
+    },
+    /// `planes` of `seat` flew along a shortcut.
+    Flew {
+        seat: usize,
+        planes: Vec<usize>,
+        from: CellId,
+        to: CellId,
+    },
+    /// The player in `seat` finished in `place`, 1 for the winner, having
+    /// brought every plane home or ranked as the game ended.
+    Finished { seat: usize, place: usize },
+    /// The player in `seat` is to throw, again if `extra`.
+    TurnChanged { seat: usize, extra: bool },
+    /// The player in `seat` resigned.
+    Resigned { seat: usize },
+}
+
+impl GameEvent {
+    /// What `mv` changed going from `before` to `after`.
+    pub fn between(
+        before: &GameState,
+        mv: Move,
+        after: &GameState,
+    ) -> Vec<GameEvent> {
+        let seat = before.turn().seat;
+        let mut events = vec![];
+        match mv {
+            Move::Roll(total) => {
+                events.push(GameEvent::DiceRolled { seat, total });
+            }
+            Move::Launch { plane } | Move::Advance { plane } => {
+                let mover = after
+                    .sides()
+                    .iter()
+                    .find(|side| side.seat == seat)
+                    .expect("the mover stays seated");
+                // Where the move ended before jumping or flying on.
+                let to = after
+                    .events()
+                    .iter()
+                    .find_map(|event| match event {
+                        Event::Jump { from, .. } | Event::Fly { from, .. } => {
+                            Some(Position::Track(*from))
+                        }
+                        _ => None,
+                    })
+                    .unwrap_or(mover.planes[plane]);
+                events.push(GameEvent::PieceMoved {
+                    seat,
+                    planes: before.movers(plane),
+                    from: before.turn().planes[plane],
+                    to,
+                });
+            }
+            Move::Pass => events.push(GameEvent::Passed { seat }),
+        }
+        for event in after.events() {
+            events.push(match event.clone() {
+                Event::Penalty { seat } => GameEvent::Penalized { seat },
+                Event::Capture { seat, cell, planes } => {
+                    GameEvent::Captured { seat, cell, planes }
+                }
+                Event::Jump {
+                    seat,
+                    planes,
+                    from,
+                    to,
+                } => GameEvent::Jumped {
+                    seat,
+                    planes,
+                    from,
+                    to,
+                },
+                Event::Fly {
+                    seat,
+                    planes,
+                    from,
+                    to,
+                } => GameEvent::Flew {
+                    seat,
+                    planes,
+                    from,
+                    to,
+                },
+                Event::ExtraTurn { .. } => continue,
+            });
+        }
+        events.extend(Self::finished(before, after));
+        if after.phase() == Phase::Roll {
+            let next = after.turn().seat;
+            events.push(GameEvent::TurnChanged {
+                seat: next,
+                extra: next == seat,
+            });
+        }
+        events
+    }
+
+    /// What the player in `seat` resigning changed going from `before` to
+    /// `after`.
+    pub fn resigning(
+        before: &GameState,
+        seat: usize,
+        after: &GameState,
+    ) -> Vec<GameEvent> {
+        let mut events = vec![GameEvent::Resigned { seat }];
+        events.extend(Self::finished(before, after));
+        let next = after.turn().seat;
+        if after.phase() == Phase::Roll && next != before.turn().seat {
+            events.push(GameEvent::TurnChanged {
+                seat: next,
+                extra: false,
+            });
+        }
+        events
+    }
+
+    /// Players ranked going from `before` to `after`.
+    fn finished(before: &GameState, after: &GameState) -> Vec<GameEvent> {
+        let ranked = before.ranking().len();
+        let places = after.ranking().iter().enumerate().skip(ranked);
+        places
+            .map(|(index, &seat)| GameEvent::Finished {
+                seat,
+                place: index + 1,
+            })
+            .collect()
+    }
+}
+
+/// Something told of every change of a game it is registered with, see
+/// [`Game::observe`](crate::game::Game::observe).
+pub trait Observer {
+    /// Called for each event of a move, with the state the move led to.
+    fn notify(&mut self, event: &GameEvent, state: &GameState);
+}
+
+impl<F: FnMut(&GameEvent, &GameState)> Observer for F {
+    fn notify(&mut self, event: &GameEvent, state: &GameState) {
+        self(event, state)
+    }
+}
+
+/// Shared observers stay readable by whoever registered them.
+impl<O: Observer> Observer for Rc<RefCell<O>> {
+    fn notify(&mut self, event: &GameEvent, state: &GameState) {
+        self.borrow_mut().notify(event, state)
+    }
+}
@@ -0,0 +1,274 @@
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use anyhow::{Context, Result, bail, ensure};
+use rand::rngs::StdRng;
+use sha2::{Digest, Sha256};
+
+use crate::board::Board;
+use crate::dice::Dice;
+use crate::game::Game;
+use crate::protocol::{
+    self, ChatLine, ClientRequest, PeerMessage, ServerEvent, ServerResponse,
+    VERSION,
+};
+use crate::rules::{GameState, Move, Phase, RuleSet};
+
+/// A game between two friends playing each other directly, without a
+/// server. One hosts and the other connects, then both keep the whole
+/// game, play it in lockstep and check every move of the other against
+/// the rules and the dice.
+///
+/// The dice are thrown from a seed both make together: each commits to a
+/// secret of its own by its hash and reveals it only once the other has
+/// committed, so neither can choose the seed.
+pub struct Peer {
+    link: Link,
+    game: Game,
+    dice: Dice<StdRng>,
+    seat: usize,
+    /// Seat and name of both players.
+    players: Vec<(usize, String)>,
+}
+
+impl Peer {
+    /// Waits on `address` for a friend to connect and plays them as
+    /// `name`, on a board for two under `rules`, taking the first seat.
+    pub fn host(
+        address: impl ToSocketAddrs,
+        name: &str,
+        rules: RuleSet,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let (stream, _) = listener.accept()?;
+        let mut peer = Link::new(stream)?;
+        let guest = match peer.receive()? {
+            PeerMessage::Request(ClientRequest::Hello { version, name }) => {
+                if version != VERSION {
+                    let reason = format!(
+                        "the host speaks protocol {VERSION}, not {version}"
+                    );
+                    let rejected =
+                        ServerResponse::Rejected { reason, seq: None };
+                    peer.send(&PeerMessage::Response(rejected))?;
+                    bail!(
+                        "the friend speaks protocol {version}, not {VERSION}"
+                    );
+                }
+                name
+            }
+            _ => bail!("the friend did not say hello"),
+        };
+        let board = Board::new(2);
+        let state = GameState::new(&board);
+        let seats: Vec<usize> =
+            state.sides().iter().map(|side| side.seat).collect();
+        let players = vec![(seats[0], name.to_string()), (seats[1], guest)];
+        peer.send(&PeerMessage::Response(ServerResponse::Welcome {
+            room: 0,
+            seat: seats[1],
+            token: String::new(),
+            board: Box::new(board.clone()),
+            rules,
+            players: players.clone(),
+            spectators: 0,
+        }))?;
+        let seed = peer.agree(true)?;
+        Self::start(peer, board, rules, seed, seats[0], players)
+    }
+
+    /// Connects to the friend hosting at `address` and plays them as
+    /// `name`.
+    pub fn join(address: impl ToSocketAddrs, name: &str) -> Result<Self> {
+        let stream =
+            TcpStream::connect(address).context("failed to connect")?;
+        let mut peer = Link::new(stream)?;
+        peer.send(&PeerMessage::Request(ClientRequest::Hello {
+            version: VERSION,
+            name: name.into(),
+        }))?;
+        let (seat, board, rules, players) = match peer.receive()? {
+            PeerMessage::Response(ServerResponse::Welcome {
+                seat,
+                board,
+                rules,
+                players,
+                ..
+            }) => (seat, *board, rules, players),
+            PeerMessage::Response(ServerResponse::Rejected {
+                reason, ..
+            }) => bail!("{reason}"),
+            _ => bail!("the host did not answer the hello"),
+        };
+        let seed = peer.agree(false)?;
+        Self::start(peer, board, rules, seed, seat, players)
+    }
+
+    fn start(
+        peer: Link,
+        board: Board,
+        rules: RuleSet,
+        seed: u64,
+        seat: usize,
+        players: Vec<(usize, String)>,
+    ) -> Result<Self> {
+        Ok(Self {
+            link: peer,
+            dice: Dice::seeded(rules.dice, seed)?,
+            game: Game::new(board, rules, Some(seed)),
+            seat,
+            players,
+        })
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    /// Seat and name of both players.
+    pub fn players(&self) -> &[(usize, String)] {
+        &self.players
+    }
+
+    /// Whether the game waits for this side to play.
+    pub fn ours(&self) -> bool {
+        let state = self.game.state();
+        state.phase() != Phase::Finished && state.turn().seat == self.seat
+    }
+
+    /// Throws the dice for this side, telling the friend.
+    pub fn roll(&mut self) -> Result<Move> {
+        ensure!(
+            self.ours() && self.game.state().phase() == Phase::Roll,
+            "not ours to roll"
+        );
+        let mv = Move::Roll(self.dice.roll().total());
+        self.commit(mv)?;
+        Ok(mv)
+    }
+
+    /// Plays `mv` for this side, telling the friend.
+    pub fn play(&mut self, mv: Move) -> Result<()> {
+        ensure!(self.ours(), "not our turn");
+        ensure!(!matches!(mv, Move::Roll(_)), "throws are made with roll");
+        self.commit(mv)
+    }
+
+    /// Says `text` to the friend.
+    pub fn chat(&mut self, text: &str) -> Result<()> {
+        let from = self.players.iter().find(|(seat, _)| *seat == self.seat);
+        let line = ChatLine {
+            from: from.map(|(_, name)| name.clone()).unwrap_or_default(),
+            seat: Some(self.seat),
+            text: text.into(),
+        };
+        self.link.send(&PeerMessage::Event(ServerEvent::Chat(line)))
+    }
+
+    /// Waits for the next move or chat message of the friend. Moves are
+    /// checked against the rules, throws against the shared dice, and
+    /// played.
+    pub fn wait(&mut self) -> Result<ServerEvent> {
+        let PeerMessage::Event(event) = self.link.receive()? else {
+            bail!("the friend sent something else than a move");
+        };
+        match event {
+            ServerEvent::Played {
+                seat, mv, checksum, ..
+            } => {
+                let state = self.game.state();
+                ensure!(
+                    seat != self.seat
+                        && state.phase() != Phase::Finished
+                        && state.turn().seat == seat,
+                    "the friend played out of turn"
+                );
+                if let Move::Roll(total) = mv {
+                    ensure!(
+                        total == self.dice.roll().total(),
+                        "the friend threw dice of their own"
+                    );
+                }
+                let game = &mut self.game;
+                game.play(mv).context("the friend played an illegal move")?;
+                ensure!(
+                    game.state().checksum() == checksum,
+                    "out of step with the friend"
+                );
+            }
+            ServerEvent::Chat(_) => {}
+            _ => bail!("the friend sent something else than a move"),
+        }
+        Ok(event)
+    }
+
+    /// Plays `mv` and tells the friend.
+    fn commit(&mut self, mv: Move) -> Result<()> {
+        self.game.play(mv)?;
+        let played = ServerEvent::Played {
+            seat: self.seat,
+            mv,
+            checksum: self.game.state().checksum(),
+            seq: None,
+        };
+        self.link.send(&PeerMessage::Event(played))
+    }
+}
+
+/// The connection to the friend.
+struct Link {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Link {
+    fn new(stream: TcpStream) -> Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    fn send(&mut self, message: &PeerMessage) -> Result<()> {
+        protocol::write_frame(&mut self.writer, message)
+    }
+
+    fn receive(&mut self) -> Result<PeerMessage> {
+        let message = protocol::read_frame(&mut self.reader)?;
+        message.context("the friend hung up")
+    }
+
+    /// Makes the seed of the dice with the friend: both commit to a secret
+    /// by its hash, then reveal it, and the seed comes from the hash of
+    /// both secrets, the host's first.
+    fn agree(&mut self, hosting: bool) -> Result<u64> {
+        let secret: [u8; 32] = rand::random();
+        let hash = Sha256::digest(secret).into();
+        self.send(&PeerMessage::Commit { hash })?;
+        let PeerMessage::Commit { hash: committed } = self.receive()? else {
+            bail!("the friend did not commit to a secret");
+        };
+        self.send(&PeerMessage::Reveal { secret })?;
+        let PeerMessage::Reveal { secret: theirs } = self.receive()? else {
+            bail!("the friend did not reveal their secret");
+        };
+        let revealed: [u8; 32] = Sha256::digest(theirs).into();
+        ensure!(
+            revealed == committed,
+            "the friend revealed another secret than the one committed to"
+        );
+        let (first, second) = if hosting {
+            (secret, theirs)
+        } else {
+            (theirs, secret)
+        };
+        let seed = Sha256::new().chain_update(first).chain_update(second);
+        let seed = seed.finalize();
+        let bytes = seed[..8].try_into().expect("hashes are 32 bytes");
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
@@ -0,0 +1,290 @@
+use std::io::{self, Read, Write};
+
+use anyhow::{Context, Result, bail, ensure};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::record::GameRecord;
+use crate::rules::{GameState, Move, Phase, RuleSet};
+
+/// Version of the protocol spoken here. Clients say which one they speak
+/// in their hello and the server turns away those speaking another.
+pub const VERSION: u32 = 5;
+
+/// Port the server listens on unless told otherwise.
+pub const PORT: u16 = 7878;
+
+/// Largest frame either side reads, far more than any message takes.
+pub const MAX_FRAME: usize = 1 << 20;
+
+/// Longest chat message the server passes on, in characters.
+pub const MAX_CHAT: usize = 500;
+
+/// What a client asks of the server.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientRequest {
+    /// Enters the lobby as `name`, first thing after connecting, speaking
+    /// protocol `version`.
+    Hello { version: u32, name: String },
+    /// Asks for the rooms of the lobby again.
+    ListRooms,
+    /// Opens a room for `players` under `rules` and takes its first seat.
+    /// Only those knowing the password can join a room with one.
+    CreateRoom {
+        name: String,
+        players: usize,
+        #[serde(default)]
+        rules: RuleSet,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Takes a seat in room `room`.
+    JoinRoom {
+        room: u64,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Takes back the seat `token` was given for after dropping out of
+    /// its game.
+    Resume { token: String },
+    /// Follows the game of room `room` without a seat, told everything
+    /// its players are but playing nothing.
+    Watch {
+        room: u64,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Goes back to the lobby, closing the room if its game started and
+    /// the client sits in it.
+    LeaveRoom,
+    /// Says `text` to everyone in the room, players and spectators.
+    Chat { text: String },
+    /// Asks the server to throw the dice for the player's turn. The
+    /// sequence number `seq`, if any, comes back with the answer.
+    Roll {
+        #[serde(default)]
+        seq: Option<u64>,
+    },
+    /// Plays what the player does with the throw. Throws are the server's
+    /// to make and rejected here.
+    Play {
+        mv: Move,
+        #[serde(default)]
+        seq: Option<u64>,
+    },
+}
+
+/// How the server answers a request, to the client that made it alone.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerResponse {
+    /// Answers the hello with the protocol spoken and the rooms of the
+    /// lobby.
+    Hello { version: u32, rooms: Vec<RoomInfo> },
+    /// Answers asking for the rooms of the lobby.
+    Rooms { rooms: Vec<RoomInfo> },
+    /// Answers joining a room with the seat taken and the game played.
+    Welcome {
+        room: u64,
+        seat: usize,
+        /// Secret to resume the seat with after dropping out.
+        token: String,
+        board: Box<Board>,
+        rules: RuleSet,
+        /// Seat and name of every player seated so far, the new one
+        /// included.
+        players: Vec<(usize, String)>,
+        spectators: usize,
+    },
+    /// Answers resuming a seat with the game as played so far.
+    Resumed {
+        room: u64,
+        seat: usize,
+        token: String,
+        record: Box<GameRecord>,
+        state: Box<GameState>,
+        /// Seat and name of every player seated.
+        players: Vec<(usize, String)>,
+        spectators: usize,
+    },
+    /// Answers watching a room with the game as played so far.
+    Watching {
+        room: u64,
+        record: Box<GameRecord>,
+        state: Box<GameState>,
+        /// Seat and name of every player seated.
+        players: Vec<(usize, String)>,
+        spectators: usize,
+    },
+    /// The request was refused, the one numbered `seq` if it was.
+    Rejected {
+        reason: String,
+        #[serde(default)]
+        seq: Option<u64>,
+    },
+}
+
+/// What the server tells clients unasked, as the lobby and the rooms
+/// change.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    /// The rooms of the lobby changed, told to those in it.
+    Lobby { rooms: Vec<RoomInfo> },
+    /// Another player took a seat in the room.
+    Joined { seat: usize, name: String },
+    /// A player left the room.
+    Left { seat: usize },
+    /// The player in `seat` dropped out of the game and has `grace`
+    /// seconds to resume their seat.
+    Dropped { seat: usize, grace: u64 },
+    /// The player in `seat` resumed their seat.
+    Rejoined { seat: usize },
+    /// The grace of the player in `seat` ran out and the computer player
+    /// called `ai` plays for them from now on.
+    Substituted { seat: usize, ai: String },
+    /// Someone started or stopped watching the room, `count` watching
+    /// now.
+    Spectators { count: usize },
+    /// Someone in the room said something.
+    Chat(ChatLine),
+    /// Every seat is taken and the first player is to roll.
+    Started,
+    /// The player in `seat` played `mv`, throws included, leading to a
+    /// state of `checksum`. Moves asked for with a sequence number carry
+    /// it back, for the player to match with what they predicted.
+    Played {
+        seat: usize,
+        mv: Move,
+        checksum: u64,
+        #[serde(default)]
+        seq: Option<u64>,
+    },
+    /// The player in `seat` is to roll or to move.
+    Turn { seat: usize, phase: Phase },
+    /// The game is over, the seats in the order they finished. The room
+    /// closes and its players are back in the lobby.
+    Over { ranking: Vec<usize> },
+    /// The room closed before its game was over, its players back in the
+    /// lobby.
+    Closed { reason: String },
+}
+
+/// A chat message as the room reads it.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ChatLine {
+    /// Name of who said it.
+    pub from: String,
+    /// Seat of who said it, none for spectators.
+    pub seat: Option<usize>,
+    pub text: String,
+}
+
+/// Anything the server sends, told apart by its `kind`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Response(ServerResponse),
+    Event(ServerEvent),
+}
+
+/// What two peers playing without a server tell each other: the requests
+/// and answers of clients and servers, the events of the game, and the
+/// commitments making the seed of the dice.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PeerMessage {
+    Request(ClientRequest),
+    Response(ServerResponse),
+    Event(ServerEvent),
+    /// Hash of the secret the peer adds to the seed of the dice.
+    Commit {
+        hash: [u8; 32],
+    },
+    /// The secret committed to, once both peers did.
+    Reveal {
+        secret: [u8; 32],
+    },
+}
+
+/// A room of the lobby as its clients see it.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RoomInfo {
+    pub id: u64,
+    pub name: String,
+    /// Players the room seats.
+    pub players: usize,
+    /// Seat and name of every player seated.
+    pub seated: Vec<(usize, String)>,
+    pub rules: RuleSet,
+    /// Whether joining takes a password.
+    pub locked: bool,
+    /// Whether every seat was taken and the game is on.
+    pub started: bool,
+    /// Connections watching the room.
+    pub spectators: usize,
+}
+
+impl From<ServerResponse> for ServerMessage {
+    fn from(response: ServerResponse) -> Self {
+        ServerMessage::Response(response)
+    }
+}
+
+impl From<ServerEvent> for ServerMessage {
+    fn from(event: ServerEvent) -> Self {
+        ServerMessage::Event(event)
+    }
+}
+
+/// `message` as a frame: the length of its JSON in four bytes, big-endian,
+/// then the JSON. Over WebSockets the frames of the socket stand in for
+/// these and carry the JSON alone.
+pub fn frame(message: &impl Serialize) -> Result<Vec<u8>> {
+    let json = serde_json::to_vec(message)?;
+    ensure!(json.len() <= MAX_FRAME, "message too long to send");
+    let mut frame = Vec::with_capacity(4 + json.len());
+    frame.extend_from_slice(&(json.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&json);
+    Ok(frame)
+}
+
+/// Writes `message` to `writer` as a [`frame`].
+pub fn write_frame(
+    writer: &mut impl Write,
+    message: &impl Serialize,
+) -> Result<()> {
+    writer.write_all(&frame(message)?)?;
+    Ok(())
+}
+
+/// Reads the next frame of `reader` as a message, none if the stream
+/// ended between frames.
+pub fn read_frame<T: DeserializeOwned>(
+    reader: &mut impl Read,
+) -> Result<Option<T>> {
+    let mut header = [0; 4];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+            return Ok(None);
+        }
+        Err(error) => return Err(error.into()),
+    }
+    let mut json = vec![0; length(header)?];
+    reader.read_exact(&mut json).context("frame cut short")?;
+    let message = serde_json::from_slice(&json).context("malformed message")?;
+    Ok(Some(message))
+}
+
+/// Length of the frame starting with `header`, refused past [`MAX_FRAME`]
+/// as the stream is likely not speaking the protocol at all.
+pub(crate) fn length(header: [u8; 4]) -> Result<usize> {
+    let length = u32::from_be_bytes(header) as usize;
+    if length > MAX_FRAME {
+        bail!("frame of {length} bytes is too long");
+    }
+    Ok(length)
+}
@@ -0,0 +1,483 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::dice::Dice;
+use crate::rules::{Event, GameState, Move, Phase, Position, RuleSet};
+
+/// Where a plane is, in notation: `H` for its hangar, `G` for the goal
+/// and the track cell index otherwise.
+struct Spot(Position);
+
+impl fmt::Display for Spot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Position::Hangar => write!(f, "H"),
+            Position::Track(cell) => write!(f, "{cell}"),
+            Position::Home => write!(f, "G"),
+        }
+    }
+}
+
+impl FromStr for Spot {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Ok(Spot(match text {
+            "H" => Position::Hangar,
+            "G" => Position::Home,
+            cell => Position::Track(
+                cell.parse()
+                    .with_context(|| format!("invalid cell {cell:?}"))?,
+            ),
+        }))
+    }
+}
+
+/// A plane launched or advanced with a throw.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Flight {
+    pub plane: usize,
+    pub from: Position,
+    /// Where the plane stopped, after any jump and flight.
+    pub to: Position,
+    pub jump: bool,
+    pub fly: bool,
+    pub capture: bool,
+}
+
+/// What a player did with a throw.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+    Flight(Flight),
+    /// No plane could use the throw.
+    Pass,
+    /// The throw was the third top throw in a row and lost the turn.
+    Penalty,
+    /// Not used yet, last in a record of a game in progress or left by a
+    /// player who resigned.
+    Pending,
+}
+
+/// One throw and what its player did with it, written in canonical
+/// notation as `seat:dice action`. The action is the plane moved followed
+/// by `from-to` and the flags `j` for a jump, `f` for a flight and `x`
+/// for a capture, or `-` for a pass, `!` for a penalty and `?` while
+/// pending. `0:6 2 H-1` launches plane 2 of seat 0, `0:4 2 1-9jx` moves
+/// it on, jumping and capturing.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Ply {
+    pub seat: usize,
+    pub dice: u8,
+    pub action: Action,
+}
+
+impl Ply {
+    /// The moves taking the ply, the throw first.
+    pub fn moves(&self) -> Vec<Move> {
+        let action = match self.action {
+            Action::Flight(flight) if flight.from == Position::Hangar => {
+                Move::Launch {
+                    plane: flight.plane,
+                }
+            }
+            Action::Flight(flight) => Move::Advance {
+                plane: flight.plane,
+            },
+            Action::Pass => Move::Pass,
+            Action::Penalty | Action::Pending => {
+                return vec![Move::Roll(self.dice)];
+            }
+        };
+        vec![Move::Roll(self.dice), action]
+    }
+
+    /// The ply `mv` takes after the throw leading to `before`, ending in
+    /// `after`.
+    fn describe(before: &GameState, mv: Move, after: &GameState) -> Self {
+        let seat = before.turn().seat;
+        let dice = before.dice().expect("moves follow a throw");
+        let action = match mv {
+            Move::Launch { plane } | Move::Advance { plane } => {
+                let side = after
+                    .sides()
+                    .iter()
+                    .find(|side| side.seat == seat)
+                    .expect("the mover stays seated");
+                let happened = |matches: fn(&Event) -> bool| {
+                    after.events().iter().any(matches)
+                };
+                Action::Flight(Flight {
+                    plane,
+                    from: before.turn().planes[plane],
+                    to: side.planes[plane],
+                    jump: happened(|e| matches!(e, Event::Jump { .. })),
+                    fly: happened(|e| matches!(e, Event::Fly { .. })),
+                    capture: happened(|e| matches!(e, Event::Capture { .. })),
+                })
+            }
+            Move::Pass => Action::Pass,
+            Move::Roll(_) => unreachable!("throws start plies"),
+        };
+        Self { seat, dice, action }
+    }
+}
+
+impl fmt::Display for Ply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{} ", self.seat, self.dice)?;
+        match self.action {
+            Action::Flight(flight) => {
+                write!(
+                    f,
+                    "{} {}-{}",
+                    flight.plane,
+                    Spot(flight.from),
+                    Spot(flight.to)
+                )?;
+                for (flag, set) in [
+                    ('j', flight.jump),
+                    ('f', flight.fly),
+                    ('x', flight.capture),
+                ] {
+                    if set {
+                        write!(f, "{flag}")?;
+                    }
+                }
+                Ok(())
+            }
+            Action::Pass => write!(f, "-"),
+            Action::Penalty => write!(f, "!"),
+            Action::Pending => write!(f, "?"),
+        }
+    }
+}
+
+impl FromStr for Ply {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let invalid = || format!("invalid ply {text:?}");
+        let mut tokens = text.split_whitespace();
+        let (seat, dice) = tokens
+            .next()
+            .and_then(|throw| throw.split_once(':'))
+            .ok_or_else(|| anyhow!("expected seat:dice"))
+            .with_context(invalid)?;
+        let seat = seat.parse().with_context(invalid)?;
+        let dice = dice.parse().with_context(invalid)?;
+        let action = match tokens.next() {
+            Some("-") => Action::Pass,
+            Some("!") => Action::Penalty,
+            Some("?") => Action::Pending,
+            Some(plane) => {
+                let plane = plane.parse().with_context(invalid)?;
+                let flight = tokens.next().with_context(invalid)?;
+                let (route, flags) = flight.split_at(
+                    flight.find(|c| "jfx".contains(c)).unwrap_or(flight.len()),
+                );
+                let (from, to) = route.split_once('-').with_context(invalid)?;
+                ensure!(
+                    flags.chars().all(|flag| "jfx".contains(flag)),
+                    "unknown flags {flags:?} in ply {text:?}"
+                );
+                Action::Flight(Flight {
+                    plane,
+                    from: from.parse::<Spot>().with_context(invalid)?.0,
+                    to: to.parse::<Spot>().with_context(invalid)?.0,
+                    jump: flags.contains('j'),
+                    fly: flags.contains('f'),
+                    capture: flags.contains('x'),
+                })
+            }
+            None => bail!("missing action in ply {text:?}"),
+        };
+        ensure!(tokens.next().is_none(), "trailing text in ply {text:?}");
+        Ok(Self { seat, dice, action })
+    }
+}
+
+impl From<Ply> for String {
+    fn from(ply: Ply) -> Self {
+        ply.to_string()
+    }
+}
+
+impl TryFrom<String> for Ply {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        text.parse()
+    }
+}
+
+/// A player leaving the game before it is over.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Resignation {
+    pub seat: usize,
+    /// Moves of the record played before the player resigned.
+    pub after: usize,
+}
+
+/// Everything needed to play a game again: the board, the rules, the
+/// seed of the dice, every ply in notation and who resigned when. Records
+/// are TOML files.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub board: Board,
+    #[serde(default)]
+    pub rules: RuleSet,
+    /// Seed the dice were thrown from, when thrown by [`Dice::seeded`].
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub plies: Vec<Ply>,
+    /// [`GameState::checksum`] after each ply, when recorded.
+    #[serde(default, with = "hex", skip_serializing_if = "Vec::is_empty")]
+    pub checksums: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resignations: Vec<Resignation>,
+}
+
+impl GameRecord {
+    /// Records the game played by `moves` from the start.
+    pub fn from_moves(
+        board: Board,
+        rules: RuleSet,
+        seed: Option<u64>,
+        moves: &[Move],
+    ) -> Result<Self> {
+        let mut record = Self {
+            board,
+            rules,
+            seed,
+            plies: vec![],
+            checksums: vec![],
+            resignations: vec![],
+        };
+        let mut state = GameState::new(&record.board).with_rules(rules);
+        for &mv in moves {
+            let next = state.apply(mv)?;
+            record.push(&state, mv, &next);
+            state = next;
+        }
+        Ok(record)
+    }
+
+    /// Notes `mv`, played from `state` to `next`, with the checksum of
+    /// `next`.
+    pub(crate) fn push(
+        &mut self,
+        state: &GameState,
+        mv: Move,
+        next: &GameState,
+    ) {
+        let seat = state.turn().seat;
+        match mv {
+            Move::Roll(dice) => {
+                self.plies.push(Ply {
+                    seat,
+                    dice,
+                    action: if next.phase() == Phase::Move {
+                        Action::Pending
+                    } else {
+                        Action::Penalty
+                    },
+                });
+                self.checksums.push(next.checksum());
+            }
+            mv => {
+                let ply = self.plies.last_mut().expect("moves follow a throw");
+                *ply = Ply::describe(state, mv, next);
+                if let Some(checksum) = self.checksums.last_mut() {
+                    *checksum = next.checksum();
+                }
+            }
+        }
+    }
+
+    /// Notes the player in `seat` resigning, leading to `next`, whose
+    /// checksum becomes the last ply's.
+    pub(crate) fn resign(&mut self, seat: usize, next: &GameState) {
+        let after = self.moves().len();
+        self.resignations.push(Resignation { seat, after });
+        if let Some(checksum) = self.checksums.last_mut() {
+            *checksum = next.checksum();
+        }
+    }
+
+    /// Seats of the players resigning once `played` moves of the record
+    /// were played, in order.
+    pub fn resigning(&self, played: usize) -> impl Iterator<Item = usize> {
+        self.resignations
+            .iter()
+            .filter(move |resignation| resignation.after == played)
+            .map(|resignation| resignation.seat)
+    }
+
+    /// `state` after the players resigning once `played` moves of the
+    /// record were played resign.
+    pub fn resign_after(
+        &self,
+        state: GameState,
+        played: usize,
+    ) -> Result<GameState> {
+        self.resigning(played).try_fold(state, |state, seat| {
+            state.resign(seat).with_context(|| {
+                format!("seat {seat} resigning after {played} moves")
+            })
+        })
+    }
+
+    /// Takes the last ply off the record with its checksum.
+    pub(crate) fn pop(&mut self) -> Option<Ply> {
+        self.checksums.truncate(self.plies.len().saturating_sub(1));
+        self.plies.pop()
+    }
+
+    /// Every move of the game in order.
+    pub fn moves(&self) -> Vec<Move> {
+        self.plies.iter().flat_map(Ply::moves).collect()
+    }
+
+    /// Plays the record from the start, checking every ply against the
+    /// rules and the state after it against its checksum, if recorded.
+    pub fn replay(&self) -> Result<GameState> {
+        self.play(None)
+    }
+
+    /// Replays the record as [`replay`](Self::replay) does, also throwing
+    /// the dice again from the seed, so a game that played out any other
+    /// way, on a peer or an older engine, is caught at the first ply it
+    /// went differently.
+    pub fn verify(&self) -> Result<GameState> {
+        let Some(seed) = self.seed else {
+            bail!("the record has no seed to throw the dice from");
+        };
+        ensure!(
+            self.checksums.len() == self.plies.len(),
+            "the record has {} checksums for {} plies",
+            self.checksums.len(),
+            self.plies.len()
+        );
+        self.play(Some(Dice::seeded(self.rules.dice, seed)?))
+    }
+
+    fn play(&self, mut dice: Option<Dice<StdRng>>) -> Result<GameState> {
+        let moves = self.moves().len();
+        if let Some(late) = self.resignations.iter().find(|r| r.after > moves) {
+            bail!(
+                "seat {} resigns after {} moves of the {moves} played",
+                late.seat,
+                late.after
+            );
+        }
+        ensure!(
+            self.checksums.is_empty()
+                || self.checksums.len() == self.plies.len(),
+            "the record has {} checksums for {} plies",
+            self.checksums.len(),
+            self.plies.len()
+        );
+        let start = GameState::new(&self.board).with_rules(self.rules);
+        let mut state = self.resign_after(start, 0)?;
+        let mut played = 0;
+        for (index, ply) in self.plies.iter().enumerate() {
+            let context = || format!("ply {} {ply}", index + 1);
+            ensure!(
+                ply.seat == state.turn().seat,
+                "ply {} {ply}: seat {} is to move",
+                index + 1,
+                state.turn().seat
+            );
+            if let Some(dice) = &mut dice {
+                let total = dice.roll().total();
+                ensure!(
+                    ply.dice == total,
+                    "ply {} {ply}: the seeded dice throw {total}",
+                    index + 1
+                );
+            }
+            let mut moves = ply.moves().into_iter();
+            let roll = moves.next().expect("plies start with a throw");
+            let thrown = state.apply(roll).with_context(context)?;
+            let penalized = thrown.phase() != Phase::Move;
+            ensure!(
+                penalized == (ply.action == Action::Penalty),
+                "ply {} {ply}: the throw {} a penalty",
+                index + 1,
+                if penalized { "is" } else { "is not" }
+            );
+            played += 1;
+            let thrown = self.resign_after(thrown, played)?;
+            state = match moves.next() {
+                Some(mv) => {
+                    let next = thrown.apply(mv).with_context(context)?;
+                    let described = Ply::describe(&thrown, mv, &next);
+                    ensure!(
+                        described == *ply,
+                        "ply {} {ply}: the rules play {described}",
+                        index + 1
+                    );
+                    played += 1;
+                    self.resign_after(next, played)?
+                }
+                None => thrown,
+            };
+            if let Some(&recorded) = self.checksums.get(index) {
+                let checksum = state.checksum();
+                ensure!(
+                    checksum == recorded,
+                    "ply {} {ply}: the state sums to {checksum:016x}, \
+                     recorded as {recorded:016x}",
+                    index + 1
+                );
+            }
+        }
+        Ok(state)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&text)
+            .with_context(|| format!("invalid game record {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+}
+
+/// Serde adapter writing checksums as hex strings, as TOML integers are
+/// signed and too small for them.
+mod hex {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        sums: &[u64],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(sums.iter().map(|sum| format!("{sum:016x}")))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u64>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|sum| u64::from_str_radix(sum, 16).map_err(D::Error::custom))
+            .collect()
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{Result, ensure};
+
+use crate::record::GameRecord;
+use crate::rules::{Event, GameState, Move};
+
+/// A move of a replay and the state it led to.
+#[derive(Clone)]
+pub struct Frame {
+    /// Turn the move belongs to, the index of its ply in the record.
+    pub turn: usize,
+    pub mv: Move,
+    pub state: GameState,
+}
+
+impl Frame {
+    /// What the move set off.
+    pub fn events(&self) -> &[Event] {
+        self.state.events()
+    }
+}
+
+/// Plays a record back a move at a time, yielding a [`Frame`] for each.
+/// Every throw is a turn of its own, so turns are the plies of the
+/// record.
+pub struct Replay<'a> {
+    record: &'a GameRecord,
+    moves: Vec<Move>,
+    /// Index into `moves` where each turn starts.
+    starts: Vec<usize>,
+    next: usize,
+    state: GameState,
+}
+
+impl<'a> Replay<'a> {
+    /// A replay of `record` from the start, which is checked first so
+    /// playing it back cannot fail.
+    pub fn new(record: &'a GameRecord) -> Result<Self> {
+        record.replay()?;
+        let mut starts = vec![];
+        let mut moves = vec![];
+        for ply in &record.plies {
+            starts.push(moves.len());
+            moves.extend(ply.moves());
+        }
+        Ok(Self {
+            record,
+            moves,
+            starts,
+            next: 0,
+            state: Self::start(record),
+        })
+    }
+
+    fn start(record: &GameRecord) -> GameState {
+        let state = GameState::new(&record.board).with_rules(record.rules);
+        record
+            .resign_after(state, 0)
+            .expect("the record was checked")
+    }
+
+    /// State after the moves played back so far.
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Number of turns in the record.
+    pub fn turns(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Turn the next move belongs to, [`turns`](Self::turns) at the end.
+    pub fn turn(&self) -> usize {
+        self.starts.partition_point(|&start| start <= self.next)
+            - usize::from(self.next < self.moves.len())
+    }
+
+    /// Moves to the start of `turn`, or to the end of the game for
+    /// [`turns`](Self::turns), replaying from the start to go back.
+    pub fn seek(&mut self, turn: usize) -> Result<()> {
+        ensure!(
+            turn <= self.turns(),
+            "turn {turn} is past the {} turns of the game",
+            self.turns()
+        );
+        let target = self.starts.get(turn).copied().unwrap_or(self.moves.len());
+        if target < self.next {
+            self.next = 0;
+            self.state = Self::start(self.record);
+        }
+        while self.next < target {
+            self.next();
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Replay<'_> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        let mv = *self.moves.get(self.next)?;
+        let turn = self.turn();
+        let next = self.state.apply(mv).expect("the record was checked");
+        self.next += 1;
+        self.state = self
+            .record
+            .resign_after(next, self.next)
+            .expect("the record was checked");
+        Some(Frame {
+            turn,
+            mv,
+            state: self.state.clone(),
+        })
+    }
+}
@@ -0,0 +1,867 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail, ensure};
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::dice::DiceSpec;
+use crate::track::{CellId, Spot, Track};
+
+/// Planes each player brings around the board.
+pub const PLANES: usize = 4;
+
+/// Where a plane is.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    /// Parked in a hangar of its home base.
+    Hangar,
+    /// On a cell of the track.
+    Track(CellId),
+    /// Landed on the goal at the end of its runway.
+    Home,
+}
+
+/// What the game waits for.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    /// The player to move rolls the die.
+    Roll,
+    /// The player to move picks what to do with the roll.
+    Move,
+    /// A player brought every plane home, or every other player resigned.
+    Finished,
+}
+
+/// One step of the game, taken by the player whose turn it is.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Move {
+    /// The dice show a total of `value`.
+    Roll(u8),
+    /// Takes `plane` out of its hangar onto the launch cell, on the top
+    /// throw.
+    Launch { plane: usize },
+    /// Flies `plane` forward by the roll.
+    Advance { plane: usize },
+    /// Gives up the roll when no plane can use it.
+    Pass,
+}
+
+/// House rules a game is played with, consumed by the move generator
+/// and the state transition. They serialize with saved games and network
+/// sessions, missing keys taking the classic rules and unknown ones, from
+/// later versions, skipped.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleSet {
+    pub dice: DiceSpec,
+    /// Planes finishing a move on a ring cell of their color jump ahead
+    /// to the next cell of it, four cells on the classic track.
+    pub jumps: bool,
+    /// Planes keep jumping and flying for as long as they land on cells
+    /// of their color, instead of at most one jump and one flight.
+    pub jump_chains: bool,
+    /// Planes finishing a move on the start of a shortcut of their color
+    /// fly along it, capturing opponents on the cells they cross.
+    pub shortcuts: bool,
+    /// Own planes sharing a cell move together as a stack.
+    pub stacks_move: bool,
+    /// Opponents cannot move or jump past a stack, only land on it.
+    pub stacks_block: bool,
+    /// Planes overshooting the goal bounce back down their runway. Without
+    /// it they have to land on the goal exactly.
+    pub bounce: bool,
+    /// A third top throw in a row ends the turn without moving.
+    pub three_sixes: bool,
+    pub ending: Ending,
+}
+
+/// When a game is over.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ending {
+    /// As soon as one player brings every plane home.
+    FirstHome,
+    /// Once every player but the last is home, ranking them all.
+    Ranking,
+}
+
+impl RuleSet {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&text)
+            .with_context(|| format!("invalid rules {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+}
+
+impl Default for RuleSet {
+    /// The classic rules.
+    fn default() -> Self {
+        Self {
+            dice: DiceSpec::CLASSIC,
+            jumps: true,
+            jump_chains: false,
+            shortcuts: true,
+            stacks_move: false,
+            stacks_block: false,
+            bounce: true,
+            three_sixes: true,
+            ending: Ending::FirstHome,
+        }
+    }
+}
+
+/// A plane of the player in `seat`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PlaneId {
+    pub seat: usize,
+    pub plane: usize,
+}
+
+/// Something that happened during a move, for the UI to show.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Event {
+    /// The player in `seat` threw the top throw and throws again.
+    ExtraTurn { seat: usize },
+    /// The player in `seat` threw the top throw three times in a row and
+    /// loses the turn.
+    Penalty { seat: usize },
+    /// `planes` of `seat` landed on a cell of their color and jumped
+    /// ahead from `from` to `to`.
+    Jump {
+        seat: usize,
+        planes: Vec<usize>,
+        from: CellId,
+        to: CellId,
+    },
+    /// `planes` of `seat` flew along the shortcut from `from` to `to`.
+    Fly {
+        seat: usize,
+        planes: Vec<usize>,
+        from: CellId,
+        to: CellId,
+    },
+    /// A plane of `seat` landed on or flew over `cell` and sent the
+    /// opponent `planes` there back to their hangars.
+    Capture {
+        seat: usize,
+        cell: CellId,
+        planes: Vec<PlaneId>,
+    },
+}
+
+/// One stretch of the way planes take during a move, to the cell it ends
+/// on.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Leg {
+    /// On to the next cell of the track, or back down the runway when
+    /// bouncing off the goal.
+    Step(CellId),
+    /// Ahead to a cell of the planes' color.
+    Jump(CellId),
+    /// Along a shortcut.
+    Fly(CellId),
+}
+
+/// The way `planes` of the player to move take during a move, leg by leg.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Route {
+    pub planes: Vec<usize>,
+    pub legs: Vec<Leg>,
+}
+
+/// Planes of one player sharing a track cell.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Stack {
+    pub seat: usize,
+    pub cell: CellId,
+    /// Indices of the planes in their side, at least two.
+    pub planes: Vec<usize>,
+}
+
+/// The planes of a seated player.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Side {
+    pub seat: usize,
+    pub planes: [Position; PLANES],
+}
+
+impl Side {
+    /// Whether every plane is home.
+    pub fn finished(&self) -> bool {
+        self.planes.iter().all(|&plane| plane == Position::Home)
+    }
+}
+
+/// A game in progress on a board: where every plane is, whose turn it is
+/// and what they rolled. Moves never change a state in place, [`apply`]
+/// returns the next one.
+///
+/// [`apply`]: GameState::apply
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameState {
+    track: Track,
+    sides: Vec<Side>,
+    /// Index into `sides` of the player to move.
+    turn: usize,
+    dice: Option<u8>,
+    /// Top throws in a row by the player to move.
+    tops: u8,
+    phase: Phase,
+    rules: RuleSet,
+    /// Seats in the order their players brought every plane home.
+    ranking: Vec<usize>,
+    /// Seats in the order their players resigned.
+    #[serde(default)]
+    resigned: Vec<usize>,
+    /// Raised by the move leading to this state.
+    events: Vec<Event>,
+}
+
+impl GameState {
+    /// A new game under the classic rules with every plane in its hangar
+    /// and the first seated player to roll.
+    pub fn new(board: &Board) -> Self {
+        let sides = (0..board.seats().len())
+            .filter(|&seat| board.is_seated(seat))
+            .map(|seat| Side {
+                seat,
+                planes: [Position::Hangar; PLANES],
+            })
+            .collect();
+        Self {
+            track: board.track().clone(),
+            sides,
+            turn: 0,
+            dice: None,
+            tops: 0,
+            phase: Phase::Roll,
+            rules: RuleSet::default(),
+            ranking: vec![],
+            resigned: vec![],
+            events: vec![],
+        }
+    }
+
+    /// The same game under other house rules.
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    pub fn track(&self) -> &Track {
+        &self.track
+    }
+
+    pub fn sides(&self) -> &[Side] {
+        &self.sides
+    }
+
+    /// The player to move, or the last one who moved once the game is
+    /// finished.
+    pub fn turn(&self) -> &Side {
+        &self.sides[self.turn]
+    }
+
+    /// Value rolled by the player to move, until they use it.
+    pub fn dice(&self) -> Option<u8> {
+        self.dice
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// What happened during the move leading to this state.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// FNV-1a hash of where every plane is, whose turn it is, what they
+    /// rolled and who finished or resigned, the same on every platform so
+    /// replays and peers can compare states cheaply.
+    pub fn checksum(&self) -> u64 {
+        let mut words = vec![self.sides.len() as u64];
+        for side in &self.sides {
+            words.push(side.seat as u64);
+            words.extend(side.planes.map(|plane| match plane {
+                Position::Hangar => u64::MAX,
+                Position::Track(cell) => cell as u64,
+                Position::Home => u64::MAX - 1,
+            }));
+        }
+        words.extend([
+            self.turn as u64,
+            self.dice.map_or(u64::MAX, u64::from),
+            u64::from(self.tops),
+            self.phase as u64,
+            self.ranking.len() as u64,
+        ]);
+        words.extend(self.ranking.iter().map(|&seat| seat as u64));
+        // Only games with resignations sum them, so older sums still hold.
+        if !self.resigned.is_empty() {
+            words.push(self.resigned.len() as u64);
+            words.extend(self.resigned.iter().map(|&seat| seat as u64));
+        }
+        words.iter().flat_map(|word| word.to_le_bytes()).fold(
+            0xcbf2_9ce4_8422_2325,
+            |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            },
+        )
+    }
+
+    /// Every stack of planes on the track.
+    pub fn stacks(&self) -> Vec<Stack> {
+        let mut stacks = vec![];
+        for side in &self.sides {
+            for cell in 0..self.track.cells().len() {
+                let planes = planes_on(side, cell);
+                if planes.len() > 1 {
+                    stacks.push(Stack {
+                        seat: side.seat,
+                        cell,
+                        planes,
+                    });
+                }
+            }
+        }
+        stacks
+    }
+
+    /// Seat of the player who brought every plane home first.
+    pub fn winner(&self) -> Option<usize> {
+        self.ranking.first().copied()
+    }
+
+    /// Seats in the order their players finished, every seat once a game
+    /// played out to a full ranking is over.
+    pub fn ranking(&self) -> &[usize] {
+        &self.ranking
+    }
+
+    /// Seats in the order their players resigned.
+    pub fn resigned(&self) -> &[usize] {
+        &self.resigned
+    }
+
+    /// The state after the player in `seat` resigns, whether it is their
+    /// turn or not. Their planes stay where they are but they play no
+    /// more, ranking last under the full ranking with the first to resign
+    /// last of all, and the game ends once one player is left flying. The
+    /// turn passes on if it was theirs, throw or not.
+    pub fn resign(&self, seat: usize) -> Result<GameState> {
+        ensure!(self.phase != Phase::Finished, "the game is over");
+        let side = self
+            .sides
+            .iter()
+            .position(|side| side.seat == seat)
+            .with_context(|| format!("no player sits in seat {seat}"))?;
+        ensure!(
+            self.playing().contains(&side),
+            "the player in seat {seat} is no longer playing"
+        );
+        let mut next = self.clone();
+        next.events.clear();
+        next.resigned.push(seat);
+        let playing = next.playing();
+        if side == self.turn || playing.len() <= 1 {
+            next.dice = None;
+            next.tops = 0;
+        }
+        if playing.len() <= 1 {
+            next.finish(&playing);
+        } else if side == self.turn {
+            next.phase = Phase::Roll;
+            next.pass_turn(&playing);
+        }
+        Ok(next)
+    }
+
+    /// Every move the player to move may take.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        match (self.phase, self.dice) {
+            (Phase::Roll, _) => {
+                self.rules.dice.totals().map(Move::Roll).collect()
+            }
+            (Phase::Move, Some(dice)) => {
+                let mut moves: Vec<Move> = self
+                    .turn()
+                    .planes
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(plane, position)| match *position {
+                        Position::Hangar if self.rules.dice.is_top(dice) => {
+                            Some(Move::Launch { plane })
+                        }
+                        // A stack moves once, by its first plane.
+                        Position::Track(from)
+                            if self.movers(plane)[0] == plane
+                                && self.can_advance(from, dice) =>
+                        {
+                            Some(Move::Advance { plane })
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                if moves.is_empty() {
+                    moves.push(Move::Pass);
+                }
+                moves
+            }
+            _ => vec![],
+        }
+    }
+
+    /// The state after the player to move takes `mv`.
+    pub fn apply(&self, mv: Move) -> Result<GameState> {
+        let legal = self.legal_moves();
+        if !legal.contains(&mv) {
+            bail!("illegal move {mv:?}, expected one of {legal:?}");
+        }
+        let mut next = self.clone();
+        next.events.clear();
+        let seat = self.turn().seat;
+        match mv {
+            Move::Roll(value) => {
+                next.tops = if self.rules.dice.is_top(value) {
+                    self.tops.saturating_add(1)
+                } else {
+                    0
+                };
+                if self.rules.three_sixes && next.tops >= 3 {
+                    next.events.push(Event::Penalty { seat });
+                } else {
+                    next.dice = Some(value);
+                    next.phase = Phase::Move;
+                    return Ok(next);
+                }
+            }
+            Move::Launch { plane } => {
+                let cell = self.track.launch(seat);
+                next.sides[next.turn].planes[plane] = Position::Track(cell);
+                next.capture(cell);
+            }
+            Move::Advance { plane } => {
+                let Position::Track(from) = self.turn().planes[plane] else {
+                    unreachable!("only planes on the track advance");
+                };
+                let steps = self.dice.expect("the die was rolled");
+                let path = self.path(seat, from, steps).unwrap_or_default();
+                let to = match path.last() {
+                    Some(&cell) if cell == self.track.goal(seat) => {
+                        Position::Home
+                    }
+                    Some(&cell) => Position::Track(cell),
+                    None => Position::Track(from),
+                };
+                let planes = self.movers(plane);
+                for &plane in &planes {
+                    next.sides[next.turn].planes[plane] = to;
+                }
+                if let Position::Track(cell) = to {
+                    next.land(&planes, cell);
+                }
+            }
+            Move::Pass => {}
+        }
+        next.end_turn();
+        Ok(next)
+    }
+
+    /// The way the planes moved by `mv` take, none for throws and passes.
+    pub fn route(&self, mv: Move) -> Result<Option<Route>> {
+        let next = self.apply(mv)?;
+        let seat = self.turn().seat;
+        let (planes, mut legs) = match mv {
+            Move::Launch { plane } => {
+                (vec![plane], vec![Leg::Step(self.track.launch(seat))])
+            }
+            Move::Advance { plane } => {
+                let Position::Track(from) = self.turn().planes[plane] else {
+                    unreachable!("only planes on the track advance");
+                };
+                let steps = self.dice.expect("the die was rolled");
+                let path = self.path(seat, from, steps).unwrap_or_default();
+                let legs = path.into_iter().map(Leg::Step).collect();
+                (self.movers(plane), legs)
+            }
+            Move::Roll(_) | Move::Pass => return Ok(None),
+        };
+        legs.extend(next.events.iter().filter_map(|event| match *event {
+            Event::Jump { to, .. } => Some(Leg::Jump(to)),
+            Event::Fly { to, .. } => Some(Leg::Fly(to)),
+            _ => None,
+        }));
+        Ok(Some(Route { planes, legs }))
+    }
+
+    /// Whether a plane of the player to move on `from` may fly `steps`
+    /// cells, not overshooting the goal unless it bounces and not passing
+    /// a blocking stack.
+    fn can_advance(&self, from: CellId, steps: u8) -> bool {
+        self.path(self.turn().seat, from, steps)
+            .is_some_and(|path| {
+                let (_, passed) = path.split_last().unwrap_or((&from, &[]));
+                !passed.iter().any(|&cell| self.blocked(cell))
+            })
+    }
+
+    /// Cells a plane of `seat` on `from` moves through in `steps`, ending
+    /// where it stops, or `None` when it would overshoot the goal without
+    /// bouncing back down its runway.
+    fn path(
+        &self,
+        seat: usize,
+        from: CellId,
+        steps: u8,
+    ) -> Option<Vec<CellId>> {
+        let goal = self.track.goal(seat);
+        let mut cell = from;
+        let mut bouncing = false;
+        let mut path = vec![];
+        for _ in 0..steps {
+            if cell == goal && !self.rules.bounce {
+                return None;
+            }
+            bouncing |= cell == goal;
+            cell = if bouncing {
+                let Spot::Runway { step, .. } = self.track.cells()[cell].spot
+                else {
+                    unreachable!("planes bounce on their runway");
+                };
+                let spot = Spot::Runway {
+                    seat,
+                    step: step.saturating_sub(1),
+                };
+                self.track.index(spot).expect("runways are on the track")
+            } else {
+                let next = self.track.successor(cell, seat);
+                next.expect("only the goal ends a runway")
+            };
+            path.push(cell);
+        }
+        Some(path)
+    }
+
+    /// Planes of the player to move that move along with `plane`: its
+    /// whole stack when stacks move together, first plane first.
+    pub(crate) fn movers(&self, plane: usize) -> Vec<usize> {
+        let side = self.turn();
+        match side.planes[plane] {
+            Position::Track(cell) if self.rules.stacks_move => {
+                planes_on(side, cell)
+            }
+            _ => vec![plane],
+        }
+    }
+
+    /// Whether an opponent stack keeps the player to move from passing
+    /// `cell`.
+    fn blocked(&self, cell: CellId) -> bool {
+        let turn = self.turn().seat;
+        self.rules.stacks_block
+            && self.sides.iter().any(|side| {
+                side.seat != turn && planes_on(side, cell).len() > 1
+            })
+    }
+
+    /// Finishes a move of `planes` of the player to move on `cell`:
+    /// captures there, then jumps or flies on. A jump ending on a shortcut
+    /// chains into the flight, further jumps and flights need jump chains.
+    fn land(&mut self, planes: &[usize], cell: CellId) {
+        let seat = self.turn().seat;
+        self.capture(cell);
+        let mut cell = cell;
+        loop {
+            let from = cell;
+            cell = self.jump_and_fly(seat, planes, cell);
+            if !self.rules.jump_chains || cell == from {
+                break;
+            }
+        }
+    }
+
+    /// Takes at most one jump then one flight from `cell`, returning where
+    /// the planes end up. Both only move forward on the ring without
+    /// passing the cell where the planes turn home, so chains end.
+    fn jump_and_fly(
+        &mut self,
+        seat: usize,
+        planes: &[usize],
+        mut cell: CellId,
+    ) -> CellId {
+        if let Some(to) = self.jump(cell) {
+            for &plane in planes {
+                self.sides[self.turn].planes[plane] = Position::Track(to);
+            }
+            self.events.push(Event::Jump {
+                seat,
+                planes: planes.to_vec(),
+                from: cell,
+                to,
+            });
+            self.capture(to);
+            cell = to;
+        }
+        if let Some(to) = self.fly(cell) {
+            for &plane in planes {
+                self.sides[self.turn].planes[plane] = Position::Track(to);
+            }
+            self.events.push(Event::Fly {
+                seat,
+                planes: planes.to_vec(),
+                from: cell,
+                to,
+            });
+            for crossed in self.track.crossings(cell) {
+                self.capture(crossed);
+            }
+            self.capture(to);
+            cell = to;
+        }
+        cell
+    }
+
+    /// Whether `cell` is a ring cell of the color of the player to move.
+    fn owned(&self, cell: CellId) -> bool {
+        self.track.get(cell).is_some_and(|track_cell| {
+            matches!(track_cell.spot, Spot::Ring { .. })
+                && track_cell.color == self.turn().seat
+        })
+    }
+
+    /// Whether the player to move turns home from `cell`.
+    fn turns_home(&self, cell: CellId) -> bool {
+        let seat = self.turn().seat;
+        self.track.successor(cell, seat) != self.track.cells()[cell].next
+    }
+
+    /// Cell a plane of the player to move jumps to from `cell`, when the
+    /// rules let it jump from there. Planes do not jump from the cell where
+    /// they turn home, off the ring nor over a blocking stack.
+    fn jump(&self, cell: CellId) -> Option<CellId> {
+        if !self.rules.jumps || !self.owned(cell) || self.turns_home(cell) {
+            return None;
+        }
+        let seat = self.turn().seat;
+        let colors = self.track.colors() as u8;
+        let path = self.path(seat, cell, colors)?;
+        let (&to, passed) = path.split_last()?;
+        if to >= self.track.ring_len()
+            || passed.iter().any(|&cell| self.blocked(cell))
+        {
+            return None;
+        }
+        Some(to)
+    }
+
+    /// Cell a plane of the player to move flies to from `cell`, when the
+    /// rules let it take a shortcut from there. Planes do not fly past the
+    /// cell where they turn home.
+    fn fly(&self, cell: CellId) -> Option<CellId> {
+        if !self.rules.shortcuts || !self.owned(cell) {
+            return None;
+        }
+        let to = self.track.cells()[cell].shortcut?;
+        let mut at = cell;
+        while at != to {
+            if self.turns_home(at) {
+                return None;
+            }
+            at = self.track.cells()[at].next?;
+        }
+        Some(to)
+    }
+
+    /// Sends every opponent plane on `cell` back to its hangar, whole
+    /// stacks at once.
+    fn capture(&mut self, cell: CellId) {
+        let turn = self.turn;
+        let mut planes = vec![];
+        for (index, side) in self.sides.iter_mut().enumerate() {
+            if index == turn {
+                continue;
+            }
+            for (plane, position) in side.planes.iter_mut().enumerate() {
+                if *position == Position::Track(cell) {
+                    *position = Position::Hangar;
+                    planes.push(PlaneId {
+                        seat: side.seat,
+                        plane,
+                    });
+                }
+            }
+        }
+        if !planes.is_empty() {
+            let seat = self.sides[turn].seat;
+            self.events.push(Event::Capture { seat, cell, planes });
+        }
+    }
+
+    /// Passes the turn on, or gives the same player another roll after
+    /// the top throw.
+    /// Players who brought every plane home are ranked and skipped from
+    /// then on, ending the game as the rules say.
+    fn end_turn(&mut self) {
+        let dice = self.dice.take();
+        let seat = self.turn().seat;
+        let finished = self.turn().finished();
+        if finished {
+            self.ranking.push(seat);
+        }
+        let playing = self.playing();
+        let over = match self.rules.ending {
+            Ending::FirstHome => finished,
+            Ending::Ranking => playing.len() <= 1,
+        };
+        if over {
+            // The last player still flying ranks last.
+            if self.rules.ending == Ending::Ranking {
+                self.finish(&playing);
+            } else {
+                self.finish(&[]);
+            }
+            return;
+        }
+        self.phase = Phase::Roll;
+        if !finished
+            && dice.is_some_and(|dice| self.rules.dice.extra_turn(dice))
+        {
+            self.events.push(Event::ExtraTurn { seat });
+            return;
+        }
+        self.tops = 0;
+        self.pass_turn(&playing);
+    }
+
+    /// Indices into `sides` of the players neither home nor resigned.
+    fn playing(&self) -> Vec<usize> {
+        (0..self.sides.len())
+            .filter(|&side| {
+                let side = &self.sides[side];
+                !side.finished() && !self.resigned.contains(&side.seat)
+            })
+            .collect()
+    }
+
+    /// Gives the turn to the next of the `playing` sides.
+    fn pass_turn(&mut self, playing: &[usize]) {
+        let sides = self.sides.len();
+        self.turn = (1..=sides)
+            .map(|offset| (self.turn + offset) % sides)
+            .find(|side| playing.contains(side))
+            .expect("a player is still flying");
+    }
+
+    /// Ends the game, ranking the `flying` sides next, then under the
+    /// full ranking those who resigned, the first to resign last.
+    fn finish(&mut self, flying: &[usize]) {
+        self.ranking
+            .extend(flying.iter().map(|&side| self.sides[side].seat));
+        if self.rules.ending == Ending::Ranking {
+            self.ranking.extend(self.resigned.iter().rev());
+        }
+        self.phase = Phase::Finished;
+    }
+}
+
+/// Planes of `side` on track cell `cell`, in order.
+fn planes_on(side: &Side, cell: CellId) -> Vec<usize> {
+    (0..PLANES)
+        .filter(|&plane| side.planes[plane] == Position::Track(cell))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Layout;
+
+    /// Seat 0's first plane moved `throw` cells from its launch cell on
+    /// `board` under `rules`.
+    fn advanced(board: &Board, rules: RuleSet, throw: u8) -> GameState {
+        let mut state = GameState::new(board).with_rules(rules);
+        let launch = state.track.launch(0);
+        state.sides[0].planes[0] = Position::Track(launch);
+        let state = state.apply(Move::Roll(throw)).unwrap();
+        state.apply(Move::Advance { plane: 0 }).unwrap()
+    }
+
+    #[test]
+    fn planes_jump_to_the_next_cell_of_their_color() {
+        let board = Board::new(4);
+        let launch = board.track().launch(0);
+        let state = advanced(&board, RuleSet::default(), 4);
+        let (from, to) = (launch + 4, launch + 8);
+        assert_eq!(state.sides()[0].planes[0], Position::Track(to));
+        let jump = Event::Jump {
+            seat: 0,
+            planes: vec![0],
+            from,
+            to,
+        };
+        assert!(state.events().contains(&jump));
+    }
+
+    #[test]
+    fn jumps_chain_into_shortcuts() {
+        // A shortcut from the cell the plane jumps to, to the next quadrant.
+        let text = include_str!("../layouts/classic.toml").to_owned()
+            + "\n[[shortcut]]\nfrom = 9\nahead = 12\n";
+        let board = Board::with_layout(4, Layout::parse(&text).unwrap());
+        let launch = board.track().launch(0);
+        let state = advanced(&board, RuleSet::default(), 4);
+        let (jumped, flown) = (launch + 8, launch + 20);
+        assert_eq!(state.sides()[0].planes[0], Position::Track(flown));
+        let fly = Event::Fly {
+            seat: 0,
+            planes: vec![0],
+            from: jumped,
+            to: flown,
+        };
+        assert!(state.events().contains(&fly));
+    }
+
+    #[test]
+    fn top_throws_in_a_row_do_not_overflow() {
+        let rules = RuleSet {
+            three_sixes: false,
+            ..RuleSet::default()
+        };
+        let mut state = GameState::new(&Board::new(4)).with_rules(rules);
+        state.tops = u8::MAX;
+        let state = state.apply(Move::Roll(6)).unwrap();
+        assert_eq!(state.tops, u8::MAX);
+    }
+
+    #[test]
+    fn planes_do_not_jump_without_the_rule() {
+        let board = Board::new(4);
+        let launch = board.track().launch(0);
+        let rules = RuleSet {
+            jumps: false,
+            ..RuleSet::default()
+        };
+        let state = advanced(&board, rules, 4);
+        let landed = Position::Track(launch + 4);
+        assert_eq!(state.sides()[0].planes[0], landed);
+        let jumped = state.events().iter().any(|event| {
+            matches!(event, Event::Jump { .. } | Event::Fly { .. })
+        });
+        assert!(!jumped);
+    }
+}
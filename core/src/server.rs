@@ -0,0 +1,1191 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::future;
+use std::io;
+use std::mem;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, ensure};
+use futures_util::{SinkExt, StreamExt};
+use rand::rngs::StdRng;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::runtime;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::{Error, Message};
+
+use crate::ai::{self, AiPlayer};
+use crate::board::Board;
+use crate::dice::Dice;
+use crate::game::Game;
+use crate::protocol::{
+    self, ChatLine, ClientRequest, MAX_CHAT, MAX_FRAME, RoomInfo, ServerEvent,
+    ServerMessage, ServerResponse, VERSION,
+};
+use crate::rules::{GameState, Move, PLANES, Phase, RuleSet};
+
+/// Time players dropping out of a game have to come back, unless the
+/// server is told otherwise.
+pub const GRACE: Duration = Duration::from_secs(60);
+
+/// Chat messages a connection can send within [`CHAT_WINDOW`], those past
+/// it refused.
+const CHAT_BURST: usize = 5;
+
+const CHAT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Time a computer player standing in for a player gets to think about a
+/// move, the lobby waiting meanwhile.
+const BUDGET: Duration = Duration::from_millis(100);
+
+/// Hosts games for players connecting over TCP. They meet in a lobby,
+/// open rooms there and take the seats of others, one seat a connection,
+/// and each room plays a game once its seats are taken. The server keeps
+/// the only games that count: it throws the dice and checks every move,
+/// the clients only ask and follow.
+///
+/// Players dropping out of a game keep their seat for a grace period,
+/// during which they can connect again and resume it with the token they
+/// were welcomed with.
+pub struct Server {
+    /// Address to take WebSocket connections on as well, for browsers.
+    /// They carry the same messages, one a text frame, instead of the
+    /// length-prefixed frames of [`protocol`].
+    pub websocket: Option<SocketAddr>,
+    /// Time a player dropping out of a game has to resume their seat.
+    pub grace: Duration,
+    /// Computer player, by its name in [`ai::names`], playing the seats
+    /// whose grace ran out. Without one the player forfeits and the room
+    /// closes.
+    pub substitute: Option<String>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self {
+            websocket: None,
+            grace: GRACE,
+            substitute: None,
+        }
+    }
+}
+
+impl Server {
+    /// Hosts the lobby on `address` until it fails, telling `report`
+    /// everything the players of a room are told, with the room and its
+    /// game.
+    pub fn run(
+        &self,
+        address: impl ToSocketAddrs,
+        report: impl FnMut(&RoomInfo, &Game, &ServerEvent),
+    ) -> Result<()> {
+        if let Some(substitute) = &self.substitute {
+            ensure!(
+                ai::names().contains(substitute),
+                "unknown computer player {substitute:?}"
+            );
+        }
+        let runtime =
+            runtime::Builder::new_multi_thread().enable_all().build()?;
+        runtime.block_on(self.host(address, report))
+    }
+
+    async fn host(
+        &self,
+        address: impl ToSocketAddrs,
+        report: impl FnMut(&RoomInfo, &Game, &ServerEvent),
+    ) -> Result<()> {
+        let listener = TcpListener::bind(address).await?;
+        let websockets = match self.websocket {
+            Some(address) => Some(TcpListener::bind(address).await?),
+            None => None,
+        };
+        let (timers, mut expired) = mpsc::unbounded_channel();
+        let mut host = Host {
+            connections: HashMap::new(),
+            rooms: BTreeMap::new(),
+            next_room: 1,
+            grace: self.grace,
+            substitute: self.substitute.clone(),
+            timers,
+            report,
+        };
+        let (events, mut incoming) = mpsc::unbounded_channel();
+        let mut next = 0;
+        loop {
+            let (stream, websocket) = tokio::select! {
+                accepted = listener.accept() => (accepted?.0, false),
+                accepted = accept(websockets.as_ref()) => (accepted?.0, true),
+                event = incoming.recv() => {
+                    let (id, event) = event.expect("the host holds a sender");
+                    host.handle(id, event);
+                    continue;
+                }
+                timer = expired.recv() => {
+                    let (room, seat, drops) =
+                        timer.expect("the host holds a sender");
+                    host.expire(room, seat, drops);
+                    continue;
+                }
+            };
+            let (outgoing, receiver) = mpsc::unbounded_channel();
+            let connection = Connection {
+                outgoing,
+                name: None,
+                room: None,
+                watching: false,
+                chatted: VecDeque::new(),
+            };
+            host.connections.insert(next, connection);
+            let events = events.clone();
+            if websocket {
+                tokio::spawn(connect_websocket(next, stream, events, receiver));
+            } else {
+                tokio::spawn(connect(next, stream, events, receiver));
+            }
+            next += 1;
+        }
+    }
+}
+
+/// The lobby and its rooms.
+struct Host<F> {
+    connections: HashMap<usize, Connection>,
+    rooms: BTreeMap<u64, Room>,
+    next_room: u64,
+    grace: Duration,
+    substitute: Option<String>,
+    /// Tells the host when the grace of a seat runs out, with the room,
+    /// the seat and the drop it was given for.
+    timers: UnboundedSender<(u64, usize, u64)>,
+    report: F,
+}
+
+/// A client connected to the server.
+struct Connection {
+    outgoing: UnboundedSender<ServerMessage>,
+    /// Name the client said hello with.
+    name: Option<String>,
+    /// Room the client sits in or watches, in the lobby otherwise.
+    room: Option<u64>,
+    /// Whether the client only watches the room.
+    watching: bool,
+    /// When the client chatted, within the last [`CHAT_WINDOW`].
+    chatted: VecDeque<Instant>,
+}
+
+/// A game being hosted and the players seated for it.
+struct Room {
+    id: u64,
+    name: String,
+    password: Option<String>,
+    game: Game,
+    dice: Dice<StdRng>,
+    /// Every player seated, in seat order.
+    seated: Vec<Seated>,
+    /// Whether every seat was taken, after which the players stay.
+    started: bool,
+    /// Connections watching the game.
+    spectators: Vec<usize>,
+}
+
+/// A player seated in a room.
+struct Seated {
+    seat: usize,
+    name: String,
+    /// Secret the player resumes the seat with after dropping out.
+    token: String,
+    /// Connection playing the seat, none while the player is away.
+    connection: Option<usize>,
+    /// Computer player standing in once the grace of the player ran out.
+    ai: Option<Box<dyn AiPlayer>>,
+    /// Times the player dropped out, telling the grace of the latest drop
+    /// from those of earlier ones.
+    drops: u64,
+}
+
+/// What a connection tells the host.
+enum Incoming {
+    Message(ClientRequest),
+    /// A frame that is not a message.
+    Malformed(String),
+    Closed,
+}
+
+impl Incoming {
+    fn parse(json: &[u8]) -> Self {
+        match serde_json::from_slice(json) {
+            Ok(message) => Incoming::Message(message),
+            Err(error) => Incoming::Malformed(error.to_string()),
+        }
+    }
+}
+
+impl<F: FnMut(&RoomInfo, &Game, &ServerEvent)> Host<F> {
+    fn handle(&mut self, id: usize, event: Incoming) {
+        match event {
+            Incoming::Message(message) => self.receive(id, message),
+            Incoming::Malformed(error) => {
+                self.reject(id, &format!("malformed message: {error}"));
+            }
+            Incoming::Closed => {
+                self.drop_out(id);
+                self.connections.remove(&id);
+            }
+        }
+    }
+
+    fn receive(&mut self, id: usize, message: ClientRequest) {
+        let Some(connection) = self.connections.get_mut(&id) else {
+            return;
+        };
+        let room = connection.room;
+        match message {
+            ClientRequest::Hello { version, .. }
+                if version != VERSION && connection.name.is_none() =>
+            {
+                let reason = format!(
+                    "the server speaks protocol {VERSION}, not {version}"
+                );
+                self.reject(id, &reason);
+                // Dropping the connection hangs up once the refusal is out.
+                self.connections.remove(&id);
+            }
+            ClientRequest::Hello { name, .. } if connection.name.is_none() => {
+                let name = name.trim();
+                if name.is_empty() {
+                    return self.reject(id, "names cannot be empty");
+                }
+                connection.name = Some(name.into());
+                let rooms = self.rooms();
+                let version = VERSION;
+                self.send(id, ServerResponse::Hello { version, rooms });
+            }
+            ClientRequest::Hello { .. } => self.reject(id, "already in"),
+            _ if connection.name.is_none() => {
+                self.reject(id, "say hello first")
+            }
+            ClientRequest::ListRooms => {
+                let rooms = self.rooms();
+                self.send(id, ServerResponse::Rooms { rooms });
+            }
+            ClientRequest::CreateRoom { .. }
+            | ClientRequest::JoinRoom { .. }
+            | ClientRequest::Resume { .. }
+            | ClientRequest::Watch { .. }
+                if room.is_some() =>
+            {
+                self.reject(id, "leave the room first");
+            }
+            ClientRequest::CreateRoom {
+                name,
+                players,
+                rules,
+                password,
+            } => self.create(id, name, players, rules, password),
+            ClientRequest::JoinRoom { room, password } => {
+                self.join(id, room, password.as_deref());
+            }
+            ClientRequest::Resume { token } => self.resume(id, &token),
+            ClientRequest::Watch { room, password } => {
+                self.watch(id, room, password.as_deref());
+            }
+            ClientRequest::LeaveRoom if room.is_some() => self.leave(id),
+            ClientRequest::Chat { text } => match room {
+                Some(room) => self.chat(id, room, &text),
+                None => self.reject(id, "join a room to chat"),
+            },
+            _ if connection.watching => {
+                self.reject(id, "spectators cannot play");
+            }
+            _ => match room {
+                Some(room) => self.submit(id, room, message),
+                None => self.reject(id, "join a room first"),
+            },
+        }
+    }
+
+    /// Opens a room and seats its creator, connection `id`, in it.
+    fn create(
+        &mut self,
+        id: usize,
+        name: String,
+        players: usize,
+        rules: RuleSet,
+        password: Option<String>,
+    ) {
+        if !Board::PLAYERS.contains(&players) {
+            let players = Board::PLAYERS;
+            return self.reject(id, &format!("rooms seat one of {players:?}"));
+        }
+        let seed = rand::random();
+        let dice = match Dice::seeded(rules.dice, seed) {
+            Ok(dice) => dice,
+            Err(error) => return self.reject(id, &format!("{error:#}")),
+        };
+        let opened = Room {
+            id: self.next_room,
+            name: name.trim().into(),
+            password,
+            game: Game::new(Board::new(players), rules, Some(seed)),
+            dice,
+            seated: vec![],
+            started: false,
+            spectators: vec![],
+        };
+        self.next_room += 1;
+        let (room, password) = (opened.id, opened.password.clone());
+        self.rooms.insert(room, opened);
+        self.join(id, room, password.as_deref());
+    }
+
+    /// Seats connection `id` in the first free seat of room `room`,
+    /// starting its game once none is left.
+    fn join(&mut self, id: usize, room: u64, password: Option<&str>) {
+        let Some(entered) = self.rooms.get_mut(&room) else {
+            return self.reject(id, &format!("there is no room {room}"));
+        };
+        if entered.started {
+            return self.reject(id, "the game has started");
+        }
+        if entered.password.is_some() && entered.password.as_deref() != password
+        {
+            return self.reject(id, "wrong password");
+        }
+        let sides = entered.game.state().sides();
+        let free = sides.iter().map(|side| side.seat).find(|&seat| {
+            entered.seated.iter().all(|taken| taken.seat != seat)
+        });
+        let Some(seat) = free else {
+            return self.reject(id, "the room is full");
+        };
+        let connection = self.connections.get_mut(&id).expect("joining");
+        let name = connection.name.clone().expect("said hello");
+        connection.room = Some(room);
+        let joined = ServerEvent::Joined {
+            seat,
+            name: name.clone(),
+        };
+        entered.broadcast(&self.connections, &mut self.report, joined);
+        let token = format!("{:032x}", rand::random::<u128>());
+        entered.seated.push(Seated {
+            seat,
+            name,
+            token: token.clone(),
+            connection: Some(id),
+            ai: None,
+            drops: 0,
+        });
+        entered.seated.sort_by_key(|taken| taken.seat);
+        let welcome = ServerResponse::Welcome {
+            room,
+            seat,
+            token,
+            board: Box::new(entered.game.record().board.clone()),
+            rules: entered.game.state().rules(),
+            players: entered.players(),
+            spectators: entered.spectators.len(),
+        };
+        let _ = self.connections[&id].outgoing.send(welcome.into());
+        if entered.seated.len() == entered.game.state().sides().len() {
+            entered.started = true;
+            let turn = entered.turn();
+            let report = &mut self.report;
+            entered.broadcast(&self.connections, report, ServerEvent::Started);
+            entered.broadcast(&self.connections, report, turn);
+        }
+        self.lobby();
+    }
+
+    /// Seats connection `id` back in the seat `token` was given for, the
+    /// connection playing it until now, if any, going back to the lobby.
+    fn resume(&mut self, id: usize, token: &str) {
+        let found = self.rooms.values().find_map(|room| {
+            let seated = room.seated.iter();
+            let index = seated.into_iter().position(|p| p.token == token)?;
+            Some((room.id, index))
+        });
+        let Some((room, index)) = found else {
+            return self.reject(id, "there is no seat to resume");
+        };
+        let resumed = self.rooms.get_mut(&room).expect("found");
+        let player = &mut resumed.seated[index];
+        let replaced = player.connection.replace(id);
+        player.ai = None;
+        player.drops += 1;
+        let seat = player.seat;
+        if let Some(replaced) =
+            replaced.and_then(|replaced| self.connections.get_mut(&replaced))
+        {
+            replaced.room = None;
+            let reason = "the seat was resumed elsewhere".into();
+            let closed = ServerEvent::Closed { reason };
+            let _ = replaced.outgoing.send(closed.into());
+        }
+        self.connections.get_mut(&id).expect("resuming").room = Some(room);
+        let response = ServerResponse::Resumed {
+            room,
+            seat,
+            token: token.into(),
+            record: Box::new(resumed.game.record().clone()),
+            state: Box::new(resumed.game.state().clone()),
+            players: resumed.players(),
+            spectators: resumed.spectators.len(),
+        };
+        let _ = self.connections[&id].outgoing.send(response.into());
+        let rejoined = ServerEvent::Rejoined { seat };
+        resumed.broadcast(&self.connections, &mut self.report, rejoined);
+        if resumed.started {
+            // The game may be waiting for them, they need to know.
+            let turn = resumed.turn();
+            let _ = self.connections[&id].outgoing.send(turn.into());
+        }
+    }
+
+    /// Lets connection `id` watch the game of room `room`.
+    fn watch(&mut self, id: usize, room: u64, password: Option<&str>) {
+        let Some(watched) = self.rooms.get_mut(&room) else {
+            return self.reject(id, &format!("there is no room {room}"));
+        };
+        if watched.password.is_some() && watched.password.as_deref() != password
+        {
+            return self.reject(id, "wrong password");
+        }
+        let connection = self.connections.get_mut(&id).expect("watching");
+        connection.room = Some(room);
+        connection.watching = true;
+        watched.spectators.push(id);
+        let watching = ServerResponse::Watching {
+            room,
+            record: Box::new(watched.game.record().clone()),
+            state: Box::new(watched.game.state().clone()),
+            players: watched.players(),
+            spectators: watched.spectators.len(),
+        };
+        let _ = self.connections[&id].outgoing.send(watching.into());
+        let count = watched.spectators.len();
+        let event = ServerEvent::Spectators { count };
+        watched.broadcast(&self.connections, &mut self.report, event);
+        if watched.started {
+            let turn = watched.turn();
+            let _ = self.connections[&id].outgoing.send(turn.into());
+        }
+        self.lobby();
+    }
+
+    /// Takes connection `id` out of its room, closing the room if its game
+    /// started or no one is left in it. Spectators leave without a trace.
+    fn leave(&mut self, id: usize) {
+        let Some(connection) = self.connections.get_mut(&id) else {
+            return;
+        };
+        let Some(room) = connection.room.take() else {
+            return;
+        };
+        if mem::take(&mut connection.watching) {
+            let watched = self.rooms.get_mut(&room).expect("rooms hold theirs");
+            watched.spectators.retain(|&watcher| watcher != id);
+            let count = watched.spectators.len();
+            let event = ServerEvent::Spectators { count };
+            watched.broadcast(&self.connections, &mut self.report, event);
+            return self.lobby();
+        }
+        let left = self.rooms.get_mut(&room).expect("rooms hold their players");
+        let member = left.seated.iter().position(|p| p.connection == Some(id));
+        let player = left.seated.remove(member.expect("seated"));
+        if left.started {
+            let reason = format!("{} left the game", player.name);
+            self.close(room, ServerEvent::Closed { reason });
+        } else if left.seated.is_empty() {
+            self.rooms.remove(&room);
+        } else {
+            let seat = player.seat;
+            let left_event = ServerEvent::Left { seat };
+            left.broadcast(&self.connections, &mut self.report, left_event);
+        }
+        self.lobby();
+    }
+
+    /// Lets connection `id` go after it hung up. A game that started
+    /// keeps its seat for the grace period, the others are told to wait.
+    fn drop_out(&mut self, id: usize) {
+        let Some(connection) = self.connections.get_mut(&id) else {
+            return;
+        };
+        let Some(room) = connection.room else {
+            return;
+        };
+        let dropped =
+            self.rooms.get_mut(&room).expect("rooms hold their players");
+        if !dropped.started || connection.watching {
+            return self.leave(id);
+        }
+        connection.room = None;
+        let away = dropped.seated.iter_mut().find(|p| p.connection == Some(id));
+        let away = away.expect("seated");
+        away.connection = None;
+        away.drops += 1;
+        let (seat, drops) = (away.seat, away.drops);
+        if dropped.seated.iter().all(|p| p.connection.is_none()) {
+            let reason = "every player left".into();
+            self.close(room, ServerEvent::Closed { reason });
+            return self.lobby();
+        }
+        let grace = self.grace.as_secs();
+        let event = ServerEvent::Dropped { seat, grace };
+        dropped.broadcast(&self.connections, &mut self.report, event);
+        let (timers, grace) = (self.timers.clone(), self.grace);
+        tokio::spawn(async move {
+            time::sleep(grace).await;
+            let _ = timers.send((room, seat, drops));
+        });
+    }
+
+    /// Gives up seat `seat` of room `room` if its player is still away
+    /// since their drop `drops`: the substitute plays it from now on, or
+    /// without one the player forfeits and the room closes.
+    fn expire(&mut self, room: u64, seat: usize, drops: u64) {
+        let Some(waiting) = self.rooms.get_mut(&room) else {
+            return;
+        };
+        let away = waiting.seated.iter_mut().find(|p| p.seat == seat);
+        let Some(away) = away else {
+            return;
+        };
+        if away.connection.is_some() || away.drops != drops {
+            return;
+        }
+        let Some(substitute) = &self.substitute else {
+            let reason = format!("{} forfeited", away.name);
+            self.close(room, ServerEvent::Closed { reason });
+            return self.lobby();
+        };
+        let ai = ai::named(substitute, rand::random());
+        away.ai = Some(ai.expect("substitutes are checked at the start"));
+        let ai = substitute.clone();
+        let event = ServerEvent::Substituted { seat, ai };
+        waiting.broadcast(&self.connections, &mut self.report, event);
+        self.automate(room);
+    }
+
+    /// Passes on what connection `id` says to everyone in room `room`, as
+    /// long as it does not say too much too fast.
+    fn chat(&mut self, id: usize, room: u64, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return self.reject(id, "say something");
+        }
+        if text.chars().count() > MAX_CHAT {
+            let reason = format!("say it in {MAX_CHAT} characters or fewer");
+            return self.reject(id, &reason);
+        }
+        let connection = self.connections.get_mut(&id).expect("chatting");
+        let now = Instant::now();
+        let chatted = &mut connection.chatted;
+        while chatted.front().is_some_and(|&at| now - at >= CHAT_WINDOW) {
+            chatted.pop_front();
+        }
+        if chatted.len() >= CHAT_BURST {
+            return self.reject(id, "slow down");
+        }
+        chatted.push_back(now);
+        let room = self.rooms.get(&room).expect("rooms hold theirs");
+        let player = room.seated.iter().find(|p| p.connection == Some(id));
+        let line = ChatLine {
+            from: match player {
+                Some(player) => player.name.clone(),
+                None => connection.name.clone().expect("said hello"),
+            },
+            seat: player.map(|player| player.seat),
+            text: text.into(),
+        };
+        let event = ServerEvent::Chat(line);
+        room.broadcast(&self.connections, &mut self.report, event);
+    }
+
+    /// Plays what the player of connection `id` asked for in room `room`
+    /// if it is theirs to play and legal, telling everyone how it went.
+    fn submit(&mut self, id: usize, room: u64, request: ClientRequest) {
+        let playing =
+            self.rooms.get_mut(&room).expect("rooms hold their players");
+        let seat = playing.seated.iter().find(|p| p.connection == Some(id));
+        let seat = seat.expect("seated").seat;
+        if !playing.started {
+            return self.reject(id, "the game has not started");
+        }
+        let seq = match request {
+            ClientRequest::Roll { seq } | ClientRequest::Play { seq, .. } => {
+                seq
+            }
+            _ => None,
+        };
+        let state = playing.game.state();
+        let mv = match judge(state, seat, request, &mut playing.dice) {
+            Ok(mv) => mv,
+            Err(reason) => return self.refuse(id, &reason, seq),
+        };
+        if let Err(error) = self.play(room, seat, mv, seq) {
+            return self.refuse(id, &format!("{error:#}"), seq);
+        }
+        self.automate(room);
+    }
+
+    /// Plays the turns of the seats computer players stand in for, until
+    /// the game of room `room` waits for a player or is over.
+    fn automate(&mut self, room: u64) {
+        loop {
+            let Some(playing) = self.rooms.get_mut(&room) else {
+                return;
+            };
+            let state = playing.game.state();
+            let seat = state.turn().seat;
+            let player = playing.seated.iter_mut().find(|p| p.seat == seat);
+            let Some(ai) = player.and_then(|player| player.ai.as_mut()) else {
+                return;
+            };
+            let mv = match state.phase() {
+                Phase::Roll => Move::Roll(playing.dice.roll().total()),
+                Phase::Move => {
+                    ai.choose_move(state, &state.legal_moves(), BUDGET)
+                }
+                Phase::Finished => return,
+            };
+            let played = self.play(room, seat, mv, None);
+            played.expect("computer players play legal moves");
+        }
+    }
+
+    /// Plays `mv` for the player in `seat` of room `room`, asked for as
+    /// number `seq`, telling everyone whose turn is next or, once the game
+    /// is over, closing the room.
+    fn play(
+        &mut self,
+        room: u64,
+        seat: usize,
+        mv: Move,
+        seq: Option<u64>,
+    ) -> Result<()> {
+        let playing = self.rooms.get_mut(&room).expect("playing in a room");
+        playing.game.play(mv)?;
+        let checksum = playing.game.state().checksum();
+        let played = ServerEvent::Played {
+            seat,
+            mv,
+            checksum,
+            seq,
+        };
+        playing.broadcast(&self.connections, &mut self.report, played);
+        if playing.game.state().phase() == Phase::Finished {
+            let ranking = playing.game.state().ranking().to_vec();
+            self.close(room, ServerEvent::Over { ranking });
+            self.lobby();
+        } else {
+            let turn = playing.turn();
+            playing.broadcast(&self.connections, &mut self.report, turn);
+        }
+        Ok(())
+    }
+
+    /// Closes room `room` with `event`, sending its players back to the
+    /// lobby.
+    fn close(&mut self, room: u64, event: ServerEvent) {
+        let closed = self.rooms.remove(&room).expect("closing a room");
+        closed.broadcast(&self.connections, &mut self.report, event);
+        let players = closed.seated.iter().filter_map(|p| p.connection);
+        for id in players.chain(closed.spectators) {
+            if let Some(connection) = self.connections.get_mut(&id) {
+                connection.room = None;
+                connection.watching = false;
+            }
+        }
+    }
+
+    /// The rooms of the lobby.
+    fn rooms(&self) -> Vec<RoomInfo> {
+        self.rooms.values().map(Room::info).collect()
+    }
+
+    /// Tells everyone in the lobby how its rooms are.
+    fn lobby(&self) {
+        let rooms = ServerMessage::Event(ServerEvent::Lobby {
+            rooms: self.rooms(),
+        });
+        for connection in self.connections.values() {
+            if connection.name.is_some() && connection.room.is_none() {
+                let _ = connection.outgoing.send(rooms.clone());
+            }
+        }
+    }
+
+    fn send(&self, id: usize, response: ServerResponse) {
+        if let Some(connection) = self.connections.get(&id) {
+            // Connections hanging up are let go when they say so.
+            let _ = connection.outgoing.send(response.into());
+        }
+    }
+
+    fn reject(&self, id: usize, reason: &str) {
+        self.refuse(id, reason, None);
+    }
+
+    /// Rejects request `seq` of connection `id`.
+    fn refuse(&self, id: usize, reason: &str, seq: Option<u64>) {
+        let reason = reason.into();
+        self.send(id, ServerResponse::Rejected { reason, seq });
+    }
+}
+
+/// The move the player in `seat` asks for with `request` in `state`, or
+/// why it is refused. Whatever the client believes, the request is held
+/// against the moves legal in the state, and throws are made here with
+/// `dice`, never taken from the client.
+fn judge(
+    state: &GameState,
+    seat: usize,
+    request: ClientRequest,
+    dice: &mut Dice<StdRng>,
+) -> Result<Move, String> {
+    if state.phase() == Phase::Finished {
+        return Err("the game is over".into());
+    }
+    if state.turn().seat != seat {
+        return Err("not your turn".into());
+    }
+    let mv = match (request, state.phase()) {
+        (ClientRequest::Roll { .. }, Phase::Roll) => {
+            return Ok(Move::Roll(dice.roll().total()));
+        }
+        (ClientRequest::Roll { .. }, _) => {
+            return Err("the dice are thrown already".into());
+        }
+        (
+            ClientRequest::Play {
+                mv: Move::Roll(_), ..
+            },
+            _,
+        ) => {
+            return Err("the server throws the dice".into());
+        }
+        (ClientRequest::Play { .. }, Phase::Roll) => {
+            return Err("throw the dice first".into());
+        }
+        (ClientRequest::Play { mv, .. }, _) => mv,
+        _ => return Err("already in a room".into()),
+    };
+    if state.legal_moves().contains(&mv) {
+        return Ok(mv);
+    }
+    Err(match mv {
+        Move::Launch { plane } | Move::Advance { plane } if plane >= PLANES => {
+            format!("there is no plane {}", plane + 1)
+        }
+        Move::Launch { plane } => {
+            format!("plane {} cannot launch with this throw", plane + 1)
+        }
+        Move::Advance { plane } => format!("plane {} cannot move", plane + 1),
+        Move::Pass => "a plane can move, there is no passing".into(),
+        Move::Roll(_) => unreachable!("throws are refused above"),
+    })
+}
+
+impl Room {
+    fn info(&self) -> RoomInfo {
+        RoomInfo {
+            id: self.id,
+            name: self.name.clone(),
+            players: self.game.state().sides().len(),
+            seated: self.players(),
+            rules: self.game.state().rules(),
+            locked: self.password.is_some(),
+            started: self.started,
+            spectators: self.spectators.len(),
+        }
+    }
+
+    /// Seats and names of the players seated.
+    fn players(&self) -> Vec<(usize, String)> {
+        let players = self.seated.iter();
+        players.map(|p| (p.seat, p.name.clone())).collect()
+    }
+
+    /// Whose turn the game waits for.
+    fn turn(&self) -> ServerEvent {
+        let state = self.game.state();
+        ServerEvent::Turn {
+            seat: state.turn().seat,
+            phase: state.phase(),
+        }
+    }
+
+    /// Tells `event` to every player seated and connected and to every
+    /// spectator, and to `report` too.
+    fn broadcast(
+        &self,
+        connections: &HashMap<usize, Connection>,
+        report: &mut impl FnMut(&RoomInfo, &Game, &ServerEvent),
+        event: ServerEvent,
+    ) {
+        report(&self.info(), &self.game, &event);
+        let message = ServerMessage::Event(event);
+        let players = self.seated.iter().filter_map(|p| p.connection);
+        for id in players.chain(self.spectators.iter().copied()) {
+            if let Some(connection) = connections.get(&id) {
+                let _ = connection.outgoing.send(message.clone());
+            }
+        }
+    }
+}
+
+/// Carries the messages of connection `id` over `stream`: the frames
+/// coming in to `events` and the messages going out from `outgoing`,
+/// until either side hangs up.
+async fn connect(
+    id: usize,
+    stream: TcpStream,
+    events: UnboundedSender<(usize, Incoming)>,
+    mut outgoing: UnboundedReceiver<ServerMessage>,
+) {
+    let (mut reader, mut writer) = stream.into_split();
+    // Reading a frame is not to be cut short by a message going out, so
+    // reading and writing go on side by side rather than taking turns.
+    let reading = async {
+        loop {
+            let event = read(&mut reader).await;
+            let closed = matches!(event, Incoming::Closed);
+            if events.send((id, event)).is_err() || closed {
+                return;
+            }
+        }
+    };
+    let writing = async {
+        while let Some(message) = outgoing.recv().await {
+            // Messages too long to send hang up like a broken stream.
+            let Ok(frame) = protocol::frame(&message) else {
+                break;
+            };
+            if writer.write_all(&frame).await.is_err() {
+                break;
+            }
+        }
+    };
+    tokio::select! {
+        () = reading => {}
+        () = writing => {
+            let _ = events.send((id, Incoming::Closed));
+        }
+    }
+}
+
+/// The next frame of `reader`, closing the connection once the stream
+/// ends. Frames too long to take are skipped and refused.
+async fn read(reader: &mut (impl AsyncRead + Unpin)) -> Incoming {
+    let mut header = [0; 4];
+    if reader.read_exact(&mut header).await.is_err() {
+        return Incoming::Closed;
+    }
+    let length = match protocol::length(header) {
+        Ok(length) => length,
+        Err(error) => {
+            let skipped = u32::from_be_bytes(header).into();
+            let (mut frame, mut sink) =
+                (reader.take(skipped), tokio::io::sink());
+            return match tokio::io::copy(&mut frame, &mut sink).await {
+                Ok(read) if read == skipped => {
+                    Incoming::Malformed(error.to_string())
+                }
+                _ => Incoming::Closed,
+            };
+        }
+    };
+    let mut json = vec![0; length];
+    if reader.read_exact(&mut json).await.is_err() {
+        return Incoming::Closed;
+    }
+    Incoming::parse(&json)
+}
+
+/// Carries the messages of connection `id` like [`connect`], over a
+/// WebSocket opened on `stream`, one message a text frame. Messages are
+/// held to [`MAX_FRAME`] as frames are, the connection hanging up once
+/// one too long coming in is refused.
+async fn connect_websocket(
+    id: usize,
+    stream: TcpStream,
+    events: UnboundedSender<(usize, Incoming)>,
+    mut outgoing: UnboundedReceiver<ServerMessage>,
+) {
+    let config = WebSocketConfig::default()
+        .max_message_size(Some(MAX_FRAME))
+        .max_frame_size(Some(MAX_FRAME));
+    let accepting =
+        tokio_tungstenite::accept_async_with_config(stream, Some(config));
+    let Ok(socket) = accepting.await else {
+        let _ = events.send((id, Incoming::Closed));
+        return;
+    };
+    let (mut sink, mut frames) = socket.split();
+    // Whether a message too long was refused. The rest of it cannot be
+    // told from the next, so nothing more is read.
+    let mut refused = false;
+    loop {
+        tokio::select! {
+            frame = frames.next(), if !refused => {
+                let event = match frame {
+                    Some(Ok(Message::Text(text))) => {
+                        Incoming::parse(text.as_bytes())
+                    }
+                    Some(Ok(Message::Binary(_))) => {
+                        Incoming::Malformed("binary frame".into())
+                    }
+                    Some(Err(Error::Capacity(error))) => {
+                        refused = true;
+                        Incoming::Malformed(error.to_string())
+                    }
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => {
+                        Incoming::Closed
+                    }
+                    // Pings are answered by the socket itself.
+                    Some(Ok(_)) => continue,
+                };
+                let closed = matches!(event, Incoming::Closed);
+                if events.send((id, event)).is_err() || closed {
+                    return;
+                }
+            }
+            message = outgoing.recv() => {
+                let Some(message) = message else {
+                    let _ = sink.close().await;
+                    return;
+                };
+                let rejected = matches!(
+                    message,
+                    ServerMessage::Response(ServerResponse::Rejected { .. })
+                );
+                let text = serde_json::to_string(&message);
+                let text = text.expect("messages serialize");
+                // Messages too long to send hang up like a broken socket.
+                if text.len() > MAX_FRAME
+                    || sink.send(Message::text(text)).await.is_err()
+                    || (refused && rejected)
+                {
+                    let _ = sink.close().await;
+                    let _ = events.send((id, Incoming::Closed));
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// The next connection of `listener`, never if there is none.
+async fn accept(
+    listener: Option<&TcpListener>,
+) -> io::Result<(TcpStream, SocketAddr)> {
+    match listener {
+        Some(listener) => listener.accept().await,
+        None => future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Report = fn(&RoomInfo, &Game, &ServerEvent);
+
+    /// A host with connections 0 and 1 seated in the started game of room
+    /// 1 for two, and what each was sent.
+    fn started() -> (Host<Report>, Vec<UnboundedReceiver<ServerMessage>>) {
+        let (timers, _) = mpsc::unbounded_channel();
+        let mut host = Host {
+            connections: HashMap::new(),
+            rooms: BTreeMap::new(),
+            next_room: 1,
+            grace: GRACE,
+            substitute: None,
+            timers,
+            report: (|_, _, _| {}) as Report,
+        };
+        let mut receivers = vec![];
+        for id in 0..2 {
+            let (outgoing, receiver) = mpsc::unbounded_channel();
+            let connection = Connection {
+                outgoing,
+                name: None,
+                room: None,
+                watching: false,
+                chatted: VecDeque::new(),
+            };
+            host.connections.insert(id, connection);
+            receivers.push(receiver);
+            let name = format!("player {id}");
+            host.receive(
+                id,
+                ClientRequest::Hello {
+                    version: VERSION,
+                    name,
+                },
+            );
+        }
+        host.receive(
+            0,
+            ClientRequest::CreateRoom {
+                name: "test".into(),
+                players: 2,
+                rules: RuleSet::default(),
+                password: None,
+            },
+        );
+        let join = ClientRequest::JoinRoom {
+            room: 1,
+            password: None,
+        };
+        host.receive(1, join);
+        assert!(host.rooms[&1].started);
+        for receiver in &mut receivers {
+            while receiver.try_recv().is_ok() {}
+        }
+        (host, receivers)
+    }
+
+    /// Checks that `event` from connection `id` is rejected, room 1
+    /// keeping its game.
+    fn assert_rejected(
+        host: &mut Host<Report>,
+        receivers: &mut [UnboundedReceiver<ServerMessage>],
+        id: usize,
+        event: Incoming,
+    ) {
+        let before = host.rooms[&1].game.state().clone();
+        host.handle(id, event);
+        let after = host.rooms[&1].game.state();
+        assert_eq!(after.checksum(), before.checksum());
+        assert_eq!(after.phase(), before.phase());
+        let sent: Vec<_> =
+            std::iter::from_fn(|| receivers[id].try_recv().ok()).collect();
+        assert!(matches!(
+            sent.as_slice(),
+            [ServerMessage::Response(ServerResponse::Rejected { .. })]
+        ));
+    }
+
+    /// Frames `json` as a client sends it.
+    fn frame(json: &[u8]) -> Vec<u8> {
+        let mut frame = (json.len() as u32).to_be_bytes().to_vec();
+        frame.extend_from_slice(json);
+        frame
+    }
+
+    /// Connection of the player of room 1 whose turn it is, or of one
+    /// waiting for it when not `moving`.
+    fn player(host: &Host<Report>, moving: bool) -> usize {
+        let room = &host.rooms[&1];
+        let seat = room.game.state().turn().seat;
+        let seated = room.seated.iter();
+        seated
+            .filter(|p| (p.seat == seat) == moving)
+            .find_map(|p| p.connection)
+            .expect("both players are connected")
+    }
+
+    #[test]
+    fn moves_out_of_turn_are_rejected() {
+        let (mut host, mut receivers) = started();
+        let waiting = player(&host, false);
+        let roll = ClientRequest::Roll { seq: Some(1) };
+        let event = Incoming::Message(roll);
+        assert_rejected(&mut host, &mut receivers, waiting, event);
+    }
+
+    #[test]
+    fn throws_from_clients_are_rejected() {
+        let (mut host, mut receivers) = started();
+        let moving = player(&host, true);
+        let play = ClientRequest::Play {
+            mv: Move::Roll(6),
+            seq: None,
+        };
+        let event = Incoming::Message(play);
+        assert_rejected(&mut host, &mut receivers, moving, event);
+    }
+
+    #[test]
+    fn illegal_moves_are_rejected() {
+        let (mut host, mut receivers) = started();
+        let moving = player(&host, true);
+        let game = &mut host.rooms.get_mut(&1).unwrap().game;
+        // No plane launches with a one, nor is any on the track.
+        game.play(Move::Roll(1)).unwrap();
+        for mv in [Move::Launch { plane: 0 }, Move::Advance { plane: 0 }] {
+            let play = ClientRequest::Play { mv, seq: None };
+            let event = Incoming::Message(play);
+            assert_rejected(&mut host, &mut receivers, moving, event);
+        }
+    }
+
+    #[test]
+    fn players_saying_hello_again_keep_their_seat() {
+        let (mut host, mut receivers) = started();
+        let hello = ClientRequest::Hello {
+            version: VERSION + 1,
+            name: "again".into(),
+        };
+        let event = Incoming::Message(hello);
+        assert_rejected(&mut host, &mut receivers, 0, event);
+        assert!(host.connections.contains_key(&0));
+        let seated = &host.rooms[&1].seated;
+        assert!(seated.iter().any(|p| p.connection == Some(0)));
+    }
+
+    #[tokio::test]
+    async fn garbage_frames_are_rejected() {
+        let (mut host, mut receivers) = started();
+        let frame = frame(b"{\"type\": \"fly\"}");
+        let event = read(&mut frame.as_slice()).await;
+        assert!(matches!(event, Incoming::Malformed(_)));
+        assert_rejected(&mut host, &mut receivers, 0, event);
+    }
+
+    #[tokio::test]
+    async fn oversize_frames_are_skipped_and_rejected() {
+        let (mut host, mut receivers) = started();
+        let mut stream = frame(&vec![b' '; MAX_FRAME + 1]);
+        stream.extend(frame(b"{\"type\": \"list_rooms\"}"));
+        let mut reader = stream.as_slice();
+        let event = read(&mut reader).await;
+        assert!(matches!(event, Incoming::Malformed(_)));
+        assert_rejected(&mut host, &mut receivers, 0, event);
+        let next = read(&mut reader).await;
+        assert!(matches!(next, Incoming::Message(ClientRequest::ListRooms)));
+    }
+
+    #[tokio::test]
+    async fn oversize_websocket_messages_are_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (events, mut incoming) = mpsc::unbounded_channel();
+        let (outgoing, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            connect_websocket(0, stream, events, receiver).await;
+        });
+        let stream = TcpStream::connect(address).await.unwrap();
+        let url = format!("ws://{address}");
+        let (mut client, _) =
+            tokio_tungstenite::client_async(url, stream).await.unwrap();
+
+        let text = " ".repeat(MAX_FRAME + 1);
+        client.send(Message::text(text)).await.unwrap();
+        let (_, event) = incoming.recv().await.unwrap();
+        assert!(matches!(event, Incoming::Malformed(_)));
+        let reason = "too long".into();
+        let rejected = ServerResponse::Rejected { reason, seq: None };
+        outgoing.send(rejected.into()).unwrap();
+        let answer = client.next().await.unwrap().unwrap();
+        assert!(answer.into_text().unwrap().contains("rejected"));
+        let (_, event) = incoming.recv().await.unwrap();
+        assert!(matches!(event, Incoming::Closed));
+    }
+}
@@ -0,0 +1,163 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, ensure};
+use rayon::ThreadPoolBuilder;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::ai;
+use crate::board::Board;
+use crate::dice::Dice;
+use crate::game::Game;
+use crate::rules::{GameState, RuleSet};
+use crate::stats::Stats;
+
+/// Games between computer players, played out without drawing them to
+/// compare players and rules.
+pub struct Simulation {
+    pub board: Board,
+    pub rules: RuleSet,
+    /// Names of the players as [`ai::named`] takes them, one per seat.
+    /// They take turns going first, each game seating them one seat
+    /// further round.
+    pub players: Vec<String>,
+    pub games: u64,
+    /// Time each player may think about a move at most, the time they
+    /// take themselves with [`Duration::MAX`].
+    pub budget: Duration,
+    /// Seed of the first game, each next one adding one. The dice and the
+    /// players of a game are seeded from it.
+    pub seed: u64,
+}
+
+/// What a [`Simulation`] came to.
+#[derive(Clone, Debug, Serialize)]
+pub struct Summary {
+    pub players: Vec<String>,
+    pub games: u64,
+    /// Games won by each player, in the order they were given.
+    pub wins: Vec<u64>,
+    pub plies: u64,
+    /// Totals over every game, by seat.
+    pub stats: Stats,
+}
+
+impl Simulation {
+    /// Plays every game, sharing them out between `jobs` threads, or one
+    /// per core for 0. Each game is seeded on its own, so the summary is
+    /// the same however many threads play.
+    pub fn run(&self, jobs: usize) -> Result<Summary> {
+        let seated = GameState::new(&self.board).sides().len();
+        ensure!(
+            self.players.len() == seated,
+            "{} computer players for a board of {} players",
+            self.players.len(),
+            self.board.players()
+        );
+        let pool = ThreadPoolBuilder::new().num_threads(jobs).build()?;
+        let games = pool.install(|| {
+            (0..self.games)
+                .into_par_iter()
+                .map(|index| self.play(index))
+                .collect::<Result<Vec<_>>>()
+        })?;
+        let mut summary = Summary {
+            players: self.players.clone(),
+            games: self.games,
+            wins: vec![0; self.players.len()],
+            plies: 0,
+            stats: Stats::default(),
+        };
+        for played in games {
+            if let Some(winner) = played.winner {
+                summary.wins[winner] += 1;
+            }
+            summary.plies += played.plies;
+            summary.stats.merge(&played.stats);
+        }
+        Ok(summary)
+    }
+
+    /// Plays game `index` of the simulation.
+    fn play(&self, index: u64) -> Result<Played> {
+        let seed = self.seed.wrapping_add(index);
+        let count = self.players.len();
+        // Player taking each side, rotating so everyone goes first.
+        let order: Vec<usize> = (0..count)
+            .map(|side| (side + index as usize) % count)
+            .collect();
+        let mut players = order
+            .iter()
+            .map(|&player| {
+                ai::named(&self.players[player], player_seed(seed, player))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let stats = Rc::new(RefCell::new(Stats::default()));
+        stats.borrow_mut().begin_game();
+        let mut game = Game::new(self.board.clone(), self.rules, Some(seed));
+        game.observe(stats.clone());
+        let mut dice = Dice::seeded(self.rules.dice, seed)?;
+        game.play_out(&mut dice, &mut players, self.budget)
+            .with_context(|| format!("game {} failed", index + 1))?;
+        let state = game.state();
+        let winner = state.winner().map(|winner| {
+            let side = state.sides().iter().position(|s| s.seat == winner);
+            order[side.expect("winners sit")]
+        });
+        Ok(Played {
+            winner,
+            plies: game.record().plies.len() as u64,
+            stats: stats.take(),
+        })
+    }
+}
+
+/// How one game of a simulation went.
+struct Played {
+    /// Index of the winning player.
+    winner: Option<usize>,
+    plies: u64,
+    stats: Stats,
+}
+
+/// Seed of `player` in the game of `seed`, different for every player.
+fn player_seed(seed: u64, player: usize) -> u64 {
+    seed ^ (player as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+impl Summary {
+    /// The wins of every player and a few totals as an aligned table.
+    pub fn table(&self) -> String {
+        let names: Vec<String> = (self.players.iter().enumerate())
+            .map(|(index, name)| format!("p{} {name}", index + 1))
+            .collect();
+        let width = names.iter().map(String::len).max().unwrap_or(0);
+        let width = width.max("player".len());
+        let mut table =
+            format!("{:<width$}  {:>6}  {:>6}\n", "player", "wins", "rate");
+        for (name, wins) in names.iter().zip(&self.wins) {
+            let rate = 100.0 * *wins as f64 / self.games.max(1) as f64;
+            table += &format!("{name:<width$}  {wins:>6}  {rate:>5.1}%\n");
+        }
+        let games = self.games.max(1) as f64;
+        table += &format!(
+            "\n{} games, {:.1} plies, {:.1} captures and {:.1} penalties \
+             a game",
+            self.games,
+            self.plies as f64 / games,
+            self.stats.captures as f64 / games,
+            self.stats.penalties as f64 / games,
+        );
+        if let Some(moves) = self.stats.average_winning_moves() {
+            table += &format!(", {moves:.1} moves to win");
+        }
+        table += "\n";
+        table
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::observe::{GameEvent, Observer};
+use crate::record::GameRecord;
+use crate::replay::Replay;
+use crate::rules::{GameState, Move, Position};
+
+/// Totals over any number of games, fed one move at a time by
+/// [`observe`](Stats::observe), as an [`Observer`] of a game, or a whole
+/// record at a time by [`add_record`](Stats::add_record).
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub games: u64,
+    /// Games someone won, having brought every plane home first.
+    pub won: u64,
+    /// Games won by each seat.
+    pub wins: Vec<u64>,
+    pub throws: u64,
+    /// Throws showing the top total, a six on the classic die.
+    pub top_throws: u64,
+    /// Third top throws in a row, which lost the turn.
+    pub penalties: u64,
+    pub launches: u64,
+    pub advances: u64,
+    pub passes: u64,
+    /// Moves capturing planes, however many.
+    pub captures: u64,
+    /// Planes sent back to their hangars.
+    pub planes_captured: u64,
+    pub jumps: u64,
+    /// Shortcuts flown.
+    pub flights: u64,
+    /// Launches and advances of every winner in the games they won.
+    pub winning_moves: u64,
+    /// Launches and advances by seat in the game being observed.
+    #[serde(skip)]
+    moves: Vec<u64>,
+}
+
+impl Stats {
+    /// Starts counting another game.
+    pub fn begin_game(&mut self) {
+        self.games += 1;
+        self.moves.clear();
+    }
+
+    /// Counts `mv`, played from `before` to `after`.
+    pub fn observe(&mut self, before: &GameState, mv: Move, after: &GameState) {
+        for event in GameEvent::between(before, mv, after) {
+            self.notify(&event, after);
+        }
+    }
+
+    /// Counts every move of a recorded game.
+    pub fn add_record(&mut self, record: &GameRecord) -> Result<()> {
+        self.begin_game();
+        let mut replay = Replay::new(record)?;
+        loop {
+            let before = replay.state().clone();
+            let Some(frame) = replay.next() else {
+                return Ok(());
+            };
+            self.observe(&before, frame.mv, &frame.state);
+        }
+    }
+
+    /// Adds the totals of `other`, counted over other games.
+    pub fn merge(&mut self, other: &Stats) {
+        self.games += other.games;
+        self.won += other.won;
+        for (seat, &wins) in other.wins.iter().enumerate() {
+            *grown(&mut self.wins, seat) += wins;
+        }
+        self.throws += other.throws;
+        self.top_throws += other.top_throws;
+        self.penalties += other.penalties;
+        self.launches += other.launches;
+        self.advances += other.advances;
+        self.passes += other.passes;
+        self.captures += other.captures;
+        self.planes_captured += other.planes_captured;
+        self.jumps += other.jumps;
+        self.flights += other.flights;
+        self.winning_moves += other.winning_moves;
+    }
+
+    /// Launches and advances the winners took on average to win.
+    pub fn average_winning_moves(&self) -> Option<f64> {
+        (self.won > 0).then(|| self.winning_moves as f64 / self.won as f64)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl Observer for Stats {
+    fn notify(&mut self, event: &GameEvent, state: &GameState) {
+        match *event {
+            GameEvent::DiceRolled { total, .. } => {
+                self.throws += 1;
+                if state.rules().dice.is_top(total) {
+                    self.top_throws += 1;
+                }
+            }
+            GameEvent::PieceMoved { seat, from, .. } => {
+                if from == Position::Hangar {
+                    self.launches += 1;
+                } else {
+                    self.advances += 1;
+                }
+                *grown(&mut self.moves, seat) += 1;
+            }
+            GameEvent::Passed { .. } => self.passes += 1,
+            GameEvent::Penalized { .. } => self.penalties += 1,
+            GameEvent::Captured { ref planes, .. } => {
+                self.captures += 1;
+                self.planes_captured += planes.len() as u64;
+            }
+            GameEvent::Jumped { .. } => self.jumps += 1,
+            GameEvent::Flew { .. } => self.flights += 1,
+            GameEvent::Finished { seat, place: 1 } => {
+                self.won += 1;
+                *grown(&mut self.wins, seat) += 1;
+                self.winning_moves +=
+                    self.moves.get(seat).copied().unwrap_or(0);
+            }
+            GameEvent::Finished { .. }
+            | GameEvent::TurnChanged { .. }
+            | GameEvent::Resigned { .. } => {}
+        }
+    }
+}
+
+/// The counter of `seat`, adding seats up to it.
+fn grown(counts: &mut Vec<u64>, seat: usize) -> &mut u64 {
+    if counts.len() <= seat {
+        counts.resize(seat + 1, 0);
+    }
+    &mut counts[seat]
+}
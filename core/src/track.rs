@@ -0,0 +1,228 @@
+use kurbo::{Affine, Line, ParamCurveNearest, Point};
+use serde::{Deserialize, Serialize};
+
+use crate::board::{Board, Player};
+
+/// Stable index of a walkable cell in a [`Track`].
+pub type CellId = usize;
+
+/// Where a walkable spot of the track is drawn.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Spot {
+    /// Cell `index` of the layout in `seat`'s quadrant.
+    Ring { seat: usize, index: usize },
+    /// Spot `step` of the runway leading `seat` home, outermost first.
+    Runway { seat: usize, step: usize },
+}
+
+/// A walkable spot of the track.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct TrackCell {
+    pub spot: Spot,
+    /// Center of the spot in board pixels, where plane tokens go.
+    pub center: Point,
+    /// Index into [`Player::COLOR_NAMES`] of the color owning the spot.
+    pub color: usize,
+    /// Next spot in the direction of travel, `None` at the end of a runway.
+    pub next: Option<CellId>,
+    /// First runway spot, for the ring cell where its owner turns home.
+    pub branch: Option<CellId>,
+    /// Ring cell a shortcut from this cell jumps to.
+    pub shortcut: Option<CellId>,
+}
+
+/// The walkable cells of a board with stable indices: the outer ring in
+/// clockwise order starting at seat 0, followed by the runways in seat
+/// order.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Track {
+    cells: Vec<TrackCell>,
+    ring: usize,
+    /// Ring index where planes of each seat enter the track.
+    launches: Vec<CellId>,
+    /// Radius of the center spots, which shortcuts cross.
+    radius: f64,
+}
+
+impl Track {
+    pub fn new(board: &Board) -> Self {
+        let seats = board.seats();
+        // Every seat has a color of its own.
+        let colors = seats.len();
+        let mut cells = vec![];
+        for (seat, affine) in seats.iter().enumerate() {
+            for (index, cell) in board.cells(seat).into_iter().enumerate() {
+                cells.push(TrackCell {
+                    spot: Spot::Ring { seat, index },
+                    center: *affine * cell.center(),
+                    color: cell.color % colors,
+                    next: None,
+                    branch: None,
+                    shortcut: None,
+                });
+            }
+        }
+        let ring = cells.len();
+        for (index, cell) in cells.iter_mut().enumerate() {
+            cell.next = Some((index + 1) % ring);
+        }
+        for seat in 0..seats.len() {
+            for shortcut in board.layout().shortcuts() {
+                let spot = Spot::Ring {
+                    seat,
+                    index: shortcut.from,
+                };
+                if let Some(from) = cells.iter().position(|c| c.spot == spot) {
+                    cells[from].shortcut = Some((from + shortcut.ahead) % ring);
+                }
+            }
+        }
+
+        let mut launches = vec![];
+        let runways = board.runways();
+        for (seat, affine) in seats.iter().enumerate() {
+            let spots = Player::runway_spots(board.metrics());
+            let runway = runways[seat];
+            // The runway starts next to the ring cell its owner turns at.
+            let ring_cells = || cells[..ring].iter().enumerate();
+            let entry = nearest(ring_cells(), &seats, runway * spots[0])
+                .expect("the track has cells");
+            cells[entry].branch = Some(cells.len());
+            for (step, center) in spots.into_iter().enumerate() {
+                let next = cells.len() + 1;
+                cells.push(TrackCell {
+                    spot: Spot::Runway { seat, step },
+                    center: runway * center,
+                    color: seat,
+                    next: Some(next).filter(|_| step + 1 < spots.len()),
+                    branch: None,
+                    shortcut: None,
+                });
+            }
+            // Planes take off from the base onto the closest ring cell of
+            // their color, or the closest cell when none has it.
+            let base = *affine * board.metrics().base().center();
+            let ring_cells = || cells[..ring].iter().enumerate();
+            let owned = ring_cells().filter(|(_, cell)| cell.color == seat);
+            let launch = nearest(owned, &seats, base)
+                .or_else(|| nearest(ring_cells(), &seats, base))
+                .expect("the track has cells");
+            launches.push(launch);
+        }
+        Self {
+            cells,
+            ring,
+            launches,
+            radius: board.metrics().cell_radius(),
+        }
+    }
+
+    pub fn cells(&self) -> &[TrackCell] {
+        &self.cells
+    }
+
+    pub fn get(&self, index: CellId) -> Option<&TrackCell> {
+        self.cells.get(index)
+    }
+
+    /// Index of the cell drawn at `spot`.
+    pub fn index(&self, spot: Spot) -> Option<CellId> {
+        self.cells.iter().position(|cell| cell.spot == spot)
+    }
+
+    /// Number of cells on the outer ring, which come first.
+    pub fn ring_len(&self) -> usize {
+        self.ring
+    }
+
+    /// Number of colors on the track, one for each seat.
+    pub fn colors(&self) -> usize {
+        self.launches.len()
+    }
+
+    /// Ring cell planes of `seat` enter the track on.
+    pub fn launch(&self, seat: usize) -> CellId {
+        self.launches[seat]
+    }
+
+    /// Last runway spot of `seat`, where its planes finish.
+    pub fn goal(&self, seat: usize) -> CellId {
+        self.ring + (seat + 1) * Player::RUNWAY_SPOTS - 1
+    }
+
+    /// Spots a plane of `seat` on `index` has left to fly to its goal,
+    /// not counting jumps and shortcuts.
+    pub fn distance(&self, index: CellId, seat: usize) -> usize {
+        let goal = self.goal(seat);
+        let mut cell = index;
+        let mut steps = 0;
+        while cell != goal && steps < self.cells.len() {
+            match self.successor(cell, seat) {
+                Some(next) => cell = next,
+                None => break,
+            }
+            steps += 1;
+        }
+        steps
+    }
+
+    /// Straight flight of the shortcut from `index` between the centers
+    /// of its ends.
+    pub fn shortcut_path(&self, index: CellId) -> Option<Line> {
+        let cell = self.cells.get(index)?;
+        let target = self.cells.get(cell.shortcut?)?;
+        Some(Line::new(cell.center, target.center))
+    }
+
+    /// Cells the shortcut from `index` flies over, other than its ends,
+    /// as the cells whose center spot it crosses.
+    pub fn crossings(&self, index: CellId) -> Vec<CellId> {
+        let Some(path) = self.shortcut_path(index) else {
+            return vec![];
+        };
+        let ends = [Some(index), self.cells[index].shortcut];
+        (0..self.cells.len())
+            .filter(|cell| !ends.contains(&Some(*cell)))
+            .filter(|&cell| {
+                let nearest = path.nearest(self.cells[cell].center, 1e-9);
+                nearest.distance_sq.sqrt() <= self.radius
+            })
+            .collect()
+    }
+
+    /// Spot after `index` for a plane of `seat`, which turns onto its own
+    /// runway instead of going around the ring again.
+    pub fn successor(&self, index: CellId, seat: usize) -> Option<CellId> {
+        let cell = self.cells.get(index)?;
+        match cell.branch {
+            Some(runway)
+                if self.cells[runway].spot
+                    == (Spot::Runway { seat, step: 0 }) =>
+            {
+                Some(runway)
+            }
+            _ => cell.next,
+        }
+    }
+}
+
+/// Index of the ring cell of `cells` centered closest to `point`,
+/// measured in the quadrant the cell is laid out in, which the hexagonal
+/// board shears.
+fn nearest<'a>(
+    cells: impl Iterator<Item = (CellId, &'a TrackCell)>,
+    seats: &[Affine],
+    point: Point,
+) -> Option<CellId> {
+    let distance = |cell: &TrackCell| {
+        let Spot::Ring { seat, .. } = cell.spot else {
+            unreachable!("only ring cells are searched");
+        };
+        let inverse = seats[seat].inverse();
+        (inverse * cell.center).distance(inverse * point)
+    };
+    cells
+        .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+        .map(|(index, _)| index)
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use anyhow::{Result, ensure};
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+use rayon::ThreadPoolBuilder;
+use rayon::prelude::*;
+
+use crate::ai::AiPlayer;
+use crate::board::Board;
+use crate::dice::Dice;
+use crate::game::Game;
+use crate::heuristic::{HeuristicPlayer, Weights};
+use crate::rules::{GameState, RuleSet};
+
+/// Searches for better heuristic [`Weights`] by self-play: every
+/// generation mutates the best weights so far a few times and lets each
+/// mutant play the best weights in every other seat. The mutant winning
+/// most takes over if it won more than its share of the games.
+pub struct Tuning {
+    pub board: Board,
+    pub rules: RuleSet,
+    pub generations: u32,
+    /// Mutants tried each generation.
+    pub candidates: usize,
+    /// Games each mutant plays, taking every seat in turn. The mutants of
+    /// a generation are thrown the same dice.
+    pub games: u64,
+    /// How far mutants stray: each weight is scaled by up to `e` to this
+    /// power, up or down.
+    pub spread: f64,
+    pub seed: u64,
+}
+
+/// How a generation of a [`Tuning`] went.
+#[derive(Clone, Debug)]
+pub struct Generation {
+    pub number: u32,
+    /// Share of its games the best mutant won.
+    pub rate: f64,
+    /// Share a player as strong as the best weights would win.
+    pub par: f64,
+    /// Whether the best mutant took over.
+    pub improved: bool,
+    /// The best weights after the generation.
+    pub weights: Weights,
+}
+
+impl Tuning {
+    /// Tunes `start`, playing the games on `jobs` threads or one per core
+    /// for 0 and telling `report` how each generation went. Returns the
+    /// best weights found.
+    pub fn run(
+        &self,
+        start: Weights,
+        jobs: usize,
+        mut report: impl FnMut(&Generation),
+    ) -> Result<Weights> {
+        let sides = GameState::new(&self.board).sides().len();
+        ensure!(self.games > 0, "tuning needs games to play");
+        let pool = ThreadPoolBuilder::new().num_threads(jobs).build()?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut best = start;
+        for number in 1..=self.generations {
+            let mutants: Vec<Weights> = (0..self.candidates)
+                .map(|_| mutate(&best, self.spread, &mut rng))
+                .collect();
+            let seed = self.seed.wrapping_add(u64::from(number) * self.games);
+            let won: Vec<bool> = pool.install(|| {
+                (0..mutants.len() as u64 * self.games)
+                    .into_par_iter()
+                    .map(|index| {
+                        let mutant = &mutants[(index / self.games) as usize];
+                        let game = index % self.games;
+                        self.play(mutant, &best, seed.wrapping_add(game), game)
+                    })
+                    .collect::<Result<_>>()
+            })?;
+            let par = 1.0 / sides as f64;
+            let (index, rate) = won
+                .chunks(self.games as usize)
+                .map(|games| {
+                    let wins = games.iter().filter(|&&won| won).count();
+                    wins as f64 / games.len() as f64
+                })
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap_or((0, 0.0));
+            let improved = rate > par;
+            if improved {
+                best = mutants[index];
+            }
+            report(&Generation {
+                number,
+                rate,
+                par,
+                improved,
+                weights: best,
+            });
+        }
+        Ok(best)
+    }
+
+    /// Plays a game seeded with `seed` between `mutant`, in side `index`
+    /// counting round the board, and `best` in every other side. Returns
+    /// whether the mutant won.
+    fn play(
+        &self,
+        mutant: &Weights,
+        best: &Weights,
+        seed: u64,
+        index: u64,
+    ) -> Result<bool> {
+        let mut game = Game::new(self.board.clone(), self.rules, Some(seed));
+        let sides = game.state().sides().len();
+        let side = (index % sides as u64) as usize;
+        let mut players: Vec<Box<dyn AiPlayer>> = (0..sides)
+            .map(|index| {
+                let weights = if index == side { *mutant } else { *best };
+                Box::new(HeuristicPlayer::new(weights)) as Box<dyn AiPlayer>
+            })
+            .collect();
+        let mut dice = Dice::seeded(self.rules.dice, seed)?;
+        game.play_out(&mut dice, &mut players, Duration::MAX)?;
+        let seat = game.state().sides()[side].seat;
+        Ok(game.state().winner() == Some(seat))
+    }
+}
+
+/// `weights` with each scaled by a random factor, `opponents` kept
+/// between 0 and 1.
+fn mutate(weights: &Weights, spread: f64, rng: &mut StdRng) -> Weights {
+    let mut scale =
+        |weight: f64| weight * rng.random_range(-spread..=spread).exp();
+    Weights {
+        progress: scale(weights.progress),
+        launched: scale(weights.launched),
+        home: scale(weights.home),
+        capture: scale(weights.capture),
+        jump: scale(weights.jump),
+        shortcut: scale(weights.shortcut),
+        exposure: scale(weights.exposure),
+        opponents: scale(weights.opponents).clamp(0.0, 1.0),
+    }
+}
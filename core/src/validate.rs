@@ -0,0 +1,229 @@
+use std::error::Error;
+use std::fmt;
+
+use kurbo::{Point, Vec2};
+
+use crate::board::{Board, BoardShape, Player};
+use crate::layout::Layout;
+use crate::track::{CellId, Spot};
+
+/// A way in which a board cannot be played on.
+#[derive(Clone, PartialEq, Debug)]
+pub enum BoardError {
+    /// Ring cell `cell` does not touch `next`, so the ring is not closed.
+    Gap { cell: CellId, next: CellId },
+    /// Planes of `color` enter the track on `found` distinct cells of their
+    /// color instead of `expected`.
+    Launches {
+        color: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Planes of `color` turn home from `found` distinct cells of their
+    /// color instead of `expected`.
+    Landings {
+        color: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Two ring cells cover the same area.
+    Overlap { first: CellId, second: CellId },
+    /// The shortcut from `cell` lands on `target`, which has another color.
+    Shortcut { cell: CellId, target: CellId },
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::Gap { cell, next } => {
+                write!(f, "ring cell {cell} does not touch cell {next}")
+            }
+            BoardError::Launches {
+                color,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} planes launch from {found} cells, expected {expected}",
+                color_name(*color)
+            ),
+            BoardError::Landings {
+                color,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} planes turn home from {found} cells, expected {expected}",
+                color_name(*color)
+            ),
+            BoardError::Overlap { first, second } => {
+                write!(f, "cells {first} and {second} overlap")
+            }
+            BoardError::Shortcut { cell, target } => write!(
+                f,
+                "the shortcut from cell {cell} lands on cell {target} of \
+                 another color"
+            ),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+fn color_name(color: usize) -> &'static str {
+    Player::COLOR_NAMES[color % Player::COLOR_NAMES.len()]
+}
+
+impl Board {
+    /// Checks that the ring is closed, that every seat launches and lands
+    /// on one cell of its own color, that no two ring
+    /// cells overlap and that shortcuts join cells of one color. Returns
+    /// every violation found.
+    pub fn validate(&self) -> Result<(), Vec<BoardError>> {
+        let mut errors = vec![];
+        let track = self.track();
+        let ring = track.ring_len();
+        // Tolerates rounding in the seat transforms.
+        let epsilon = self.metrics().dim * 1e-6;
+
+        let seats = self.seats();
+        let polygons: Vec<Vec<Point>> = track.cells()[..ring]
+            .iter()
+            .map(|cell| {
+                let Spot::Ring { seat, index } = cell.spot else {
+                    unreachable!("the ring comes first");
+                };
+                let shape = self.cells(seat)[index];
+                shape
+                    .vertices()
+                    .into_iter()
+                    .map(|p| seats[seat] * p)
+                    .collect()
+            })
+            .collect();
+
+        for cell in 0..ring {
+            let next = (cell + 1) % ring;
+            let touches = polygons[cell].iter().any(|a| {
+                polygons[next].iter().any(|b| a.distance(*b) <= epsilon)
+            });
+            if !touches {
+                errors.push(BoardError::Gap { cell, next });
+            }
+        }
+
+        // Every seat owns the color of its own number.
+        for color in 0..track.colors() {
+            let launch = track.launch(color);
+            let found = usize::from(track.cells()[launch].color == color);
+            if found != 1 {
+                errors.push(BoardError::Launches {
+                    color,
+                    expected: 1,
+                    found,
+                });
+            }
+            let found = track.cells()[..ring]
+                .iter()
+                .filter(|cell| {
+                    cell.color == color
+                        && cell.branch.is_some_and(|runway| {
+                            track.cells()[runway].spot
+                                == (Spot::Runway {
+                                    seat: color,
+                                    step: 0,
+                                })
+                        })
+                })
+                .count();
+            if found != 1 {
+                errors.push(BoardError::Landings {
+                    color,
+                    expected: 1,
+                    found,
+                });
+            }
+        }
+
+        for first in 0..ring {
+            for second in first + 1..ring {
+                if overlap(&polygons[first], &polygons[second], epsilon) {
+                    errors.push(BoardError::Overlap { first, second });
+                }
+            }
+        }
+
+        for (cell, from) in track.cells()[..ring].iter().enumerate() {
+            if let Some(target) = from.shortcut
+                && track.cells()[target].color != from.color
+            {
+                errors.push(BoardError::Shortcut { cell, target });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Layout {
+    /// Validates the layout on the four-player square board it is authored
+    /// for.
+    pub fn validate(&self) -> Result<(), Vec<BoardError>> {
+        Board::with_shape(BoardShape::Square, 4, self.clone()).validate()
+    }
+}
+
+/// Whether two convex polygons share more than an edge, by looking for a
+/// separating axis among their edge normals.
+fn overlap(a: &[Point], b: &[Point], epsilon: f64) -> bool {
+    let normals = |polygon: &[Point]| -> Vec<Vec2> {
+        (0..polygon.len())
+            .map(|i| {
+                let edge = polygon[(i + 1) % polygon.len()] - polygon[i];
+                Vec2::new(-edge.y, edge.x).normalize()
+            })
+            .collect()
+    };
+    let project = |polygon: &[Point], axis: Vec2| {
+        polygon
+            .iter()
+            .map(|p| p.to_vec2().dot(axis))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                (min.min(x), max.max(x))
+            })
+    };
+    normals(a).into_iter().chain(normals(b)).all(|axis| {
+        let (a_min, a_max) = project(a, axis);
+        let (b_min, b_max) = project(b, axis);
+        a_max.min(b_max) - a_min.max(b_min) > epsilon
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_boards_validate() {
+        for players in Board::PLAYERS {
+            assert_eq!(Board::new(players).validate(), Ok(()), "{players}");
+        }
+    }
+
+    #[test]
+    fn hex_board_gives_every_seat_its_color() {
+        let board = Board::new(6);
+        let track = board.track();
+        assert_eq!(track.colors(), 6);
+        for seat in 0..6 {
+            let launch = track.cells()[track.launch(seat)];
+            assert_eq!(launch.spot, Spot::Ring { seat, index: 3 });
+            assert_eq!(launch.color, seat);
+            assert_eq!(track.cells()[track.goal(seat)].color, seat);
+        }
+    }
+}